@@ -4,9 +4,11 @@
 
 use cstr::cstr;
 use failure::{format_err, Error, ResultExt};
-use fidl_fuchsia_hardware_pty::{DeviceProxy, WindowSize};
-use fuchsia_async as fasync;
+use fidl_fuchsia_hardware_pty::{DeviceProxy, WindowSize, EVENT_HANGUP};
+use fuchsia_async::{self as fasync, TimeoutExt};
 use fuchsia_zircon::{self as zx, HandleBased, Task};
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use futures::stream::{self, Stream};
 use parking_lot::Mutex;
 use std::{
     ffi::CStr,
@@ -15,48 +17,265 @@ use std::{
     sync::Arc,
 };
 
+/// A signal that can be sent to the shell through the pty, as if a user had typed the
+/// corresponding control character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PtySignal {
+    /// Equivalent to a user typing `Ctrl-C`; requests that the foreground process group
+    /// interrupt (`SIGINT`).
+    Interrupt,
+    /// Equivalent to a user typing `Ctrl-Z`; requests that the foreground process group
+    /// suspend (`SIGTSTP`).
+    Suspend,
+}
+
+impl PtySignal {
+    /// The control character the terminal driver interprets as this signal.
+    fn as_control_char(self) -> u8 {
+        match self {
+            PtySignal::Interrupt => 0x03, // ^C
+            PtySignal::Suspend => 0x1a,   // ^Z
+        }
+    }
+}
+
+/// The result of a [`Pty::read_with_timeout`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadOutcome {
+    /// This many bytes of data were read into the caller's buffer.
+    Data(usize),
+    /// No data arrived from the pty before the timeout elapsed.
+    TimedOut,
+}
+
+/// The default size, in bytes, of the buffer `Pty`'s high-level read APIs (such as
+/// [`Pty::wait_for_sentinel`] and [`Pty::output_stream`]) use for each read from the shell.
+const DEFAULT_READ_BUFFER_SIZE: usize = 1024;
+
+/// The ptmx device [`Pty::new`] opens as the server side of the pty.
+const DEFAULT_PTMX_PATH: &str = "/dev/misc/ptmx";
+
+/// The maximum time, in seconds, [`Pty::shutdown`] waits for the shell to exit on its own after
+/// its pty is closed before falling back to killing it outright.
+const SHUTDOWN_TIMEOUT_SECONDS: i64 = 5;
+
+/// The maximum time, in seconds, a single read waits for more data while `spawn` is capturing
+/// the shell's initial output, before giving up on seeing any more of it.
+const INITIAL_OUTPUT_READ_TIMEOUT_SECONDS: i64 = 5;
+
 /// An object used for interacting with the shell
+///
+/// Note: `fuchsia.hardware.pty.Device` has no notion of job-control process
+/// groups; the only OOB signals it exposes are [`EVENT_HANGUP`], `EVENT_INTERRUPT`,
+/// and `EVENT_SUSPEND`, and `MakeActive` selects among *client PTYs*, not among
+/// process groups within a single shell session. Surfacing the shell's
+/// foreground process group (so a terminal can route signals directly to it
+/// rather than through the control characters [`Pty::send_signal`] writes)
+/// would require extending that protocol and its driver, which is beyond what
+/// this client wrapper can do on its own.
 pub struct Pty {
     // The server side file descriptor. This file is safe to clone.
     server_pty: File,
 
     // The running shell process. This object will remain None until after the shell is spawned.
     shell_process: Arc<Mutex<Option<zx::Process>>>,
+
+    // The job the shell process was spawned into, or None until the shell is spawned. For
+    // `spawn` and `reconnect` this is an invalid handle (the shell goes into the default job).
+    // Held onto so a job created by `spawn_detached` (and the shell process inside it) stays
+    // alive even if every other handle to it is dropped.
+    shell_job: Option<zx::Job>,
+
+    // The window size last used to spawn the shell, retained so `reconnect` can re-establish a
+    // client pty without the caller having to supply it again.
+    last_window_size: Option<WindowSize>,
+
+    // The size of the buffer used for each read performed by the high-level read APIs.
+    read_buffer_size: usize,
+
+    // The number of bytes of the shell's output `spawn` should capture for later retrieval via
+    // `initial_output`, or None if initial-output capture is disabled (the default).
+    initial_output_capture_bytes: Option<usize>,
+
+    // The bytes captured by the most recent `spawn`, per `initial_output_capture_bytes`.
+    initial_output: Vec<u8>,
 }
 
 impl Pty {
     /// Creates a new instance of the Pty which must later be spawned.
     pub fn new() -> Result<Self, Error> {
-        let server_pty = Pty::open_server_pty()?;
+        Pty::with_ptmx_path(DEFAULT_PTMX_PATH)
+    }
+
+    /// Like [`Pty::new`], but opens `ptmx_path` as the server side of the pty instead of
+    /// assuming the default ptmx device. Mainly useful for tests that want to exercise the
+    /// open failure path without a real ptmx device available.
+    pub fn with_ptmx_path(ptmx_path: &str) -> Result<Self, Error> {
+        let server_pty = Pty::open_server_pty(ptmx_path)?;
         let shell_process = Arc::new(Mutex::new(None));
 
-        Ok(Pty { server_pty, shell_process })
+        Ok(Pty {
+            server_pty,
+            shell_process,
+            shell_job: None,
+            last_window_size: None,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            initial_output_capture_bytes: None,
+            initial_output: Vec::new(),
+        })
+    }
+
+    /// Returns the size, in bytes, of the buffer used for each read performed by the high-level
+    /// read APIs (such as [`Pty::wait_for_sentinel`] and [`Pty::output_stream`]).
+    pub fn read_buffer_size(&self) -> usize {
+        self.read_buffer_size
+    }
+
+    /// Sets the size, in bytes, of the buffer used for each read performed by the high-level
+    /// read APIs. Larger buffers reduce the number of reads needed to drain a given amount of
+    /// shell output, at the cost of a larger allocation per read.
+    pub fn set_read_buffer_size(&mut self, read_buffer_size: usize) {
+        self.read_buffer_size = read_buffer_size;
+    }
+
+    /// Enables capturing up to `max_bytes` of the shell's output as part of the next `spawn`
+    /// (`spawn_detached`/`spawn_in_job` included), retrievable afterward via
+    /// [`Pty::initial_output`]. Disabled by default.
+    pub fn set_initial_output_capture(&mut self, max_bytes: usize) {
+        self.initial_output_capture_bytes = Some(max_bytes);
+    }
+
+    /// Returns the shell's output captured during the most recent `spawn`, if
+    /// [`Pty::set_initial_output_capture`] was called beforehand.
+    ///
+    /// Empty if capture was never enabled, or if the shell hadn't produced any output before the
+    /// capture gave up waiting for more.
+    pub fn initial_output(&self) -> &[u8] {
+        &self.initial_output
     }
 
     /// Spawns the Pty. The pty needs to have a valid window size before it can be spawned or the
     /// shell will not respond to any commands.
     pub async fn spawn(&mut self, window_size: WindowSize) -> Result<(), Error> {
+        await!(self.spawn_in_job_impl(window_size, zx::Job::from_handle(zx::Handle::invalid())))
+    }
+
+    /// Spawns the Pty's shell in a new job that is detached from the caller's own job.
+    ///
+    /// Because the shell lives in its own job rather than being a direct child of the caller's
+    /// job, the shell survives even if the caller (e.g. the component hosting this `Pty`)
+    /// restarts, allowing a future `Pty` to reconnect to it. The pty needs to have a valid
+    /// window size before it can be spawned or the shell will not respond to any commands.
+    pub async fn spawn_detached(&mut self, window_size: WindowSize) -> Result<(), Error> {
+        let job = fuchsia_runtime::job_default()
+            .create_child_job()
+            .context("unable to create detached job for shell")?;
+        await!(self.spawn_in_job_impl(window_size, job))
+    }
+
+    /// Spawns the Pty's shell into a caller-supplied `job`, for callers that want to manage the
+    /// shell's lifetime independently of the caller's own job (for example, giving each of
+    /// several shells its own job so they can be killed individually). The pty needs to have a
+    /// valid window size before it can be spawned or the shell will not respond to any commands.
+    pub async fn spawn_in_job(
+        &mut self,
+        window_size: WindowSize,
+        job: zx::Job,
+    ) -> Result<(), Error> {
+        await!(self.spawn_in_job_impl(window_size, job))
+    }
+
+    /// Shared implementation of `spawn`, `spawn_detached`, and `spawn_in_job`.
+    async fn spawn_in_job_impl(
+        &mut self,
+        window_size: WindowSize,
+        job: zx::Job,
+    ) -> Result<(), Error> {
         let spawn_fd = self.try_clone_fd().context("unable to clone pty for shell spawn")?;
-        let process = await!(Pty::launch_shell(&spawn_fd, &cstr!("/boot/bin/sh")))
+        let process = await!(Pty::launch_shell(&spawn_fd, &cstr!("/boot/bin/sh"), &job))
             .context("launch shell process")?;
 
         {
             let mut option = self.shell_process.lock();
             *option = Some(process);
         }
+        self.shell_job = Some(job);
+        self.last_window_size = Some(window_size.clone());
 
         await!(Pty::set_window_size(&spawn_fd, window_size))
             .context("unable to set initial window size for shell")?;
 
+        if let Some(max_bytes) = self.initial_output_capture_bytes {
+            self.initial_output = await!(self.capture_initial_output(max_bytes))
+                .context("unable to capture shell's initial output")?;
+        }
+
         Ok(())
     }
 
+    /// Reads up to `max_bytes` of output the shell has produced since spawn, for `spawn`'s
+    /// initial-output capture. Gives up early, rather than blocking indefinitely, if the shell
+    /// hasn't produced `max_bytes` of output within `INITIAL_OUTPUT_READ_TIMEOUT_SECONDS` of the
+    /// most recent byte (or of spawn, if it produced none at all).
+    async fn capture_initial_output(&self, max_bytes: usize) -> Result<Vec<u8>, Error> {
+        let timeout = zx::Duration::from_seconds(INITIAL_OUTPUT_READ_TIMEOUT_SECONDS);
+        let mut collected = Vec::with_capacity(max_bytes);
+
+        while collected.len() < max_bytes {
+            let mut buf = vec![0u8; max_bytes - collected.len()];
+            match await!(self.read_with_timeout(&mut buf, timeout))? {
+                ReadOutcome::Data(bytes_read) if bytes_read > 0 => {
+                    collected.extend_from_slice(&buf[..bytes_read]);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(collected)
+    }
+
+    /// Reopens the client side of the pty and spawns a fresh shell, for use after the previous
+    /// shell process has died. Reuses the window size that was last set.
+    ///
+    /// Returns an error if the `Pty` has never been spawned, since there is no prior window
+    /// size to reconnect with, or if the previous shell process is still running, since
+    /// spawning a new one would leak it and open a second client pty alongside it.
+    pub async fn reconnect(&mut self) -> Result<(), Error> {
+        let window_size = self
+            .last_window_size
+            .clone()
+            .ok_or_else(|| format_err!("cannot reconnect a pty that has never been spawned"))?;
+
+        if self.is_alive() {
+            return Err(format_err!(
+                "cannot reconnect a pty whose previous shell process is still running"
+            ));
+        }
+
+        await!(self.spawn_in_job_impl(window_size, zx::Job::from_handle(zx::Handle::invalid())))
+    }
+
     /// Attempts to clone the server side of the file descriptor.
     pub fn try_clone_fd(&self) -> Result<File, Error> {
         let fd = self.server_pty.try_clone()?;
         Ok(fd)
     }
 
+    /// Returns whether the shell process is still running.
+    ///
+    /// After the shell dies, the server side of the pty may still be open (reads on it will
+    /// simply start returning EOF), so this checks the shell process directly rather than the
+    /// state of the pty. Returns `false` if the `Pty` has never been spawned.
+    pub fn is_alive(&self) -> bool {
+        match self.shell_process.lock().as_ref() {
+            Some(process) => match process.info() {
+                Ok(info) => info.started && !info.exited,
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
     /// Closes the shell. This method is safe to call multiple times.
     /// The close method will be called automatically when the Pty is dropped.
     pub fn close(&self) -> Result<(), Error> {
@@ -67,27 +286,190 @@ impl Pty {
         Ok(())
     }
 
+    /// Performs a clean shutdown of the shell, rather than killing it outright as `close` does.
+    ///
+    /// This closes our connection to the client side of the pty, giving the shell a chance to
+    /// observe the hangup and exit on its own, and waits for the shell process to actually
+    /// terminate before returning. If the shell hasn't exited on its own within
+    /// `SHUTDOWN_TIMEOUT_SECONDS`, it is killed outright so this method never hangs on a
+    /// misbehaving shell.
+    pub async fn shutdown(&mut self) -> Result<(), Error> {
+        let on_signals = {
+            let shell_process = self.shell_process.lock();
+            shell_process
+                .as_ref()
+                .map(|process| fasync::OnSignals::new(process, zx::Signals::PROCESS_TERMINATED))
+        };
+
+        // Close our side of the pty. With no one left to service it, the shell's reads and
+        // writes will start failing and it should exit on its own rather than being killed.
+        let placeholder =
+            File::open("/dev/null").context("unable to open placeholder for closed pty")?;
+        let _ = std::mem::replace(&mut self.server_pty, placeholder);
+
+        if let Some(on_signals) = on_signals {
+            let timeout = zx::Duration::from_seconds(SHUTDOWN_TIMEOUT_SECONDS);
+            let deadline = fasync::Time::after(timeout);
+            let terminated = await!(on_signals.on_timeout(deadline, || Err(zx::Status::TIMED_OUT)));
+
+            if terminated.is_err() {
+                // The shell didn't exit on its own within the timeout; kill it outright rather
+                // than waiting forever.
+                self.close().context("failed to kill shell that did not exit on its own")?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Sends a message to the shell that the window has been resized.
     pub async fn resize(&self, window_size: WindowSize) -> Result<(), Error> {
         await!(Pty::set_window_size(&self.server_pty, window_size))?;
         Ok(())
     }
 
-    /// Opens the initial server side of the pty.
-    fn open_server_pty() -> Result<File, Error> {
-        let server_pty = OpenOptions::new().read(true).write(true).open("/dev/misc/ptmx")?;
+    /// Sends `signal` to the shell by writing its corresponding control character to the
+    /// server side of the pty, as if a user had typed it.
+    pub async fn send_signal(&self, signal: PtySignal) -> Result<(), Error> {
+        let fd = self.try_clone_fd().context("unable to clone pty to send signal")?;
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(fd)? };
+        await!(evented_fd.write_all(&[signal.as_control_char()]))
+            .context("failed to write signal to pty")?;
+        Ok(())
+    }
+
+    /// Reads from the pty into `buf`, without blocking forever if the shell never produces any
+    /// data.
+    ///
+    /// Returns `ReadOutcome::TimedOut` instead of an error if no data arrives within `timeout`.
+    pub async fn read_with_timeout(
+        &self,
+        buf: &mut [u8],
+        timeout: zx::Duration,
+    ) -> Result<ReadOutcome, Error> {
+        let fd = self.try_clone_fd().context("unable to clone pty to read with timeout")?;
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(fd)? };
+        let deadline = fasync::Time::after(timeout);
+
+        let result = await!(evented_fd
+            .read(buf)
+            .on_timeout(deadline, || Err(std::io::ErrorKind::TimedOut.into())));
+
+        if let Err(ref e) = result {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                return Ok(ReadOutcome::TimedOut);
+            }
+        }
+
+        let bytes_read = result.context("failed to read from pty")?;
+        Ok(ReadOutcome::Data(bytes_read))
+    }
+
+    /// Reads whatever output the shell produced but that the caller hasn't consumed yet,
+    /// stopping once the pty reports end-of-file.
+    ///
+    /// Intended for use once the shell process has exited (for example, after waiting on
+    /// `zx::Signals::PROCESS_TERMINATED`), to collect any trailing output it wrote before
+    /// dying without having to drive `output_stream` by hand.
+    pub async fn drain_remaining(&self) -> Result<Vec<u8>, Error> {
+        let fd = self.try_clone_fd().context("unable to clone pty to drain remaining output")?;
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(fd)? };
+
+        let mut collected = Vec::new();
+        loop {
+            let mut buf = vec![0u8; self.read_buffer_size];
+            let bytes_read =
+                await!(evented_fd.read(&mut buf)).context("failed to read from pty")?;
+            if bytes_read == 0 {
+                break;
+            }
+            collected.extend_from_slice(&buf[..bytes_read]);
+        }
+
+        Ok(collected)
+    }
+
+    /// Returns a `Stream` that yields chunks of the shell's output as they arrive.
+    ///
+    /// This is an adaptor over repeated reads from the pty, for callers that would rather drive
+    /// output with `while let Some(chunk) = stream.next().await` than manage their own read
+    /// loop. The stream ends once the pty is closed or a read fails; a failed read is yielded
+    /// as an `Err` before the stream ends.
+    pub fn output_stream(&self) -> Result<impl Stream<Item = Result<Vec<u8>, Error>>, Error> {
+        let fd = self.try_clone_fd().context("unable to clone pty for output stream")?;
+        let evented_fd = unsafe { fasync::net::EventedFd::new(fd)? };
+        let read_buffer_size = self.read_buffer_size;
+
+        Ok(stream::unfold(Some(evented_fd), move |state| {
+            async move {
+                let mut evented_fd = state?;
+                let mut buf = vec![0u8; read_buffer_size];
+                match await!(evented_fd.read(&mut buf)).context("failed to read from pty") {
+                    Ok(bytes_read) if bytes_read > 0 => {
+                        Some((Ok(buf[..bytes_read].to_vec()), Some(evented_fd)))
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some((Err(Error::from(e)), None)),
+                }
+            }
+        }))
+    }
+
+    /// Drains output from the shell until it emits its prompt, so callers can tell when the
+    /// shell is ready to receive a command without reimplementing this logic themselves.
+    ///
+    /// Readiness is detected by waiting for a single space in the shell's output, which is the
+    /// default shell's prompt terminator.
+    pub async fn wait_for_ready(&self) -> Result<(), Error> {
+        await!(self.wait_for_sentinel(b' '))
+    }
+
+    /// Like [`Pty::wait_for_ready`], but waits for `sentinel` to appear in the shell's output
+    /// instead of assuming the default shell's space-terminated prompt.
+    pub async fn wait_for_sentinel(&self, sentinel: u8) -> Result<(), Error> {
+        let fd = self.try_clone_fd().context("unable to clone pty to wait for ready")?;
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(fd)? };
+
+        loop {
+            let mut output = vec![0u8; self.read_buffer_size];
+            let bytes_read =
+                await!(evented_fd.read(&mut output)).context("failed to read from pty")?;
+            if output[..bytes_read].contains(&sentinel) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Opens `ptmx_path` as the initial server side of the pty.
+    fn open_server_pty(ptmx_path: &str) -> Result<File, Error> {
+        let server_pty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(ptmx_path)
+            .with_context(|_| format!("unable to open ptmx device at {}", ptmx_path))?;
         fasync::net::set_nonblock(server_pty.as_raw_fd())
             .context("failed to set PTY to non-blocking")?;
         Ok(server_pty)
     }
 
     /// Launches the shell process by creating the client side of the pty and then spawning the
-    /// shell.
-    async fn launch_shell(server_pty: &File, command: &CStr) -> Result<zx::Process, Error> {
-        let client_pty =
-            await!(Pty::open_client_pty(server_pty)).context("unable to create client_pty")?;
-        let process = Pty::spawn_shell_process(client_pty, command)
-            .context("unable to spawn shell process")?;
+    /// shell into `job`.
+    ///
+    /// If either step fails, any channel or file descriptor already created for the attempt
+    /// (the client pty's channel, or the client pty file itself) is closed by its own `Drop`
+    /// impl as this function unwinds, so no explicit cleanup is needed here; the errors below
+    /// are composed only to make the failure easier to diagnose.
+    async fn launch_shell(
+        server_pty: &File,
+        command: &CStr,
+        job: &zx::Job,
+    ) -> Result<zx::Process, Error> {
+        let client_pty = await!(Pty::open_client_pty(server_pty))
+            .map_err(|e| format_err!("unable to create client_pty: {}", e))?;
+        let process = Pty::spawn_shell_process(client_pty, command, job)
+            .map_err(|e| format_err!("unable to spawn shell process: {}", e))?;
 
         Ok(process)
     }
@@ -113,10 +495,14 @@ impl Pty {
         Ok(client_pty)
     }
 
-    /// spawns the shell and transfers the client pty to the process.
-    fn spawn_shell_process(client_pty: File, command: &CStr) -> Result<zx::Process, Error> {
+    /// spawns the shell into `job` and transfers the client pty to the process.
+    fn spawn_shell_process(
+        client_pty: File,
+        command: &CStr,
+        job: &zx::Job,
+    ) -> Result<zx::Process, Error> {
         let process = fdio::spawn_etc(
-            &zx::Job::from_handle(zx::Handle::invalid()),
+            job,
             fdio::SpawnOptions::CLONE_ALL - fdio::SpawnOptions::CLONE_STDIO,
             command,
             &[command],
@@ -131,20 +517,83 @@ impl Pty {
         Ok(process)
     }
 
+    /// Returns an error without contacting the pty device if `window_size` has a zero width or
+    /// height, since a 0x0 terminal is never valid.
     pub async fn set_window_size(
         server_pty: &File,
         mut window_size: WindowSize,
     ) -> Result<(), Error> {
-        let server_pty_channel = fdio::clone_channel(server_pty)
-            .context("failed to clone channel from server PTY FD")?;
-        let server_pty_fidl_channel = fasync::Channel::from_channel(server_pty_channel)
-            .context("failed to create FIDL channel from zircon channel")?;
-        let device_proxy = DeviceProxy::new(server_pty_fidl_channel);
+        if window_size.width == 0 || window_size.height == 0 {
+            return Err(format_err!(
+                "cannot set a {}x{} window size: width and height must be non-zero",
+                window_size.width,
+                window_size.height
+            ));
+        }
+
+        let device_proxy = Pty::device_proxy(server_pty)?;
 
         await!(device_proxy.set_window_size(&mut window_size))
             .context("Unable to resize window")?;
         Ok(())
     }
+
+    /// Returns whether the server side of the pty currently has an active client connected.
+    ///
+    /// A pty loses its active client (and becomes unconnected) when, for example, the shell
+    /// using it exits; it regains one once a new client is opened via `OpenClient`.
+    pub async fn is_connected(&self) -> Result<bool, Error> {
+        let device_proxy = Pty::device_proxy(&self.server_pty)?;
+        let (status, events) =
+            await!(device_proxy.read_events()).context("unable to read pty events")?;
+        zx::Status::ok(status).context("unable to read pty events")?;
+        Ok(events & EVENT_HANGUP == 0)
+    }
+
+    /// Discards any data that has been written to the pty but not yet read by the shell.
+    pub fn flush_input(&self) -> Result<(), Error> {
+        Pty::tcflush(&self.server_pty, libc::TCIFLUSH)
+    }
+
+    /// Discards any data that the shell has written but that has not yet been read from the
+    /// pty.
+    pub fn flush_output(&self) -> Result<(), Error> {
+        Pty::tcflush(&self.server_pty, libc::TCOFLUSH)
+    }
+
+    /// Flushes `file`'s `queue_selector` queue (one of the `libc::TC*FLUSH` constants).
+    fn tcflush(file: &File, queue_selector: libc::c_int) -> Result<(), Error> {
+        // Safe because `file` is a valid, open file descriptor for the duration of this call and
+        // `tcflush` does not retain it.
+        let result = unsafe { libc::tcflush(file.as_raw_fd(), queue_selector) };
+        if result != 0 {
+            return Err(format_err!("tcflush failed: {}", std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    /// Creates a `DeviceProxy` for issuing FIDL requests to the pty's server side.
+    fn device_proxy(server_pty: &File) -> Result<DeviceProxy, Error> {
+        Ok(DeviceProxy::new(Pty::clone_fidl_channel(server_pty)?))
+    }
+
+    /// Clones `server_pty`'s underlying channel as a `fasync::Channel`.
+    fn clone_fidl_channel(server_pty: &File) -> Result<fasync::Channel, Error> {
+        let server_pty_channel = fdio::clone_channel(server_pty)
+            .context("failed to clone channel from server PTY FD")?;
+        fasync::Channel::from_channel(server_pty_channel)
+            .context("failed to create FIDL channel from zircon channel")
+    }
+
+    /// Returns a clone of the underlying channel for the server side of the pty, wrapped as a
+    /// `fasync::Channel` so it can be registered with a caller's own async executor.
+    ///
+    /// This is the same channel-cloning operation `set_window_size` uses internally to talk to
+    /// the pty device; this method simply exposes it for callers that want to make their own
+    /// FIDL requests against the server side of the pty.
+    pub fn server_channel(&self) -> Result<fasync::Channel, Error> {
+        Pty::clone_fidl_channel(&self.server_pty)
+    }
 }
 
 impl Drop for Pty {
@@ -158,7 +607,7 @@ impl Drop for Pty {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::io::{AsyncReadExt, AsyncWriteExt};
+    use futures::stream::StreamExt;
 
     #[fasync::run_singlethreaded(test)]
     async fn can_create_pty() -> Result<(), Error> {
@@ -168,7 +617,7 @@ mod tests {
 
     #[fasync::run_singlethreaded(test)]
     async fn can_open_client_pty() -> Result<(), Error> {
-        let server_pty = Pty::open_server_pty()?;
+        let server_pty = Pty::open_server_pty(DEFAULT_PTMX_PATH)?;
         let client_pty = await!(Pty::open_client_pty(&server_pty))?;
         assert!(client_pty.as_raw_fd() > 0);
 
@@ -177,7 +626,7 @@ mod tests {
 
     #[fasync::run_singlethreaded(test)]
     async fn can_open_server_pty() -> Result<(), Error> {
-        let server_pty = Pty::open_server_pty()?;
+        let server_pty = Pty::open_server_pty(DEFAULT_PTMX_PATH)?;
         assert!(server_pty.as_raw_fd() > 0);
 
         Ok(())
@@ -185,8 +634,9 @@ mod tests {
 
     #[fasync::run_singlethreaded(test)]
     async fn can_spawn_shell_process() -> Result<(), Error> {
-        let server_pty = Pty::open_server_pty()?;
-        let process = await!(Pty::launch_shell(&server_pty, &cstr!("/pkg/bin/sh")))?;
+        let server_pty = Pty::open_server_pty(DEFAULT_PTMX_PATH)?;
+        let job = zx::Job::from_handle(zx::Handle::invalid());
+        let process = await!(Pty::launch_shell(&server_pty, &cstr!("/pkg/bin/sh"), &job))?;
 
         let mut started = false;
         if let Ok(info) = process.info() {
@@ -198,6 +648,22 @@ mod tests {
         Ok(())
     }
 
+    #[fasync::run_singlethreaded(test)]
+    async fn launch_shell_fails_cleanly_when_server_pty_is_not_a_pty_device() -> Result<(), Error> {
+        // /dev/null doesn't implement `fuchsia.hardware.pty.Device`, so the FIDL call inside
+        // `open_client_pty` fails, letting us exercise `launch_shell`'s failure path without a
+        // real ptmx device.
+        let not_a_pty = File::open("/dev/null").context("unable to open /dev/null")?;
+        let job = zx::Job::from_handle(zx::Handle::invalid());
+
+        let result = await!(Pty::launch_shell(&not_a_pty, &cstr!("/pkg/bin/sh"), &job));
+
+        let error = result.expect_err("launch_shell should fail against a non-pty server_pty");
+        assert!(format!("{}", error).contains("unable to create client_pty"));
+
+        Ok(())
+    }
+
     #[fasync::run_singlethreaded(test)]
     async fn shell_process_is_spawned() -> Result<(), Error> {
         let pty = await!(spawn_pty());
@@ -213,6 +679,74 @@ mod tests {
         Ok(())
     }
 
+    #[fasync::run_singlethreaded(test)]
+    async fn shell_process_is_spawned_detached() -> Result<(), Error> {
+        let window_size = WindowSize { width: 300 as u32, height: 300 as u32 };
+        let mut pty = Pty::new().unwrap();
+        await!(pty.spawn_detached(window_size))?;
+
+        assert!(pty.shell_job.is_some());
+
+        let mut started = false;
+        let process_ref = pty.shell_process.clone();
+        if let Some(process) = process_ref.lock().as_ref() {
+            let info = process.info().unwrap();
+            started = info.started;
+        }
+        assert_eq!(started, true);
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn spawn_in_job_spawns_process_into_given_job() -> Result<(), Error> {
+        use fuchsia_zircon::AsHandleRef;
+
+        let window_size = WindowSize { width: 300 as u32, height: 300 as u32 };
+        let job = fuchsia_runtime::job_default().create_child_job()?;
+        let job_koid = job.as_handle_ref().basic_info()?.koid;
+
+        let mut pty = Pty::new().unwrap();
+        await!(pty.spawn_in_job(window_size, job))?;
+
+        let process_ref = pty.shell_process.clone();
+        let process = process_ref.lock();
+        let process = process.as_ref().unwrap();
+        assert_eq!(process.as_handle_ref().basic_info()?.related_koid, job_koid);
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn with_ptmx_path_opens_explicit_default_path() -> Result<(), Error> {
+        let _ = Pty::with_ptmx_path(DEFAULT_PTMX_PATH)?;
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn with_ptmx_path_fails_on_bogus_path() -> Result<(), Error> {
+        assert!(Pty::with_ptmx_path("/dev/misc/does-not-exist").is_err());
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn is_alive_before_spawn() -> Result<(), Error> {
+        let pty = Pty::new()?;
+        assert_eq!(pty.is_alive(), false);
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn is_alive_after_spawn_and_not_after_close() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        assert_eq!(pty.is_alive(), true);
+
+        pty.close()?;
+        assert_eq!(pty.is_alive(), false);
+
+        Ok(())
+    }
+
     #[fasync::run_singlethreaded(test)]
     async fn shell_is_killed_on_close() -> Result<(), Error> {
         let pty = await!(spawn_pty());
@@ -230,6 +764,46 @@ mod tests {
         Ok(())
     }
 
+    #[fasync::run_singlethreaded(test)]
+    async fn shell_exits_on_shutdown() -> Result<(), Error> {
+        let mut pty = await!(spawn_pty());
+
+        await!(pty.shutdown())?;
+
+        let mut exited = false;
+        let process_ref = pty.shell_process.clone();
+        if let Some(process) = process_ref.lock().as_ref() {
+            let info = process.info().unwrap();
+            exited = info.exited;
+        }
+        assert_eq!(exited, true);
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn shutdown_kills_shell_that_ignores_hangup() -> Result<(), Error> {
+        let mut pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(flush(&mut evented_fd))?;
+
+        // Tell the shell to ignore the hangup it will see once its pty is closed, so it never
+        // exits on its own and `shutdown` has to fall back to killing it.
+        await!(evented_fd.write_all(b"trap '' HUP; sleep 100\n"))?;
+
+        await!(pty.shutdown())?;
+
+        let mut exited = false;
+        let process_ref = pty.shell_process.clone();
+        if let Some(process) = process_ref.lock().as_ref() {
+            let info = process.info().unwrap();
+            exited = info.exited;
+        }
+        assert_eq!(exited, true);
+
+        Ok(())
+    }
+
     #[fasync::run_singlethreaded(test)]
     async fn can_safely_call_close_twice() -> Result<(), Error> {
         let pty = await!(spawn_pty());
@@ -256,6 +830,80 @@ mod tests {
         Ok(())
     }
 
+    #[fasync::run_singlethreaded(test)]
+    async fn can_write_to_shell_after_wait_for_ready() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+
+        await!(pty.wait_for_ready())?;
+
+        await!(evented_fd.write_all("a".as_bytes()))?;
+
+        let mut output = [0u8, 4];
+        let result = await!(evented_fd.read(&mut output))?;
+        assert_eq!(&output[0..result], "a".as_bytes());
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn can_reconnect_after_shell_dies() -> Result<(), Error> {
+        let mut pty = await!(spawn_pty());
+        let first_process = pty
+            .shell_process
+            .lock()
+            .as_ref()
+            .unwrap()
+            .duplicate_handle(zx::Rights::SAME_RIGHTS)?;
+        first_process.kill()?;
+
+        await!(pty.reconnect())?;
+
+        let mut started = false;
+        if let Some(process) = pty.shell_process.lock().as_ref() {
+            let info = process.info().unwrap();
+            started = info.started;
+        }
+        assert_eq!(started, true);
+
+        // The reconnected shell should actually be usable, not just started.
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(flush(&mut evented_fd))?;
+        await!(evented_fd.write_all("a".as_bytes()))?;
+
+        let mut output = [0u8, 4];
+        let result = await!(evented_fd.read(&mut output))?;
+        assert_eq!(&output[0..result], "a".as_bytes());
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn reconnect_fails_while_shell_is_still_alive() -> Result<(), Error> {
+        let mut pty = await!(spawn_pty());
+        assert!(pty.is_alive());
+
+        assert!(await!(pty.reconnect()).is_err());
+        // The still-running shell must not have been replaced.
+        assert!(pty.is_alive());
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn reconnect_fails_before_first_spawn() -> Result<(), Error> {
+        let mut pty = Pty::new()?;
+        assert!(await!(pty.reconnect()).is_err());
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn can_send_signal() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        await!(pty.send_signal(PtySignal::Interrupt))?;
+        Ok(())
+    }
+
     #[fasync::run_singlethreaded(test)]
     async fn can_resize_window() -> Result<(), Error> {
         let pty = await!(spawn_pty());
@@ -263,6 +911,218 @@ mod tests {
         Ok(())
     }
 
+    #[fasync::run_singlethreaded(test)]
+    async fn resize_rejects_zero_dimensions() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+
+        let error = await!(pty.resize(WindowSize { width: 0, height: 400 }))
+            .expect_err("resize should reject a zero width");
+        assert!(format!("{}", error).contains("width and height must be non-zero"));
+
+        let error = await!(pty.resize(WindowSize { width: 400, height: 0 }))
+            .expect_err("resize should reject a zero height");
+        assert!(format!("{}", error).contains("width and height must be non-zero"));
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn is_connected_while_shell_is_running() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        assert_eq!(await!(pty.is_connected())?, true);
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn unspawned_pty_is_not_connected() -> Result<(), Error> {
+        let pty = Pty::new()?;
+        assert_eq!(await!(pty.is_connected())?, false);
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn can_flush_input() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+
+        await!(flush(&mut evented_fd))?;
+        await!(evented_fd.write_all("a".as_bytes()))?;
+        pty.flush_input()?;
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn can_flush_output() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+
+        await!(flush(&mut evented_fd))?;
+        pty.flush_output()?;
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn server_channel_is_usable_for_device_proxy_call() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+
+        let channel = pty.server_channel()?;
+        let device_proxy = DeviceProxy::new(channel);
+        let (status, _events) = await!(device_proxy.read_events())?;
+        zx::Status::ok(status)?;
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn read_with_timeout_times_out_on_idle_shell() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(flush(&mut evented_fd))?;
+
+        let mut buf = [0u8; 16];
+        let outcome = await!(pty.read_with_timeout(&mut buf, zx::Duration::from_millis(100)))?;
+        assert_eq!(outcome, ReadOutcome::TimedOut);
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn read_with_timeout_returns_data_within_timeout() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(flush(&mut evented_fd))?;
+
+        await!(evented_fd.write_all("a".as_bytes()))?;
+
+        let mut buf = [0u8; 16];
+        let outcome = await!(pty.read_with_timeout(&mut buf, zx::Duration::from_seconds(5)))?;
+        assert_eq!(outcome, ReadOutcome::Data(1));
+        assert_eq!(&buf[0..1], "a".as_bytes());
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn output_stream_yields_shell_output_until_marker() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(flush(&mut evented_fd))?;
+
+        let mut stream = pty.output_stream()?;
+        await!(evented_fd.write_all(b"echo marker_output\n"))?;
+
+        let marker = b"marker_output";
+        let mut collected = Vec::new();
+        loop {
+            let chunk = await!(stream.next()).expect("stream ended before marker appeared")?;
+            collected.extend_from_slice(&chunk);
+            if collected.windows(marker.len()).any(|window| window == marker) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn drain_remaining_returns_output_written_before_exit() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(flush(&mut evented_fd))?;
+
+        let on_signals = {
+            let shell_process = pty.shell_process.lock();
+            let process = shell_process.as_ref().unwrap();
+            fasync::OnSignals::new(process, zx::Signals::PROCESS_TERMINATED)
+        };
+        await!(evented_fd.write_all(b"echo marker_output; exit\n"))?;
+        await!(on_signals)?;
+
+        let drained = await!(pty.drain_remaining())?;
+        let marker = b"marker_output";
+        assert!(drained.windows(marker.len()).any(|window| window == marker));
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn default_read_buffer_size_is_used_when_unconfigured() -> Result<(), Error> {
+        let pty = Pty::new()?;
+        assert_eq!(pty.read_buffer_size(), DEFAULT_READ_BUFFER_SIZE);
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn set_read_buffer_size_changes_read_buffer_size() -> Result<(), Error> {
+        let mut pty = Pty::new()?;
+        pty.set_read_buffer_size(64);
+        assert_eq!(pty.read_buffer_size(), 64);
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn initial_output_is_empty_when_capture_is_not_enabled() -> Result<(), Error> {
+        let pty = await!(spawn_pty());
+        assert!(pty.initial_output().is_empty());
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn spawn_captures_non_empty_initial_output_when_enabled() -> Result<(), Error> {
+        let mut pty = Pty::new()?;
+        pty.set_initial_output_capture(64);
+        await!(pty.spawn(WindowSize { width: 300, height: 300 }))?;
+
+        assert!(!pty.initial_output().is_empty());
+
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn larger_read_buffer_needs_fewer_reads() -> Result<(), Error> {
+        let long_string = "x".repeat(2000);
+
+        let small_buffer_reads = await!(count_reads_to_see_marker(16, &long_string))?;
+        let large_buffer_reads = await!(count_reads_to_see_marker(512, &long_string))?;
+
+        assert!(large_buffer_reads < small_buffer_reads);
+
+        Ok(())
+    }
+
+    /// Spawns a shell with `read_buffer_size` as its configured read buffer size, echoes
+    /// `marker`, and returns the number of reads `output_stream` needed before `marker`
+    /// appeared in the accumulated output.
+    async fn count_reads_to_see_marker(
+        read_buffer_size: usize,
+        marker: &str,
+    ) -> Result<usize, Error> {
+        let mut pty = Pty::new()?;
+        pty.set_read_buffer_size(read_buffer_size);
+        await!(pty.spawn(WindowSize { width: 300, height: 300 }))?;
+        await!(pty.wait_for_ready())?;
+
+        let mut evented_fd = unsafe { fasync::net::EventedFd::new(pty.try_clone_fd()?)? };
+        await!(evented_fd.write_all(format!("echo {}\n", marker).as_bytes()))?;
+
+        let marker = marker.as_bytes();
+        let mut collected = Vec::new();
+        let mut stream = pty.output_stream()?;
+        let mut reads = 0;
+        loop {
+            let chunk = await!(stream.next()).expect("stream ended before marker appeared")?;
+            reads += 1;
+            collected.extend_from_slice(&chunk);
+            if collected.windows(marker.len()).any(|window| window == marker) {
+                break;
+            }
+        }
+
+        Ok(reads)
+    }
+
     #[fasync::run_singlethreaded(test)]
     async fn pty_calls_close_on_drop() -> Result<(), Error> {
         let pty = await!(spawn_pty());
@@ -31,4 +31,7 @@ pub trait Controllable<'entries>: DirectoryEntry {
         &mut self,
         name: &str,
     ) -> Result<Option<Box<dyn DirectoryEntry + 'entries>>, Status>;
+
+    /// Returns the number of watcher connections currently attached to this directory.
+    fn watcher_count(&self) -> usize;
 }
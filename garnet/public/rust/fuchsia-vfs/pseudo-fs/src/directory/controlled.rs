@@ -77,6 +77,14 @@ pub enum RemoveEntryError {
     Terminated,
 }
 
+/// Type of errors returned by the [`Controller::watcher_count`] future.
+#[derive(Debug, Fail)]
+pub enum WatcherCountError {
+    /// Controlled directory has been destroyed.
+    #[fail(display = "Controlled directory has been destroyed.")]
+    Terminated,
+}
+
 /// Type of errors returned by the [`Controller::remove_entry_res`] future.
 #[derive(Debug, Fail)]
 pub enum RemoveEntryResError {
@@ -88,6 +96,14 @@ pub enum RemoveEntryResError {
     RemoveFailed(Status),
 }
 
+/// Type of errors returned by the [`Controller::set_on_close`] future.
+#[derive(Debug, Fail)]
+pub enum SetOnCloseError {
+    /// Controlled directory has been destroyed.
+    #[fail(display = "Controlled directory has been destroyed.")]
+    Terminated,
+}
+
 type AddEntryResponse<'entries> = Result<(), (Status, Box<dyn DirectoryEntry + 'entries>)>;
 type RemoveEntryResponse<'entries> = Result<Option<Box<dyn DirectoryEntry + 'entries>>, Status>;
 
@@ -114,6 +130,12 @@ enum Command<'entries> {
         name: String,
         res_sender: oneshot::Sender<RemoveEntryResponse<'entries>>,
     },
+    WatcherCount {
+        res_sender: oneshot::Sender<usize>,
+    },
+    SetOnClose {
+        on_close: Box<dyn FnOnce() + Send + 'entries>,
+    },
 }
 
 /// This is a "remote control" for a [`DirectoryEntry`] that it also [`Controllable`] wrapped in a
@@ -342,6 +364,54 @@ impl<'entries> Controller<'entries> {
             }
         }
     }
+
+    /// Returns the number of watcher connections currently attached to the directory controlled
+    /// by this controller.
+    pub fn watcher_count(
+        &self,
+    ) -> impl Future<Output = Result<usize, WatcherCountError>> + 'entries {
+        // Cloning the sender allows us to generate a future that does not have any lifetime
+        // dependencies on self.
+        let mut controlled = self.controlled.clone();
+        let (res_sender, res_receiver) = oneshot::channel();
+        async move {
+            controlled.send(Command::WatcherCount { res_sender }).await.map_err(|send_err| {
+                check_send_err_is_disconnection("Controller::watcher_count", send_err);
+                WatcherCountError::Terminated
+            })?;
+
+            res_receiver.await.map_err(|oneshot::Canceled| WatcherCountError::Terminated)
+        }
+    }
+
+    /// Registers a callback that will run once, when the directory controlled by this controller
+    /// is destroyed - either because the corresponding [`Controlled`] future has been dropped (as
+    /// happens, for example, when `Hub` aborts a component instance's directory via its
+    /// `abort_handle`), or because it has otherwise gone out of scope.  This is useful for tying
+    /// cleanup of resources associated with the directory to the directory's own lifetime.
+    ///
+    /// Only the last callback registered via this method is kept - registering a new one replaces
+    /// the previous one, rather than running both.
+    pub fn set_on_close<OnClose>(
+        &self,
+        on_close: OnClose,
+    ) -> impl Future<Output = Result<(), SetOnCloseError>> + 'entries
+    where
+        OnClose: FnOnce() + Send + 'entries,
+    {
+        // Cloning the sender allows us to generate a future that does not have any lifetime
+        // dependencies on self.
+        let mut controlled = self.controlled.clone();
+        async move {
+            controlled
+                .send(Command::SetOnClose { on_close: Box::new(on_close) })
+                .await
+                .map_err(|send_err| {
+                    check_send_err_is_disconnection("Controller::set_on_close", send_err);
+                    SetOnCloseError::Terminated
+                })
+        }
+    }
 }
 
 /// This is a wrapper around a [`DirectoryEntry`] that it also [`Controllable`].  A [`Controller`]
@@ -354,6 +424,9 @@ pub struct Controlled<'entries> {
 
     /// Wrapped entry.
     controllable: Box<dyn Controllable<'entries> + 'entries>,
+
+    /// Callback to run, if any, when this value is dropped.  See [`Controller::set_on_close`].
+    on_close: Option<Box<dyn FnOnce() + Send + 'entries>>,
 }
 
 /// Given a directory that can be controlled, create a "controller" for it.  Controller allows
@@ -367,7 +440,7 @@ where
     let (sender, receiver) = mpsc::channel(1);
     (
         Controller { controlled: sender },
-        Controlled { controller: receiver, controllable: Box::new(controllable) },
+        Controlled { controller: receiver, controllable: Box::new(controllable), on_close: None },
     )
 }
 
@@ -404,6 +477,11 @@ impl<'entries> Controlled<'entries> {
         self.controllable.remove_entry(name)
     }
 
+    /// Returns the number of watcher connections currently attached to the directory.
+    pub fn watcher_count(&self) -> usize {
+        self.controllable.watcher_count()
+    }
+
     fn handle_command(&mut self, command: Command<'entries>) {
         match command {
             Command::Open { flags, mode, path, server_end } => {
@@ -438,6 +516,14 @@ impl<'entries> Controlled<'entries> {
                 // destroyed.
                 let _ = res_sender.send(res);
             }
+            Command::WatcherCount { res_sender } => {
+                // Failure to send a response should indicate that the controller has been
+                // destroyed.
+                let _ = res_sender.send(self.watcher_count());
+            }
+            Command::SetOnClose { on_close } => {
+                self.on_close = Some(on_close);
+            }
         }
     }
 }
@@ -494,6 +580,14 @@ impl<'entries> FusedFuture for Controlled<'entries> {
     }
 }
 
+impl<'entries> Drop for Controlled<'entries> {
+    fn drop(&mut self) {
+        if let Some(on_close) = self.on_close.take() {
+            on_close();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -507,7 +601,13 @@ mod tests {
             INO_UNKNOWN, OPEN_FLAG_DESCRIBE, OPEN_RIGHT_READABLE, WATCH_MASK_ADDED,
             WATCH_MASK_EXISTING, WATCH_MASK_IDLE, WATCH_MASK_REMOVED,
         },
+        fuchsia_async::Executor,
+        futures::{pin_mut, select},
         proc_macro_hack::proc_macro_hack,
+        std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
     };
 
     // Create level import of this macro does not affect nested modules.  And as attributes can
@@ -915,4 +1015,73 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn watcher_count() {
+        let controller;
+        let root = pseudo_directory! {
+            "etc" => controlled_pseudo_directory! {
+                controller ->
+                "fstab" => read_only_static("/dev/fs /"),
+            },
+        };
+
+        run_server_client(OPEN_RIGHT_READABLE, root, |root| {
+            async move {
+                let flags = OPEN_RIGHT_READABLE | OPEN_FLAG_DESCRIBE;
+
+                let etc = open_get_directory_proxy_assert_ok!(&root, flags, "etc");
+
+                let watch_mask = WATCH_MASK_EXISTING | WATCH_MASK_IDLE;
+                let watcher1 = assert_watch!(etc, watch_mask);
+                let watcher2 = assert_watch!(etc, watch_mask);
+
+                assert_eq!(2, controller.watcher_count().await.unwrap());
+
+                drop(watcher2);
+
+                // Registering the new entry causes the controlled directory to be polled again,
+                // giving it a chance to notice and prune the now-dead watcher connection.
+                controller.add_entry("passwd", read_only_static("[redacted]")).await.unwrap();
+
+                assert_eq!(1, controller.watcher_count().await.unwrap());
+
+                drop(watcher1);
+                assert_close!(root);
+            }
+        });
+    }
+
+    #[test]
+    fn on_close_runs_when_controlled_directory_is_dropped() {
+        let (controller, mut root) = controlled(simple::empty());
+
+        let closed = Arc::new(AtomicBool::new(false));
+        let closed_clone = closed.clone();
+
+        let mut exec = Executor::new().expect("Executor creation failed");
+
+        // Drive `root` until the `SetOnClose` command has been delivered and processed, at which
+        // point `set_on_close` resolves.  `root` never completes on its own, so it is polled
+        // alongside `set_on_close` rather than run to completion.
+        let set_on_close =
+            controller.set_on_close(move || closed_clone.store(true, Ordering::SeqCst));
+        pin_mut!(set_on_close);
+
+        let register = async {
+            loop {
+                select! {
+                    res = set_on_close => break res.unwrap(),
+                    x = root => unreachable(x),
+                }
+            }
+        };
+        exec.run_singlethreaded(register);
+
+        assert_eq!(closed.load(Ordering::SeqCst), false);
+
+        drop(root);
+
+        assert_eq!(closed.load(Ordering::SeqCst), true);
+    }
 }
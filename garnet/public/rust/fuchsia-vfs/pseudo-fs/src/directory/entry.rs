@@ -123,6 +123,12 @@ pub trait DirectoryEntry: Future<Output = Void> + FusedFuture + Unpin + Send {
 
     /// This method is used to populate ReadDirents() output.
     fn entry_info(&self) -> EntryInfo;
+
+    /// Returns one of the `DIRENT_TYPE_*` constants describing this entry, without requiring the
+    /// caller to construct an `EntryInfo` first.  Equivalent to `self.entry_info().type_()`.
+    fn entry_type(&self) -> u8 {
+        self.entry_info().type_()
+    }
 }
 
 impl<'entries> DirectoryEntry for Box<dyn DirectoryEntry + 'entries> {
@@ -0,0 +1,160 @@
+// Copyright 2019 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! Implementation of a pseudo directory entry that forwards to a remote directory connection.
+//! See [`Remote`] for details.
+
+use crate::directory::entry::{DirectoryEntry, EntryInfo};
+
+use {
+    fidl::endpoints::ServerEnd,
+    fidl_fuchsia_io::{DirectoryProxy, NodeMarker, DIRENT_TYPE_DIRECTORY, INO_UNKNOWN},
+    futures::{future::FusedFuture, task::Context, Future, Poll},
+    std::pin::Pin,
+    void::Void,
+};
+
+/// Creates a new [`Remote`] directory entry that forwards open requests to `proxy`.
+///
+/// This allows a connection to a directory served elsewhere - for example, by another component,
+/// or by a completely separate pseudo directory tree - to be embedded as a child of a pseudo
+/// directory, without the owning directory needing to know that this particular entry is not one
+/// of its own.
+pub fn remote(proxy: DirectoryProxy) -> Remote {
+    Remote { proxy }
+}
+
+/// A directory entry that forwards every `open()` it receives to a remote `DirectoryProxy`,
+/// passing along the remaining relative path.  See [`remote`] for details.
+pub struct Remote {
+    proxy: DirectoryProxy,
+}
+
+impl DirectoryEntry for Remote {
+    fn open(
+        &mut self,
+        flags: u32,
+        mode: u32,
+        path: &mut dyn Iterator<Item = &str>,
+        server_end: ServerEnd<NodeMarker>,
+    ) {
+        let relative_path = path.collect::<Vec<&str>>().join("/");
+
+        // `DirectoryProxy::open()`/`clone()` do not return a `Result`, so there is no way to
+        // propagate a failure from here.  As documented on `DirectoryEntry::open()`, any error
+        // should simply result in `server_end` being closed, which dropping it on a `Err` here
+        // achieves.
+        let _ = if relative_path.is_empty() {
+            self.proxy.clone(flags, server_end)
+        } else {
+            self.proxy.open(flags, mode, &relative_path, server_end)
+        };
+    }
+
+    fn entry_info(&self) -> EntryInfo {
+        EntryInfo::new(INO_UNKNOWN, DIRENT_TYPE_DIRECTORY)
+    }
+}
+
+impl FusedFuture for Remote {
+    fn is_terminated(&self) -> bool {
+        // `Remote` has no work of its own to do - it only forwards `open()` calls, which are
+        // handled synchronously - so it is always safe to consider it terminated.
+        true
+    }
+}
+
+impl Future for Remote {
+    type Output = Void;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::remote;
+
+    use {
+        crate::{directory::entry::DirectoryEntry, file::simple::read_only_static, pseudo_directory},
+        fidl::endpoints::{create_proxy, ServerEnd},
+        fidl_fuchsia_io::{DirectoryMarker, FileMarker, NodeMarker, OPEN_RIGHT_READABLE},
+        fuchsia_async::Executor,
+        fuchsia_zircon::Status,
+        futures::{future::join3, select, task::Poll},
+        std::iter,
+        void::unreachable,
+    };
+
+    #[proc_macro_hack::proc_macro_hack(support_nested)]
+    use fuchsia_vfs_pseudo_fs_macros::pseudo_directory;
+
+    /// Drives `server` forever, treating its `is_terminated()` becoming true as completion, the
+    /// same way `crate::directory::test_utils::run_server_client()` does internally.
+    async fn serve_forever(mut server: impl DirectoryEntry) {
+        loop {
+            select! {
+                x = server => unreachable(x),
+                complete => break,
+            }
+        }
+    }
+
+    #[test]
+    fn remote_directory_forwards_open_to_backing_proxy() {
+        let mut exec = Executor::new().expect("Executor creation failed");
+
+        // The directory that is actually remote: served by its own pseudo directory tree, and
+        // embedded into `root` below only via a `DirectoryProxy`.
+        let mut remote_dir: Box<dyn DirectoryEntry> = Box::new(pseudo_directory! {
+            "greeting" => read_only_static(b"hello from the remote directory"),
+        });
+        let (remote_proxy, remote_server_end) =
+            create_proxy::<DirectoryMarker>().expect("Failed to create connection endpoints");
+        remote_dir.open(
+            OPEN_RIGHT_READABLE,
+            0,
+            &mut iter::empty(),
+            remote_server_end.into_channel().into(),
+        );
+
+        let mut root: Box<dyn DirectoryEntry> = Box::new(pseudo_directory! {
+            "mnt" => remote(remote_proxy),
+        });
+        let (root_proxy, root_server_end) =
+            create_proxy::<DirectoryMarker>().expect("Failed to create connection endpoints");
+        root.open(
+            OPEN_RIGHT_READABLE,
+            0,
+            &mut iter::empty(),
+            root_server_end.into_channel().into(),
+        );
+
+        let client = async move {
+            let (file_proxy, file_server_end) =
+                create_proxy::<FileMarker>().expect("Failed to create connection endpoints");
+            root_proxy
+                .open(
+                    OPEN_RIGHT_READABLE,
+                    0,
+                    "mnt/greeting",
+                    ServerEnd::<NodeMarker>::new(file_server_end.into_channel()),
+                )
+                .unwrap();
+
+            let (status, content) = file_proxy.read(100).await.expect("read failed");
+            assert_eq!(Status::from_raw(status), Status::OK);
+            assert_eq!(content.as_slice(), b"hello from the remote directory");
+        };
+
+        let mut future = Box::pin(join3(serve_forever(remote_dir), serve_forever(root), client));
+
+        assert_eq!(
+            exec.run_until_stalled(&mut future),
+            Poll::Ready(((), (), ())),
+            "future did not complete"
+        );
+    }
+}
@@ -37,7 +37,8 @@ use {
     },
     static_assertions::assert_eq_size,
     std::{
-        collections::BTreeMap, iter, iter::ExactSizeIterator, marker::Unpin, ops::Bound, pin::Pin,
+        cmp::Ordering, collections::BTreeMap, iter, iter::ExactSizeIterator, marker::Unpin,
+        ops::Bound, pin::Pin,
     },
     void::Void,
 };
@@ -56,6 +57,7 @@ pub fn empty_attr<'entries>(protection_attributes: u32) -> Simple<'entries> {
         entries: BTreeMap::new(),
         connections: FuturesUnordered::new(),
         watchers: Watchers::new(),
+        sort_comparator: None,
     }
 }
 
@@ -77,6 +79,10 @@ pub struct Simple<'entries> {
     connections: FuturesUnordered<StreamFuture<SimpleDirectoryConnection>>,
 
     watchers: Watchers,
+
+    /// When set, overrides the default alphanumeric order in which `ReadDirents` returns
+    /// entries.  See [`Simple::set_sort_comparator`].
+    sort_comparator: Option<Box<dyn Fn(&str, &str) -> Ordering + Send>>,
 }
 
 /// Return type for Simple::handle_request().
@@ -143,6 +149,67 @@ impl<'entries> Simple<'entries> {
         Ok(())
     }
 
+    /// Removes a child entry from this directory and returns it to the caller.
+    ///
+    /// Unlike [`Controllable::remove_entry`], which returns `Ok(None)` when there is no matching
+    /// entry, this method treats a missing entry as an error, since most callers reaching for a
+    /// `Simple` directly (rather than through the `Controllable` trait) are removing an entry
+    /// they expect to be present.
+    ///
+    /// Possible errors are:
+    ///   * `name` exceeding [`MAX_FILENAME`] bytes in length.
+    ///   * No entry with `name` is present in the directory.
+    pub fn remove_entry(
+        &mut self,
+        name: &str,
+    ) -> Result<Box<dyn DirectoryEntry + 'entries>, Status> {
+        assert_eq_size!(u64, usize);
+        if name.len() as u64 >= MAX_FILENAME {
+            return Err(Status::INVALID_ARGS);
+        }
+
+        let entry = self.entries.remove(name).ok_or(Status::NOT_FOUND)?;
+
+        self.watchers.send_event(WATCH_MASK_REMOVED, WATCH_EVENT_REMOVED, name).unwrap_or_else(
+            |err| match err {
+                WatchersSendError::NameTooLong => {
+                    panic!("We just checked the length of the `name`.  There should be a bug.")
+                }
+            },
+        );
+        Ok(entry)
+    }
+
+    /// Installs a comparator used to order this directory's direct children when they are
+    /// enumerated through `ReadDirents`, in place of the default alphanumeric order.  Pass `None`
+    /// to go back to the default alphanumeric order.
+    ///
+    /// Note that, unlike the default order, a directory with a comparator installed sorts all of
+    /// its entries on every `ReadDirents` call, rather than relying on the natural order of the
+    /// underlying storage.  This is fine for the directory sizes `Simple` is meant for, but is
+    /// something to keep in mind for directories with a very large number of entries.
+    pub fn set_sort_comparator(
+        &mut self,
+        comparator: Option<Box<dyn Fn(&str, &str) -> Ordering + Send>>,
+    ) {
+        self.sort_comparator = comparator;
+    }
+
+    /// Returns the number of direct children currently held by this directory.
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of watcher connections currently attached to this directory.
+    pub fn watcher_count(&self) -> usize {
+        self.watchers.count()
+    }
+
+    /// Returns the number of connections currently open to this directory.
+    pub fn open_connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
     /// Attaches a new connection (`server_end`) to this object.  Any error are reported as
     /// `OnOpen` events on the `server_end` itself.
     fn add_connection(&mut self, flags: u32, mode: u32, server_end: ServerEnd<NodeMarker>) {
@@ -317,6 +384,21 @@ impl<'entries> Simple<'entries> {
         max_bytes: u64,
         responder: R,
     ) -> Result<(), fidl::Error>
+    where
+        R: FnOnce(Status, &mut dyn ExactSizeIterator<Item = u8>) -> Result<(), fidl::Error>,
+    {
+        match &self.sort_comparator {
+            None => self.handle_read_dirents_alphabetical(connection, max_bytes, responder),
+            Some(_) => self.handle_read_dirents_custom_order(connection, max_bytes, responder),
+        }
+    }
+
+    fn handle_read_dirents_alphabetical<R>(
+        &mut self,
+        connection: &mut SimpleDirectoryConnection,
+        max_bytes: u64,
+        responder: R,
+    ) -> Result<(), fidl::Error>
     where
         R: FnOnce(Status, &mut dyn ExactSizeIterator<Item = u8>) -> Result<(), fidl::Error>,
     {
@@ -382,6 +464,92 @@ impl<'entries> Simple<'entries> {
         return responder(Status::OK, &mut buf.iter().cloned());
     }
 
+    /// Same as [`Simple::handle_read_dirents_alphabetical`], but orders entries using
+    /// `self.sort_comparator` instead of relying on the natural, alphanumeric order of the
+    /// underlying `BTreeMap`.  As the comparator can reorder entries arbitrarily, resuming a
+    /// partial listing is done by locating the last returned name in the freshly sorted list,
+    /// rather than through a `BTreeMap` range query.
+    fn handle_read_dirents_custom_order<R>(
+        &mut self,
+        connection: &mut SimpleDirectoryConnection,
+        max_bytes: u64,
+        responder: R,
+    ) -> Result<(), fidl::Error>
+    where
+        R: FnOnce(Status, &mut dyn ExactSizeIterator<Item = u8>) -> Result<(), fidl::Error>,
+    {
+        let mut buf = Vec::new();
+        let mut fit_one = false;
+
+        if connection.seek == AlphabeticalTraversal::End {
+            return responder(Status::OK, &mut buf.iter().cloned());
+        }
+
+        let comparator = self.sort_comparator.as_ref().expect(
+            "handle_read_dirents_custom_order is only called when sort_comparator is set",
+        );
+        let mut ordered: Vec<_> = self.entries.iter().collect();
+        ordered.sort_by(|(a, _), (b, _)| comparator(a, b));
+
+        let start_index = match &connection.seek {
+            AlphabeticalTraversal::Start => {
+                if !encode_dirent(
+                    &mut buf,
+                    max_bytes,
+                    &EntryInfo::new(INO_UNKNOWN, DIRENT_TYPE_DIRECTORY),
+                    ".",
+                ) {
+                    return responder(Status::BUFFER_TOO_SMALL, &mut buf.iter().cloned());
+                }
+
+                fit_one = true;
+                0
+            }
+
+            AlphabeticalTraversal::Dot => 0,
+
+            // If `last_returned_name` is still present, resume right after it. Otherwise (it may
+            // have been removed from the directory between two paginated calls) resume at the
+            // first entry that still sorts after it, rather than restarting the listing from the
+            // beginning and re-serving entries that were already returned.
+            AlphabeticalTraversal::Name(last_returned_name) => match ordered
+                .iter()
+                .position(|(name, _)| *name == last_returned_name)
+            {
+                Some(index) => index + 1,
+                None => ordered
+                    .iter()
+                    .position(|(name, _)| {
+                        comparator(name, last_returned_name) == Ordering::Greater
+                    })
+                    .unwrap_or(ordered.len()),
+            },
+
+            AlphabeticalTraversal::End => unreachable!("checked above"),
+        };
+
+        // Once "." has been encoded, a listing that stops early must not serve it again.
+        let mut last_returned = match &connection.seek {
+            AlphabeticalTraversal::Start => AlphabeticalTraversal::Dot,
+            seek => seek.clone(),
+        };
+
+        for (name, entry) in &ordered[start_index..] {
+            if !encode_dirent(&mut buf, max_bytes, &entry.entry_info(), name) {
+                connection.seek = last_returned;
+                return responder(
+                    if fit_one { Status::OK } else { Status::BUFFER_TOO_SMALL },
+                    &mut buf.iter().cloned(),
+                );
+            }
+            fit_one = true;
+            last_returned = AlphabeticalTraversal::Name((*name).clone());
+        }
+
+        connection.seek = AlphabeticalTraversal::End;
+        return responder(Status::OK, &mut buf.iter().cloned());
+    }
+
     fn poll_entries(&mut self, cx: &mut Context<'_>) {
         for (name, entry) in self.entries.iter_mut() {
             match entry.poll_unpin(cx) {
@@ -540,6 +708,10 @@ impl<'entries> Controllable<'entries> for Simple<'entries> {
         );
         Ok(self.entries.remove(name))
     }
+
+    fn watcher_count(&self) -> usize {
+        (self as &Simple).watcher_count()
+    }
 }
 
 impl<'entries> Unpin for Simple<'entries> {}
@@ -589,14 +761,101 @@ impl<'entries> FusedFuture for Simple<'entries> {
     }
 }
 
+/// Creates a directory that materializes its entries lazily, on first open.  See [`LazyCache`] for
+/// details.
+///
+/// POSIX access attributes are set to [`DEFAULT_DIRECTORY_PROTECTION_ATTRIBUTES`].
+pub fn lazy<'entries, F>(factory: F) -> LazyCache<'entries, F>
+where
+    F: Fn(&str) -> Option<Box<dyn DirectoryEntry + 'entries>> + Unpin + Send,
+{
+    LazyCache { factory, directory: empty() }
+}
+
+/// An implementation of a pseudo directory that defers constructing a child entry until it is
+/// opened for the first time.  `factory` is invoked with the name of the child being opened; the
+/// entry it returns is cached in an underlying [`Simple`] directory, so every subsequent open of
+/// the same name reuses that entry instead of calling `factory` again.  A `factory` that returns
+/// `None` means there is no such child, and nothing is cached - a later open of the same missing
+/// name will call `factory` again.
+///
+/// This is useful for subtrees that are expensive to build up front and where only a small
+/// fraction of the entries are expected to ever be accessed, unlike a fully materialized
+/// [`Simple`] directory.
+///
+/// Entries are only materialized one level at a time - once a child has been constructed, any
+/// paths nested under it are resolved by that child exactly as they would be for a [`Simple`]
+/// directory.
+pub struct LazyCache<'entries, F> {
+    factory: F,
+    directory: Simple<'entries>,
+}
+
+impl<'entries, F> DirectoryEntry for LazyCache<'entries, F>
+where
+    F: Fn(&str) -> Option<Box<dyn DirectoryEntry + 'entries>> + Unpin + Send,
+{
+    fn open(
+        &mut self,
+        flags: u32,
+        mode: u32,
+        path: &mut dyn Iterator<Item = &str>,
+        server_end: ServerEnd<NodeMarker>,
+    ) {
+        let name = match path.next() {
+            Some(name) => name,
+            None => return self.directory.open(flags, mode, path, server_end),
+        };
+
+        if !self.directory.entries.contains_key(name) {
+            if let Some(entry) = (self.factory)(name) {
+                // `name` was just checked to be missing, so insertion can not fail.
+                let _ = self.directory.add_boxed_entry(name, entry);
+            }
+        }
+
+        self.directory.open(flags, mode, &mut iter::once(name).chain(path), server_end);
+    }
+
+    fn entry_info(&self) -> EntryInfo {
+        self.directory.entry_info()
+    }
+}
+
+impl<'entries, F> Unpin for LazyCache<'entries, F> where F: Unpin {}
+
+impl<'entries, F> Future for LazyCache<'entries, F>
+where
+    F: Fn(&str) -> Option<Box<dyn DirectoryEntry + 'entries>> + Unpin + Send,
+{
+    type Output = Void;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        Pin::new(&mut this.directory).poll(cx)
+    }
+}
+
+impl<'entries, F> FusedFuture for LazyCache<'entries, F>
+where
+    F: Fn(&str) -> Option<Box<dyn DirectoryEntry + 'entries>> + Unpin + Send,
+{
+    fn is_terminated(&self) -> bool {
+        self.directory.is_terminated()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{empty, empty_attr};
+    use super::{empty, empty_attr, lazy};
 
     use {
-        crate::directory::test_utils::{
-            run_server_client, run_server_client_with_open_requests_channel,
-            DirentsSameInodeBuilder,
+        crate::directory::{
+            entry::DirectoryEntry,
+            test_utils::{
+                run_server_client, run_server_client_with_open_requests_channel,
+                DirentsSameInodeBuilder,
+            },
         },
         crate::file::simple::{read_only_static, read_write, write_only},
         crate::test_utils::open_get_proxy,
@@ -608,7 +867,7 @@ mod tests {
             OPEN_RIGHT_READABLE, OPEN_RIGHT_WRITABLE, WATCH_MASK_ADDED, WATCH_MASK_EXISTING,
             WATCH_MASK_IDLE, WATCH_MASK_REMOVED,
         },
-        fuchsia_zircon::sys::ZX_OK,
+        fuchsia_zircon::{sys::ZX_OK, Status},
         futures::SinkExt,
         libc::{S_IRGRP, S_IROTH, S_IRUSR, S_IXGRP, S_IXOTH, S_IXUSR},
         proc_macro_hack::proc_macro_hack,
@@ -903,6 +1162,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn read_dirents_with_custom_comparator() {
+        let mut root = pseudo_directory! {
+            "banana" => read_only_static("Content"),
+            "apple" => read_only_static("Content"),
+            "cherry" => read_only_static("Content"),
+        };
+        root.set_sort_comparator(Some(Box::new(|a: &str, b: &str| b.cmp(a))));
+
+        run_server_client(OPEN_RIGHT_READABLE, root, |root| {
+            async move {
+                let mut expected = DirentsSameInodeBuilder::new(INO_UNKNOWN);
+                expected
+                    .add(DIRENT_TYPE_DIRECTORY, b".")
+                    .add(DIRENT_TYPE_FILE, b"cherry")
+                    .add(DIRENT_TYPE_FILE, b"banana")
+                    .add(DIRENT_TYPE_FILE, b"apple");
+
+                assert_read_dirents!(root, 1000, expected.into_vec());
+
+                assert_close!(root);
+            }
+        });
+    }
+
     #[test]
     fn open_writable_in_subdir() {
         let write_count = &AtomicUsize::new(0);
@@ -1740,4 +2024,107 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn entry_type_reports_file_and_directory() {
+        let mut root = empty();
+        root.add_entry("file", read_only_static("Content")).unwrap();
+        root.add_entry("dir", empty()).unwrap();
+
+        assert_eq!(root.entries.get("file").unwrap().entry_type(), DIRENT_TYPE_FILE);
+        assert_eq!(root.entries.get("dir").unwrap().entry_type(), DIRENT_TYPE_DIRECTORY);
+    }
+
+    #[test]
+    fn entry_count_reflects_added_and_removed_entries() {
+        let mut root = empty();
+        assert_eq!(root.entry_count(), 0);
+
+        root.add_entry("file", read_only_static("Content")).unwrap();
+        root.add_entry("dir", empty()).unwrap();
+        root.add_entry("other", read_only_static("Content")).unwrap();
+        assert_eq!(root.entry_count(), 3);
+
+        root.remove_entry("dir").unwrap();
+        assert_eq!(root.entry_count(), 2);
+    }
+
+    #[test]
+    fn remove_entry_removes_entry_from_listing() {
+        let mut root = empty();
+        root.add_entry("file", read_only_static("Content")).unwrap();
+        root.add_entry("dir", empty()).unwrap();
+
+        assert!(root.remove_entry("file").unwrap().entry_info().type_() == DIRENT_TYPE_FILE);
+
+        run_server_client(OPEN_RIGHT_READABLE, root, |root| {
+            async move {
+                let mut expected = DirentsSameInodeBuilder::new(INO_UNKNOWN);
+                expected.add(DIRENT_TYPE_DIRECTORY, b".").add(DIRENT_TYPE_DIRECTORY, b"dir");
+                assert_read_dirents!(root, 1000, expected.into_vec());
+
+                assert_close!(root);
+            }
+        });
+    }
+
+    #[test]
+    fn remove_entry_errors_when_entry_is_absent() {
+        let mut root = empty();
+        root.add_entry("file", read_only_static("Content")).unwrap();
+
+        assert_eq!(root.remove_entry("missing").unwrap_err(), Status::NOT_FOUND);
+    }
+
+    #[test]
+    fn lazy_factory_invoked_once_across_repeated_opens() {
+        let factory_calls = &AtomicUsize::new(0);
+        let root = lazy(move |name: &str| {
+            factory_calls.fetch_add(1, Ordering::Relaxed);
+            match name {
+                "file" => Some(Box::new(read_only_static("Content")) as Box<dyn DirectoryEntry>),
+                _ => None,
+            }
+        });
+
+        run_server_client(OPEN_RIGHT_READABLE, root, |root| {
+            async move {
+                let flags = OPEN_RIGHT_READABLE | OPEN_FLAG_DESCRIBE;
+
+                let file = open_get_file_proxy_assert_ok!(&root, flags, "file");
+                assert_read!(file, "Content");
+                assert_close!(file);
+
+                let file = open_get_file_proxy_assert_ok!(&root, flags, "file");
+                assert_read!(file, "Content");
+                assert_close!(file);
+
+                assert_eq!(factory_calls.load(Ordering::Relaxed), 1);
+
+                assert_close!(root);
+            }
+        });
+    }
+
+    #[test]
+    fn lazy_missing_entry_reinvokes_factory() {
+        let factory_calls = &AtomicUsize::new(0);
+        let root = lazy(move |_name: &str| {
+            factory_calls.fetch_add(1, Ordering::Relaxed);
+            None
+        });
+
+        run_server_client(OPEN_RIGHT_READABLE, root, |root| {
+            async move {
+                let flags = OPEN_RIGHT_READABLE | OPEN_FLAG_DESCRIBE;
+
+                open_as_file_assert_err!(&root, flags, "missing", Status::NOT_FOUND);
+                open_as_file_assert_err!(&root, flags, "missing", Status::NOT_FOUND);
+
+                assert_eq!(factory_calls.load(Ordering::Relaxed), 2);
+
+                assert_close!(root);
+            }
+        });
+    }
 }
@@ -192,6 +192,12 @@ impl Watchers {
         self.connections.len() != 0
     }
 
+    /// Returns the number of currently connected watchers.  Dead connections are not counted
+    /// until [`remove_dead`] has had a chance to prune them.
+    pub fn count(&self) -> usize {
+        self.connections.len()
+    }
+
     /// Closes all the currently connected watcher connections.  New connections may still be added
     /// via add().
     pub fn close_all(&mut self) {
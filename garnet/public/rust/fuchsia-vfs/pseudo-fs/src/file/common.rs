@@ -52,13 +52,17 @@ pub fn new_connection_validate_flags(
 
     let allowed_flags = OPEN_FLAG_DESCRIBE
         | if readable { OPEN_RIGHT_READABLE } else { 0 }
-        | if writable { OPEN_RIGHT_WRITABLE | OPEN_FLAG_TRUNCATE } else { 0 };
+        | if writable {
+            OPEN_RIGHT_WRITABLE | OPEN_FLAG_TRUNCATE | OPEN_FLAG_APPEND
+        } else {
+            0
+        };
 
     let prohibited_flags = (0 | if readable {
             OPEN_FLAG_TRUNCATE
         } else {
             0
-        } | if writable {
+        } | if !writable {
             OPEN_FLAG_APPEND
         } else {
             0
@@ -11,7 +11,7 @@ use {
     fidl::{encoding::OutOfLine, endpoints::ServerEnd},
     fidl_fuchsia_io::{
         FileMarker, FileObject, FileRequest, FileRequestStream, NodeAttributes, NodeInfo,
-        NodeMarker, SeekOrigin, INO_UNKNOWN, MODE_TYPE_FILE, OPEN_FLAG_DESCRIBE,
+        NodeMarker, SeekOrigin, INO_UNKNOWN, MODE_TYPE_FILE, OPEN_FLAG_APPEND, OPEN_FLAG_DESCRIBE,
         OPEN_RIGHT_READABLE, OPEN_RIGHT_WRITABLE,
     },
     fidl_fuchsia_mem,
@@ -407,17 +407,23 @@ impl FileConnection {
     }
 
     /// Write `content` at the current seek position in the buffer associated with the connection.
-    /// The corresponding pseudo file should have a size `capacity`. On a successful write, the
-    /// number of bytes written is sent to `responder` and also returned from this function. The seek
-    /// position is increased by the number of bytes written. On an error, the error code is sent to
-    /// `responder`, and this function returns `Ok(())`. If the responder returns an error, this
-    /// funtion forwards that error back to the caller.
+    /// The corresponding pseudo file should have a size `capacity`. If the connection was opened
+    /// with `OPEN_FLAG_APPEND`, the seek position is first moved to the end of the buffer, so that
+    /// the write always lands after any content written by other connections. On a successful
+    /// write, the number of bytes written is sent to `responder` and also returned from this
+    /// function. The seek position is increased by the number of bytes written. On an error, the
+    /// error code is sent to `responder`, and this function returns `Ok(())`. If the responder
+    /// returns an error, this funtion forwards that error back to the caller.
     // Strictly speaking, we do not need to use a callback here, but we do need it in the on_read()
     // case above, so, for consistency, on_write() has the same interface.
     fn handle_write<R>(&mut self, content: Vec<u8>, responder: R) -> Result<(), fidl::Error>
     where
         R: FnOnce(Status, u64) -> Result<(), fidl::Error>,
     {
+        if self.flags & OPEN_FLAG_APPEND != 0 {
+            self.seek = self.buffer.len() as u64;
+        }
+
         let actual = self.handle_write_at(self.seek, content, responder)?;
         self.seek += actual;
         Ok(())
@@ -56,7 +56,11 @@ use {
         task::Context,
         Future, Poll,
     },
-    std::{marker::Unpin, pin::Pin},
+    std::{
+        marker::Unpin,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    },
     void::Void,
 };
 
@@ -106,6 +110,54 @@ pub fn read_only_static(
     )
 }
 
+/// See [`read_only()`].  Serves the current contents of `buffer` on every open, so updates made to
+/// `buffer` from outside of this file (through the same `Arc<Mutex<..>>`) are visible the next
+/// time the file is opened for reading.  This is handy for exposing mutable state - e.g. in the
+/// hub - without having to re-add the node to its parent directory every time the state changes.
+pub fn from_shared(
+    buffer: Arc<Mutex<Vec<u8>>>,
+) -> PseudoFile<impl FnMut() -> Result<Vec<u8>, Status> + Send, fn(Vec<u8>) -> Result<(), Status>> {
+    PseudoFile::<_, fn(Vec<u8>) -> Result<(), Status>>::new(
+        DEFAULT_READ_ONLY_PROTECTION_ATTRIBUTES,
+        Some(move || Ok(buffer.lock().unwrap().clone())),
+        0,
+        None,
+    )
+}
+
+/// See [`read_only()`].  Convenient wrapper for [`read_only()`] for content that is expensive to
+/// produce all at once - `chunk_fn` is called repeatedly with the offset of the next chunk and the
+/// number of bytes still needed, until `len` bytes have been assembled, rather than requiring the
+/// caller to have the whole `len` bytes ready to hand over up front.  Note that, same as for any
+/// other `read_only*` constructor, the assembled content is still held in a single per-connection
+/// buffer for the lifetime of the connection - see the module documentation for details - so this
+/// only helps with how the content is produced, not with how it is served.
+pub fn read_only_stream<ChunkFn>(
+    len: u64,
+    mut chunk_fn: ChunkFn,
+) -> PseudoFile<impl FnMut() -> Result<Vec<u8>, Status> + Send, fn(Vec<u8>) -> Result<(), Status>>
+where
+    ChunkFn: FnMut(u64, u64) -> Result<Vec<u8>, Status> + Send,
+{
+    PseudoFile::<_, fn(Vec<u8>) -> Result<(), Status>>::new(
+        DEFAULT_READ_ONLY_PROTECTION_ATTRIBUTES,
+        Some(move || {
+            let mut content = Vec::with_capacity(len as usize);
+            while (content.len() as u64) < len {
+                let offset = content.len() as u64;
+                let chunk = chunk_fn(offset, len - offset)?;
+                if chunk.is_empty() {
+                    break;
+                }
+                content.extend_from_slice(&chunk);
+            }
+            Ok(content)
+        }),
+        0,
+        None,
+    )
+}
+
 /// Same as [`read_only()`] but also allows to select custom attributes for the POSIX emulation
 /// layer.  Note that only the MODE_PROTECTION_MASK part of the protection_attributes argument will
 /// be stored.
@@ -307,6 +359,11 @@ where
         }
     }
 
+    /// Returns the number of connections currently open to this file.
+    pub fn open_connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
     /// Attaches a new connection, client end `server_end`, to this object.  Any error are reported
     /// as `OnOpen` events on the `server_end` itself.
     fn add_connection(&mut self, flags: u32, mode: u32, server_end: ServerEnd<NodeMarker>) {
@@ -501,9 +558,9 @@ mod tests {
         },
         fidl::endpoints::{create_proxy, ServerEnd},
         fidl_fuchsia_io::{
-            FileMarker, NodeAttributes, INO_UNKNOWN, MODE_TYPE_FILE, OPEN_FLAG_DESCRIBE,
-            OPEN_FLAG_NODE_REFERENCE, OPEN_FLAG_POSIX, OPEN_FLAG_TRUNCATE, OPEN_RIGHT_READABLE,
-            OPEN_RIGHT_WRITABLE,
+            FileMarker, NodeAttributes, INO_UNKNOWN, MODE_TYPE_FILE, OPEN_FLAG_APPEND,
+            OPEN_FLAG_DESCRIBE, OPEN_FLAG_NODE_REFERENCE, OPEN_FLAG_POSIX, OPEN_FLAG_TRUNCATE,
+            OPEN_RIGHT_READABLE, OPEN_RIGHT_WRITABLE,
         },
         fuchsia_async as fasync,
         fuchsia_zircon::sys::ZX_OK,
@@ -511,7 +568,10 @@ mod tests {
         futures::future::join,
         futures::SinkExt,
         libc::{S_IRGRP, S_IROTH, S_IRUSR, S_IWGRP, S_IWOTH, S_IWUSR, S_IXGRP, S_IXOTH, S_IXUSR},
-        std::sync::atomic::{AtomicUsize, Ordering},
+        std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
     };
 
     #[test]
@@ -683,6 +743,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_only_open_with_write_right_is_rejected() {
+        // This is the pattern used by the hub: files are created with `read_only(...)`, and any
+        // connection that asks for write rights - even alongside read rights - must be refused at
+        // open time, rather than being accepted and then failing writes later.
+        run_server_client_with_open_requests_channel(
+            read_only_static("Read only test"),
+            |mut open_sender| {
+                async move {
+                    let (proxy, server_end) = create_proxy::<FileMarker>()
+                        .expect("Failed to create connection endpoints");
+
+                    let flags = OPEN_RIGHT_READABLE | OPEN_RIGHT_WRITABLE | OPEN_FLAG_DESCRIBE;
+                    open_sender.send((flags, 0, server_end)).await.unwrap();
+                    assert_event!(proxy, FileEvent::OnOpen_ { s, info }, {
+                        assert_eq!(Status::from_raw(s), Status::ACCESS_DENIED);
+                        assert_eq!(info, None);
+                    });
+                }
+            },
+        );
+    }
+
     #[test]
     fn read_only_str_read() {
         run_server_client(
@@ -707,6 +790,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn from_shared_reflects_updates_made_outside_the_file() {
+        let buffer = Arc::new(Mutex::new(b"Initial content".to_vec()));
+
+        {
+            let buffer = buffer.clone();
+            run_server_client(OPEN_RIGHT_READABLE, from_shared(buffer), |proxy| {
+                async move {
+                    assert_read!(proxy, "Initial content");
+                    assert_close!(proxy);
+                }
+            });
+        }
+
+        *buffer.lock().unwrap() = b"Updated content".to_vec();
+
+        run_server_client(OPEN_RIGHT_READABLE, from_shared(buffer), |proxy| {
+            async move {
+                assert_read!(proxy, "Updated content");
+                assert_close!(proxy);
+            }
+        });
+    }
+
+    #[test]
+    fn read_only_stream_assembles_chunks_in_order() {
+        let content = b"Some rather long file content, split into uneven chunks";
+        let chunk_sizes = [3, 1, 10, 40];
+
+        run_server_client(
+            OPEN_RIGHT_READABLE,
+            read_only_stream(content.len() as u64, move |offset, remaining| {
+                let offset = offset as usize;
+                let chunk_size = chunk_sizes[offset % chunk_sizes.len()].min(remaining as usize);
+                let end = offset + chunk_size;
+                Ok(content[offset..end].to_vec())
+            }),
+            |proxy| {
+                async move {
+                    assert_read!(proxy, std::str::from_utf8(content).unwrap());
+                    assert_close!(proxy);
+                }
+            },
+        );
+    }
+
     #[test]
     fn write_only_write() {
         run_server_client(
@@ -839,6 +968,58 @@ mod tests {
         assert_eq!(write_attempt, 1);
     }
 
+    #[test]
+    fn write_append_preserves_prior_content_across_reopen() {
+        // Acts as the persistent storage backing the file across the two opens below, so that
+        // the second open starts from whatever the first open left behind - the same way a real
+        // on_read/on_write pair would be backed by some actual storage.
+        let storage = Arc::new(Mutex::new(Vec::<u8>::new()));
+
+        {
+            let storage = storage.clone();
+            run_server_client(
+                OPEN_RIGHT_WRITABLE | OPEN_FLAG_APPEND,
+                write_only(100, move |content| {
+                    *storage.lock().unwrap() = content;
+                    Ok(())
+                }),
+                |proxy| {
+                    async move {
+                        assert_write!(proxy, "Hello, ");
+                        assert_close!(proxy);
+                    }
+                },
+            );
+        }
+
+        {
+            let storage_for_read = storage.clone();
+            let storage_for_write = storage.clone();
+            run_server_client(
+                OPEN_RIGHT_READABLE | OPEN_RIGHT_WRITABLE | OPEN_FLAG_APPEND,
+                read_write(
+                    move || Ok(storage_for_read.lock().unwrap().clone()),
+                    100,
+                    move |content| {
+                        *storage_for_write.lock().unwrap() = content;
+                        Ok(())
+                    },
+                ),
+                |proxy| {
+                    async move {
+                        // Without honoring OPEN_FLAG_APPEND, this write would land at seek
+                        // position 0 - the position left over from connection creation - and
+                        // clobber the content written during the first open.
+                        assert_write!(proxy, "world!");
+                        assert_close!(proxy);
+                    }
+                },
+            );
+        }
+
+        assert_eq!(&*storage.lock().unwrap(), b"Hello, world!");
+    }
+
     #[test]
     fn read_error() {
         let mut read_attempt = 0;
@@ -1758,6 +1939,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn open_connection_count_tracks_connections_opening_and_closing() {
+        let mut exec = fasync::Executor::new().expect("Executor creation failed");
+
+        let mut file = read_only_static("Content");
+        assert_eq!(file.open_connection_count(), 0);
+
+        let (proxy1, server_end1) =
+            create_proxy::<FileMarker>().expect("Failed to create connection endpoints");
+        file.add_connection(OPEN_RIGHT_READABLE, 0, server_end1);
+        assert_eq!(file.open_connection_count(), 1);
+
+        let (proxy2, server_end2) =
+            create_proxy::<FileMarker>().expect("Failed to create connection endpoints");
+        file.add_connection(OPEN_RIGHT_READABLE, 0, server_end2);
+        assert_eq!(file.open_connection_count(), 2);
+
+        drop(proxy1);
+        assert_eq!(exec.run_until_stalled(&mut file), Poll::Pending);
+        assert_eq!(file.open_connection_count(), 1);
+
+        drop(proxy2);
+        assert_eq!(exec.run_until_stalled(&mut file), Poll::Pending);
+        assert_eq!(file.open_connection_count(), 0);
+    }
+
     #[test]
     /// This test checks a somewhat non-trivial case.  Two clients are connected to the same file,
     /// and we want to make sure that they get individual buffers.  The file content will be
@@ -133,6 +133,10 @@ fn handle_hop_by_hop_options_ext_hdr<
             // Safely skip and continue, as we know that if we parsed an unrecognized
             // option, the option's action was set to skip and continue.
             HopByHopOptionData::Unrecognized { kind, len, data } => {}
+            // The Jumbo Payload option's consistency with the fixed header's
+            // Payload Length was already checked while parsing extension
+            // headers, so there is nothing left to do with it here.
+            HopByHopOptionData::JumboPayload(_) => {}
         }
     }
 
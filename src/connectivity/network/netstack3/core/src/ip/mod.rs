@@ -39,7 +39,7 @@ use crate::ip::mld::{MldInterface, MldReportDelay};
 use crate::ip::path_mtu::{handle_pmtu_timer, IpLayerPathMtuCache, PmtuTimerId};
 use crate::ip::reassembly::{
     handle_reassembly_timer, process_fragment, reassemble_packet, FragmentCacheKey,
-    FragmentProcessingState, IpLayerFragmentCache,
+    FragmentProcessingState, IpLayerFragmentCache, OverlapMode,
 };
 use crate::wire::icmp::{Icmpv4ParameterProblem, Icmpv6ParameterProblem};
 use crate::wire::ipv4::{Ipv4PacketBuilder, Ipv4PacketBuilderWithOptions};
@@ -48,6 +48,7 @@ use icmp::{
     send_icmpv4_parameter_problem, send_icmpv6_parameter_problem, should_send_icmpv4_error,
     should_send_icmpv6_error, IcmpEventDispatcher, IcmpStateBuilder,
 };
+use path_mtu::PmtuEventDispatcher;
 
 // default IPv4 TTL or IPv6 hops
 const DEFAULT_TTL: u8 = 64;
@@ -145,12 +146,18 @@ impl<I: Ip, D: EventDispatcher> TransportIpContext<I> for Context<D> {
 pub struct IpStateBuilder {
     forward_v4: bool,
     forward_v6: bool,
+    ipv4_fragment_overlap_mode: OverlapMode,
     icmp: IcmpStateBuilder,
 }
 
 impl Default for IpStateBuilder {
     fn default() -> IpStateBuilder {
-        IpStateBuilder { forward_v4: false, forward_v6: false, icmp: IcmpStateBuilder::default() }
+        IpStateBuilder {
+            forward_v4: false,
+            forward_v6: false,
+            ipv4_fragment_overlap_mode: OverlapMode::Strict,
+            icmp: IcmpStateBuilder::default(),
+        }
     }
 }
 
@@ -165,6 +172,17 @@ impl IpStateBuilder {
         self
     }
 
+    /// Set how IPv4 fragment reassembly handles a newly received fragment
+    /// that overlaps with fragment blocks already received for the same
+    /// packet (default: [`OverlapMode::Strict`]).
+    ///
+    /// IPv6 reassembly always uses [`OverlapMode::Strict`], per RFC 8200
+    /// section 4.5.
+    pub fn ipv4_fragment_overlap_mode(&mut self, overlap_mode: OverlapMode) -> &mut Self {
+        self.ipv4_fragment_overlap_mode = overlap_mode;
+        self
+    }
+
     /// Get the builder for the ICMP state.
     pub fn icmp_builder(&mut self) -> &mut IcmpStateBuilder {
         &mut self.icmp
@@ -175,7 +193,9 @@ impl IpStateBuilder {
             v4: IpLayerStateInner {
                 forward: self.forward_v4,
                 table: ForwardingTable::default(),
-                fragment_cache: IpLayerFragmentCache::new(),
+                fragment_cache: IpLayerFragmentCache::with_overlap_mode(
+                    self.ipv4_fragment_overlap_mode,
+                ),
                 path_mtu: IpLayerPathMtuCache::new(),
             },
             v6: IpLayerStateInner {
@@ -218,7 +238,7 @@ impl<D: EventDispatcher> IpLayerState<D> {
 struct IpLayerStateInner<I: Ip, D: EventDispatcher> {
     forward: bool,
     table: ForwardingTable<I>,
-    fragment_cache: IpLayerFragmentCache<I>,
+    fragment_cache: IpLayerFragmentCache<I, D::Instant>,
     path_mtu: IpLayerPathMtuCache<I, D::Instant>,
 }
 
@@ -241,15 +261,15 @@ fn get_state_inner_mut<I: Ip, D: EventDispatcher>(
 }
 
 // These `AsRef` and `AsMut` impls provide us with an implementation of
-// `StateContext<(), IpLayerFragmentCache<I>>`.
-impl<I: Ip, D: EventDispatcher> AsRef<IpLayerFragmentCache<I>> for Context<D> {
-    fn as_ref(&self) -> &IpLayerFragmentCache<I> {
+// `StateContext<(), IpLayerFragmentCache<I, D::Instant>>`.
+impl<I: Ip, D: EventDispatcher> AsRef<IpLayerFragmentCache<I, D::Instant>> for Context<D> {
+    fn as_ref(&self) -> &IpLayerFragmentCache<I, D::Instant> {
         &get_state_inner(self.state()).fragment_cache
     }
 }
 
-impl<I: Ip, D: EventDispatcher> AsMut<IpLayerFragmentCache<I>> for Context<D> {
-    fn as_mut(&mut self) -> &mut IpLayerFragmentCache<I> {
+impl<I: Ip, D: EventDispatcher> AsMut<IpLayerFragmentCache<I, D::Instant>> for Context<D> {
+    fn as_mut(&mut self) -> &mut IpLayerFragmentCache<I, D::Instant> {
         &mut get_state_inner_mut(self.state_mut()).fragment_cache
     }
 }
@@ -271,7 +291,7 @@ impl<I: Ip, D: EventDispatcher> AsMut<IpLayerPathMtuCache<I, D::Instant>> for Co
 /// An event dispatcher for the IP layer.
 ///
 /// See the `EventDispatcher` trait in the crate root for more details.
-pub trait IpLayerEventDispatcher: IcmpEventDispatcher {}
+pub trait IpLayerEventDispatcher: IcmpEventDispatcher + PmtuEventDispatcher {}
 
 /// The identifier for timer events in the IP layer.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -554,8 +574,12 @@ macro_rules! process_fragment {
                 // Attempt to reassemble the packet.
                 match reassemble_packet::<$ip, _, _, _>($ctx, &key, buffer.buffer_view_mut()) {
                     // Successfully reassembled the packet, handle it.
-                    Ok(packet) => {
-                        trace!("receive_ip_packet: fragmented, reassembled packet: {:?}", packet);
+                    Ok((packet, reassembly_duration)) => {
+                        trace!(
+                            "receive_ip_packet: fragmented, reassembled packet in {:?}: {:?}",
+                            reassembly_duration,
+                            packet
+                        );
                         // TODO(joshlf):
                         // - Check for already-expired TTL?
                         let (src_ip, dst_ip, proto, meta) = drop_packet!(packet);
@@ -579,8 +603,12 @@ macro_rules! process_fragment {
             }
             // Cannot proceed since we need more fragments before we
             // can reassemble a packet.
-            FragmentProcessingState::NeedMoreFragments => {
-                trace!("receive_ip_packet: fragmented, need more before reassembly")
+            FragmentProcessingState::NeedMoreFragments { packet_len } => {
+                trace!(
+                    "receive_ip_packet: fragmented, need more before reassembly \
+                     (final size: {:?})",
+                    packet_len
+                )
             }
             // TODO(ghanan): Handle invalid fragments.
             FragmentProcessingState::InvalidFragment => {
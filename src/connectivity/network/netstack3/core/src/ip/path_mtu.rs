@@ -10,7 +10,7 @@ use std::marker::PhantomData;
 use std::time::Duration;
 
 use log::trace;
-use net_types::ip::{Ip, IpAddress};
+use net_types::ip::{Ip, IpAddr, IpAddress};
 use specialize_ip_macro::specialize_ip;
 
 use crate::context::{InstantContext, StateContext, TimerContext};
@@ -49,6 +49,14 @@ const MAINTENANCE_PERIOD: Duration = Duration::from_secs(3600);
 // TODO(ghanan): Make this value configurable by runtime options.
 const PMTU_STALE_TIMEOUT: Duration = Duration::from_secs(10800);
 
+/// Default quiet period after which a PMTU probe is attempted, per
+/// [RFC 1981 section 5.3], if probing has been enabled (see
+/// [`IpLayerPathMtuCache::set_probe_interval`]).
+///
+/// [RFC 1981 section 5.3]: https://tools.ietf.org/html/rfc1981#section-5.3
+// TODO(ghanan): Make this value configurable by runtime options.
+pub(crate) const DEFAULT_PMTU_PROBE_INTERVAL: Duration = Duration::from_secs(600);
+
 /// Common MTU values taken from [RFC 1191 section 7.1].
 ///
 /// This list includes lower bounds of groups of common MTU values that
@@ -87,6 +95,35 @@ impl<
 {
 }
 
+/// An event dispatcher for the path MTU cache.
+///
+/// See the `EventDispatcher` trait in the crate root for more details.
+pub trait PmtuEventDispatcher {
+    /// A new PMTU was discovered for the path between `src_ip` and `dst_ip`.
+    ///
+    /// This fires only the first time a path's PMTU becomes known - that is,
+    /// when a new cache entry is created, not when an existing entry's PMTU
+    /// is later updated (whether to an exact or an estimated value). This
+    /// lets routing associate the new PMTU with a route without needing to
+    /// diff cache snapshots to detect creation.
+    fn on_new_pmtu(&mut self, src_ip: IpAddr, dst_ip: IpAddr, pmtu: u32) {
+        log_unimplemented!((), "PmtuEventDispatcher::on_new_pmtu: not implemented");
+    }
+
+    /// The PMTU between `src_ip` and `dst_ip` was bumped up to `pmtu` by a
+    /// probe, after the path had been quiet for the configured probe
+    /// interval (see [`IpLayerPathMtuCache::set_probe_interval`]).
+    ///
+    /// This is purely informational; the cache has already recorded `pmtu`
+    /// as the path's current PMTU by the time this fires. Callers that care
+    /// about rediscovering a path's true PMTU (as opposed to just resuming
+    /// downward estimates) can use this as a signal to, e.g., resend a probe
+    /// packet of size `pmtu`.
+    fn on_pmtu_probe(&mut self, src_ip: IpAddr, dst_ip: IpAddr, pmtu: u32) {
+        log_unimplemented!((), "PmtuEventDispatcher::on_pmtu_probe: not implemented");
+    }
+}
+
 /// Get the minimum MTU size for a specific IP version, identified by `I`.
 #[specialize_ip]
 pub(crate) fn min_mtu<I: Ip>() -> u32 {
@@ -110,16 +147,98 @@ pub(crate) fn get_pmtu<A: IpAddress, C: PmtuContext<A::Version>>(
     ctx.get_state(()).get_pmtu(src_ip, dst_ip)
 }
 
-/// Update the PMTU between `src_ip` and `dst_ip`.
+/// Get the PMTU between `src_ip` and `dst_ip`, minus `ext_hdrs_len` bytes of
+/// extension header overhead.
 ///
-/// See [`update_pmtu_inner`].
-pub(crate) fn update_pmtu<A: IpAddress, C: PmtuContext<A::Version>>(
+/// When emitting a packet that carries a chain of IP extension headers
+/// (e.g. IPv6 extension headers), the send path must size the transport
+/// payload so that the fixed header, extension headers, and payload
+/// together still fit within the path's PMTU. `ext_hdrs_len` should be the
+/// total serialized length, in bytes, of the extension header chain built
+/// for the packet being sent; this subtracts it from the cached PMTU so
+/// callers are left with the budget available for the transport payload.
+///
+/// Returns `None` under the same conditions as [`get_pmtu`], namely, if
+/// there is no PMTU cached for the `src_ip` -> `dst_ip` path.
+pub(crate) fn get_pmtu_minus_ext_hdrs<A: IpAddress, C: PmtuContext<A::Version>>(
+    ctx: &C,
+    src_ip: A,
+    dst_ip: A,
+    ext_hdrs_len: usize,
+) -> Option<u32> {
+    let pmtu = get_pmtu(ctx, src_ip, dst_ip)?;
+    Some(pmtu.saturating_sub(ext_hdrs_len as u32))
+}
+
+/// Get the [`PmtuSource`] of the PMTU between `src_ip` and `dst_ip`.
+///
+/// See [`IpLayerPathMtuCache::get_pmtu_source`].
+pub(crate) fn get_pmtu_source<A: IpAddress, C: PmtuContext<A::Version>>(
+    ctx: &C,
+    src_ip: A,
+    dst_ip: A,
+) -> Option<PmtuSource> {
+    ctx.get_state(()).get_pmtu_source(src_ip, dst_ip)
+}
+
+/// Invalidate all cached PMTU entries toward destinations within
+/// `prefix`/`prefix_len`.
+///
+/// See [`IpLayerPathMtuCache::invalidate_prefix`].
+pub(crate) fn invalidate_pmtu_prefix<A: IpAddress, C: PmtuContext<A::Version>>(
     ctx: &mut C,
+    prefix: A,
+    prefix_len: u8,
+) {
+    ctx.get_state_mut(()).invalidate_prefix(prefix, prefix_len);
+}
+
+/// Empty the PMTU cache for `I` and cancel any scheduled maintenance timer.
+///
+/// See [`IpLayerPathMtuCache::clear`].
+pub(crate) fn clear_pmtu<I: Ip, C: PmtuContext<I>>(ctx: &mut C) {
+    ctx.get_state_mut(()).clear();
+    ctx.cancel_timer(PmtuTimerId(PhantomData));
+}
+
+/// The reason a PMTU update was rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum UpdatePmtuError {
+    /// The requested PMTU was less than the IP-specific minimum MTU (see
+    /// [`min_mtu`]).
+    ///
+    /// `min_mtu` is the minimum that was violated, and `prev_mtu` is the
+    /// PMTU known by the cache before the rejected update, if any.
+    BelowMinMtu { min_mtu: u32, prev_mtu: Option<u32> },
+    /// The requested PMTU was less than the cache's configured PMTU floor
+    /// (see [`IpLayerPathMtuCache::floor`]).
+    ///
+    /// `floor` is the configured floor that was violated, and `prev_mtu` is
+    /// the PMTU known by the cache before the rejected update, if any.
+    BelowFloor { floor: u32, prev_mtu: Option<u32> },
+}
+
+impl UpdatePmtuError {
+    /// The PMTU known by the cache before the rejected update, if any.
+    pub(crate) fn prev_mtu(&self) -> Option<u32> {
+        match self {
+            UpdatePmtuError::BelowMinMtu { prev_mtu, .. } => *prev_mtu,
+            UpdatePmtuError::BelowFloor { prev_mtu, .. } => *prev_mtu,
+        }
+    }
+}
+
+/// Update the PMTU between `src_ip` and `dst_ip` to an exact value learned
+/// from an ICMP message.
+///
+/// See [`update_pmtu_inner`].
+pub(crate) fn update_pmtu<A: IpAddress, D: EventDispatcher>(
+    ctx: &mut Context<D>,
     src_ip: A,
     dst_ip: A,
     new_mtu: u32,
-) -> Result<Option<u32>, Option<u32>> {
-    let ret = update_pmtu_inner(ctx, src_ip, dst_ip, new_mtu);
+) -> Result<Option<u32>, UpdatePmtuError> {
+    let ret = update_pmtu_inner(ctx, src_ip, dst_ip, new_mtu, PmtuSource::Exact);
     trace!(
         "update_pmtu: Updated the PMTU between src {} and dest {} to {}; was {:?}",
         src_ip,
@@ -130,24 +249,66 @@ pub(crate) fn update_pmtu<A: IpAddress, C: PmtuContext<A::Version>>(
     ret
 }
 
+/// Get the configured PMTU floor for the cache associated with `A::Version`.
+///
+/// See [`IpLayerPathMtuCache::floor`].
+pub(crate) fn get_pmtu_floor<A: IpAddress, C: PmtuContext<A::Version>>(ctx: &C) -> u32 {
+    ctx.get_state(()).floor()
+}
+
+/// Set the PMTU floor for the cache associated with `A::Version`.
+///
+/// See [`IpLayerPathMtuCache::set_floor`].
+pub(crate) fn set_pmtu_floor<A: IpAddress, C: PmtuContext<A::Version>>(ctx: &mut C, floor: u32) {
+    ctx.get_state_mut(()).set_floor(floor);
+}
+
 /// Update the PMTU between `src_ip` and `dst_ip` if `new_mtu` is less than
 /// the current PMTU and does not violate the minimum MTU size requirements
 /// for an IP.
 ///
+/// If `new_mtu` is less than the cache's configured PMTU floor (see
+/// [`IpLayerPathMtuCache::floor`]), the update is rejected; this guards
+/// against forged ICMP "packet too big" messages attempting to downgrade
+/// the PMTU below a value the caller knows to be safe.
+///
 /// See [`IpLayerPathMtuCache::update`].
 pub(crate) fn update_pmtu_if_less<A: IpAddress, D: EventDispatcher>(
     ctx: &mut Context<D>,
     src_ip: A,
     dst_ip: A,
     new_mtu: u32,
-) -> Result<Option<u32>, Option<u32>> {
+) -> Result<Option<u32>, UpdatePmtuError> {
+    update_pmtu_if_less_inner(ctx, src_ip, dst_ip, new_mtu, PmtuSource::Exact)
+}
+
+/// The implementation of [`update_pmtu_if_less`], parameterized on the
+/// `source` the resulting PMTU data should be attributed to.
+fn update_pmtu_if_less_inner<A: IpAddress, D: EventDispatcher>(
+    ctx: &mut Context<D>,
+    src_ip: A,
+    dst_ip: A,
+    new_mtu: u32,
+    source: PmtuSource,
+) -> Result<Option<u32>, UpdatePmtuError> {
     let prev_mtu = get_pmtu(ctx, src_ip, dst_ip);
+    let floor = get_pmtu_floor::<A, _>(ctx);
+
+    if new_mtu < floor {
+        trace!(
+            "update_pmtu_if_less: Not updating the PMTU between src {} and dest {} to {} as it is below the configured floor",
+            src_ip,
+            dst_ip,
+            new_mtu
+        );
+        return Err(UpdatePmtuError::BelowFloor { floor, prev_mtu });
+    }
 
     match prev_mtu {
         // No PMTU exists so update.
-        None => update_pmtu(ctx, src_ip, dst_ip, new_mtu),
+        None => update_pmtu_inner(ctx, src_ip, dst_ip, new_mtu, source),
         // A PMTU exists but it is greater than `new_mtu` so update.
-        Some(mtu) if new_mtu < mtu => update_pmtu(ctx, src_ip, dst_ip, new_mtu),
+        Some(mtu) if new_mtu < mtu => update_pmtu_inner(ctx, src_ip, dst_ip, new_mtu, source),
         // A PMTU exists but it is less than or equal to `new_mtu` so no need to update.
         _ => {
             trace!("update_pmtu_if_less: Not updating the PMTU  between src {} and dest {} to {}; is {}", src_ip, dst_ip, new_mtu, prev_mtu.unwrap());
@@ -156,6 +317,21 @@ pub(crate) fn update_pmtu_if_less<A: IpAddress, D: EventDispatcher>(
     }
 }
 
+/// The reason [`update_pmtu_next_lower`] did not result in a PMTU update.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum UpdatePmtuNextLowerError {
+    /// No plateau value lower than the estimate passed to
+    /// [`update_pmtu_next_lower`] exists (or the configured PMTU floor
+    /// rejected the one that does), so there was no lower PMTU to update to.
+    NoLowerPmtu(Option<u32>),
+    /// This path's PMTU has already been stepped down through every
+    /// available plateau since the last exact PMTU update, so no further
+    /// downward estimate will be attempted; doing so would just churn
+    /// without making progress. The path will resume accepting downward
+    /// estimates once an exact PMTU is learned via [`update_pmtu`].
+    EstimatesExhausted(Option<u32>),
+}
+
 /// Update the PMTU between `src_ip` and `dst_ip` to the next lower estimate
 /// from `from`.
 ///
@@ -163,13 +339,27 @@ pub(crate) fn update_pmtu_if_less<A: IpAddress, D: EventDispatcher>(
 /// exists that does not violate IP specific minimum MTU requirements and
 /// it is less than the current PMTU estimate, `a`. Returns `Err(a)`
 /// otherwise, where `a` is the same `a` in the success case.
+///
+/// To avoid churning through estimates indefinitely as successive ICMP
+/// errors arrive for a path, this stops attempting downward estimates, and
+/// returns [`UpdatePmtuNextLowerError::EstimatesExhausted`], once the path
+/// has been stepped down through every plateau in the cache's configured
+/// plateau table (by default, [`PMTU_PLATEAUS`]) since the last exact PMTU
+/// update.
 pub(crate) fn update_pmtu_next_lower<A: IpAddress, D: EventDispatcher>(
     ctx: &mut Context<D>,
     src_ip: A,
     dst_ip: A,
     from: u32,
-) -> Result<(Option<u32>, u32), Option<u32>> {
-    if let Some(next_pmtu) = next_lower_pmtu_plateau(from) {
+) -> Result<(Option<u32>, u32), UpdatePmtuNextLowerError> {
+    let plateaus = get_pmtu_plateaus::<A, _>(ctx).to_vec();
+
+    if get_pmtu_estimates_since_exact(ctx, src_ip, dst_ip) >= plateaus.len() as u32 {
+        trace!("update_pmtu_next_lower: Not updating PMTU between src {} and dest {} as all plateau estimates have already been exhausted", src_ip, dst_ip);
+        return Err(UpdatePmtuNextLowerError::EstimatesExhausted(get_pmtu(ctx, src_ip, dst_ip)));
+    }
+
+    if let Some(next_pmtu) = next_lower_pmtu_plateau(from, &plateaus) {
         trace!(
             "update_pmtu_next_lower: Attempting to update PMTU between src {} and dest {} to {}",
             src_ip,
@@ -177,23 +367,39 @@ pub(crate) fn update_pmtu_next_lower<A: IpAddress, D: EventDispatcher>(
             next_pmtu
         );
 
-        update_pmtu_if_less(ctx, src_ip, dst_ip, next_pmtu).map(|x| (x, next_pmtu))
+        update_pmtu_if_less_inner(ctx, src_ip, dst_ip, next_pmtu, PmtuSource::Estimated)
+            .map(|x| (x, next_pmtu))
+            .map_err(|e| UpdatePmtuNextLowerError::NoLowerPmtu(e.prev_mtu()))
     } else {
         // TODO(ghanan): Should we make sure the current PMTU value is set to the
         //               IP specific minimum MTU value?
         trace!("update_pmtu_next_lower: Not updating PMTU between src {} and dest {} as there is no lower PMTU value from {}", src_ip, dst_ip, from);
-        Err(get_pmtu(ctx, src_ip, dst_ip))
+        Err(UpdatePmtuNextLowerError::NoLowerPmtu(get_pmtu(ctx, src_ip, dst_ip)))
     }
 }
 
-/// Get next lower PMTU plateau value, if one exists.
-fn next_lower_pmtu_plateau(start_mtu: u32) -> Option<u32> {
-    for i in 0..PMTU_PLATEAUS.len() {
-        let pmtu = PMTU_PLATEAUS[i];
+/// Get the number of consecutive downward PMTU estimates recorded for the
+/// path between `src_ip` and `dst_ip` since the last exact PMTU update.
+///
+/// See [`IpLayerPathMtuCache::estimates_since_exact`].
+fn get_pmtu_estimates_since_exact<A: IpAddress, C: PmtuContext<A::Version>>(
+    ctx: &C,
+    src_ip: A,
+    dst_ip: A,
+) -> u32 {
+    ctx.get_state(()).estimates_since_exact(src_ip, dst_ip)
+}
+
+/// Get next lower PMTU plateau value from `plateaus`, if one exists.
+///
+/// `plateaus` must be sorted in descending order, as [`PMTU_PLATEAUS`] is.
+fn next_lower_pmtu_plateau(start_mtu: u32, plateaus: &[u32]) -> Option<u32> {
+    for i in 0..plateaus.len() {
+        let pmtu = plateaus[i];
 
         if pmtu < start_mtu {
             // Current PMTU is less than `start_mtu` and we know
-            // `PMTU_PLATEAUS` is sorted so this is the next best
+            // `plateaus` is sorted so this is the next best
             // PMTU estimate.
             return Some(pmtu);
         }
@@ -202,6 +408,64 @@ fn next_lower_pmtu_plateau(start_mtu: u32) -> Option<u32> {
     None
 }
 
+/// Get next higher PMTU plateau value from `plateaus`, if one exists.
+///
+/// This is the mirror of [`next_lower_pmtu_plateau`], used to pick a target
+/// for a PMTU probe: the smallest plateau strictly greater than `start_mtu`.
+/// `plateaus` must be sorted in descending order, as [`PMTU_PLATEAUS`] is.
+fn next_higher_pmtu_plateau(start_mtu: u32, plateaus: &[u32]) -> Option<u32> {
+    for i in (0..plateaus.len()).rev() {
+        let pmtu = plateaus[i];
+
+        if pmtu > start_mtu {
+            // `plateaus` is sorted in descending order, so walking it in
+            // reverse gives ascending order; the first plateau greater than
+            // `start_mtu` we see is the smallest one that is.
+            return Some(pmtu);
+        }
+    }
+
+    None
+}
+
+/// Get the configured PMTU probe interval for the cache associated with
+/// `A::Version`.
+///
+/// See [`IpLayerPathMtuCache::probe_interval`].
+pub(crate) fn get_pmtu_probe_interval<A: IpAddress, C: PmtuContext<A::Version>>(
+    ctx: &C,
+) -> Option<Duration> {
+    ctx.get_state(()).probe_interval()
+}
+
+/// Set the PMTU probe interval for the cache associated with `A::Version`.
+///
+/// See [`IpLayerPathMtuCache::set_probe_interval`].
+pub(crate) fn set_pmtu_probe_interval<A: IpAddress, C: PmtuContext<A::Version>>(
+    ctx: &mut C,
+    probe_interval: Option<Duration>,
+) {
+    ctx.get_state_mut(()).set_probe_interval(probe_interval);
+}
+
+/// Get the plateau values the cache associated with `A::Version` steps
+/// through when estimating a lower or higher PMTU.
+///
+/// See [`IpLayerPathMtuCache::plateaus`].
+pub(crate) fn get_pmtu_plateaus<A: IpAddress, C: PmtuContext<A::Version>>(ctx: &C) -> &[u32] {
+    ctx.get_state(()).plateaus()
+}
+
+/// Install a custom plateau table for the cache associated with `A::Version`.
+///
+/// See [`IpLayerPathMtuCache::set_plateaus`].
+pub(crate) fn set_pmtu_plateaus<A: IpAddress, C: PmtuContext<A::Version>>(
+    ctx: &mut C,
+    plateaus: Vec<u32>,
+) {
+    ctx.get_state_mut(()).set_plateaus(plateaus);
+}
+
 /// The key used to identify a path.
 ///
 /// This is a tuple of (src_ip, dst_ip) as a path is only identified
@@ -220,18 +484,41 @@ impl<A: IpAddress> PathMtuCacheKey<A> {
 /// some destination address.
 type PathMtuCache<A, I> = HashMap<PathMtuCacheKey<A>, PathMtuCacheData<I>>;
 
+/// Where a [`PathMtuCacheData`]'s PMTU value came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PmtuSource {
+    /// The PMTU is an exact value learned from an ICMP message (e.g. the
+    /// Next-Hop MTU field of an ICMPv4 Destination Unreachable message, or
+    /// the MTU field of an ICMPv6 Packet Too Big message).
+    Exact,
+    /// The PMTU is an estimate, taken from the next lower [`PMTU_PLATEAUS`]
+    /// value, because the ICMP message that triggered the update did not
+    /// carry an exact MTU.
+    Estimated,
+    /// The PMTU was bumped up to the next higher [`PMTU_PLATEAUS`] value by
+    /// a probe, after the path had been quiet for the configured probe
+    /// interval, to trigger rediscovery of a possibly-increased PMTU.
+    Probed,
+}
+
 /// IP layer PMTU cache data.
 pub(crate) struct PathMtuCacheData<I> {
     pmtu: u32,
     last_updated: I,
+    source: PmtuSource,
+    /// The number of consecutive [`PmtuSource::Estimated`] updates applied
+    /// to this entry since the last [`PmtuSource::Exact`] update (or since
+    /// the entry was created, if it has never received an exact update).
+    estimates_since_exact: u32,
 }
 
 impl<I: Instant> PathMtuCacheData<I> {
     /// Construct a new `PathMtuCacheData`.
     ///
     /// `last_updated` will be set to `now`.
-    fn new(pmtu: u32, now: I) -> Self {
-        Self { pmtu, last_updated: now }
+    fn new(pmtu: u32, now: I, source: PmtuSource) -> Self {
+        let estimates_since_exact = if source == PmtuSource::Estimated { 1 } else { 0 };
+        Self { pmtu, last_updated: now, source, estimates_since_exact }
     }
 }
 
@@ -239,12 +526,100 @@ impl<I: Instant> PathMtuCacheData<I> {
 pub(crate) struct IpLayerPathMtuCache<I: Ip, Instant> {
     cache: PathMtuCache<I::Addr, Instant>,
     timer_scheduled: bool,
+    next_maintenance: Option<Instant>,
+    /// The lowest PMTU this cache will ever record, regardless of what a
+    /// downstream PMTU update (e.g. from an ICMP message) requests.
+    ///
+    /// This is distinct from the absolute IP minimum MTU (see
+    /// [`min_mtu`]) and defaults to it; callers that know a path can
+    /// sustain a larger minimum may raise it to resist PMTU downgrade
+    /// attacks.
+    floor: u32,
+    /// The quiet interval after which an entry's PMTU is bumped up to the
+    /// next plateau to trigger rediscovery, per [RFC 1981 section 5.3].
+    ///
+    /// `None` (the default) disables probing entirely, so PMTU values only
+    /// ever decrease (or are reset by [`PMTU_STALE_TIMEOUT`]).
+    ///
+    /// [RFC 1981 section 5.3]: https://tools.ietf.org/html/rfc1981#section-5.3
+    probe_interval: Option<Duration>,
+    /// The plateau values this cache steps through when estimating a lower
+    /// or higher PMTU (see [`next_lower_pmtu_plateau`] and
+    /// [`next_higher_pmtu_plateau`]).
+    ///
+    /// Defaults to [`PMTU_PLATEAUS`]; callers that know the plateau values
+    /// common on their deployment's links may configure a custom table.
+    plateaus: Vec<u32>,
 }
 
-impl<I: Ip, Instant: Clone> IpLayerPathMtuCache<I, Instant> {
+impl<I: Ip, Instant: crate::Instant> IpLayerPathMtuCache<I, Instant> {
     /// Create a new `IpLayerPathMtuCache`.
     pub(crate) fn new() -> Self {
-        Self { cache: PathMtuCache::new(), timer_scheduled: false }
+        Self {
+            cache: PathMtuCache::new(),
+            timer_scheduled: false,
+            next_maintenance: None,
+            floor: min_mtu::<I>(),
+            probe_interval: None,
+            plateaus: PMTU_PLATEAUS.to_vec(),
+        }
+    }
+
+    /// Get the configured PMTU floor for this cache.
+    pub(crate) fn floor(&self) -> u32 {
+        self.floor
+    }
+
+    /// Set the PMTU floor for this cache.
+    ///
+    /// PMTU updates (e.g. [`update_pmtu_if_less`]) that would set a PMTU
+    /// below `floor` are rejected.
+    pub(crate) fn set_floor(&mut self, floor: u32) {
+        self.floor = floor;
+    }
+
+    /// Get the configured PMTU probe interval for this cache, if periodic
+    /// upward probing is enabled.
+    pub(crate) fn probe_interval(&self) -> Option<Duration> {
+        self.probe_interval
+    }
+
+    /// Enable or disable periodic PMTU probing.
+    ///
+    /// If `probe_interval` is `Some(duration)`, then each maintenance pass
+    /// (see [`run_maintenance`]) will bump the PMTU of any entry that has
+    /// gone `duration` without an update up to the next [`PMTU_PLATEAUS`]
+    /// value, and report the bump via [`PmtuEventDispatcher::on_pmtu_probe`],
+    /// to trigger rediscovery of a possibly-increased PMTU as recommended by
+    /// [RFC 1981 section 5.3]. If `probe_interval` is `None`, probing is
+    /// disabled and PMTU values only ever decrease or go stale.
+    ///
+    /// [`run_maintenance`]: IpLayerPathMtuCache::run_maintenance
+    /// [RFC 1981 section 5.3]: https://tools.ietf.org/html/rfc1981#section-5.3
+    pub(crate) fn set_probe_interval(&mut self, probe_interval: Option<Duration>) {
+        self.probe_interval = probe_interval;
+    }
+
+    /// Get the plateau values this cache steps through when estimating a
+    /// lower or higher PMTU.
+    ///
+    /// This is [`PMTU_PLATEAUS`] unless a custom table was installed with
+    /// [`set_plateaus`].
+    ///
+    /// [`set_plateaus`]: IpLayerPathMtuCache::set_plateaus
+    pub(crate) fn plateaus(&self) -> &[u32] {
+        &self.plateaus
+    }
+
+    /// Install a custom table of plateau values for this cache to step
+    /// through when estimating a lower or higher PMTU, in place of the
+    /// default [`PMTU_PLATEAUS`].
+    ///
+    /// `plateaus` must be sorted in descending order for [`next_lower_pmtu_plateau`]
+    /// and [`next_higher_pmtu_plateau`] to behave correctly, per the same
+    /// requirement documented on [`PMTU_PLATEAUS`].
+    pub(crate) fn set_plateaus(&mut self, plateaus: Vec<u32>) {
+        self.plateaus = plateaus;
     }
 
     /// Get the last updated [`Instant`] when the PMTU between `src_ip`
@@ -265,52 +640,216 @@ impl<I: Ip, Instant: Clone> IpLayerPathMtuCache<I, Instant> {
     pub(crate) fn get_pmtu(&self, src_ip: I::Addr, dst_ip: I::Addr) -> Option<u32> {
         self.cache.get(&PathMtuCacheKey::new(src_ip, dst_ip)).map(|x| x.pmtu)
     }
+
+    /// Get the PMTU from `src_ip` to each of `dst_ips` in one pass.
+    ///
+    /// Returns a `Vec` the same length as `dst_ips`, where element `i` is the
+    /// result of [`get_pmtu`] for `dst_ips[i]`.
+    ///
+    /// [`get_pmtu`]: IpLayerPathMtuCache::get_pmtu
+    pub(crate) fn get_pmtu_many(&self, src_ip: I::Addr, dst_ips: &[I::Addr]) -> Vec<Option<u32>> {
+        dst_ips.iter().map(|&dst_ip| self.get_pmtu(src_ip, dst_ip)).collect()
+    }
+
+    /// Get the [`PmtuSource`] of the PMTU between `src_ip` and `dst_ip`.
+    ///
+    /// Returns `None` if no PMTU is known by this `IpLayerPathMtuCache`.
+    pub(crate) fn get_pmtu_source(&self, src_ip: I::Addr, dst_ip: I::Addr) -> Option<PmtuSource> {
+        self.cache.get(&PathMtuCacheKey::new(src_ip, dst_ip)).map(|x| x.source)
+    }
+
+    /// Get the number of consecutive downward PMTU estimates recorded for
+    /// the path between `src_ip` and `dst_ip` since the last exact PMTU
+    /// update.
+    ///
+    /// Returns `0` if no PMTU is known, or if the most recent update was
+    /// [`PmtuSource::Exact`].
+    pub(crate) fn estimates_since_exact(&self, src_ip: I::Addr, dst_ip: I::Addr) -> u32 {
+        self.cache
+            .get(&PathMtuCacheKey::new(src_ip, dst_ip))
+            .map(|x| x.estimates_since_exact)
+            .unwrap_or(0)
+    }
+
+    /// Get the [`Instant`] at which the next scheduled PMTU maintenance
+    /// operation will fire.
+    ///
+    /// Returns `None` if no maintenance task is currently scheduled.
+    ///
+    /// [`Instant`]: crate::Instant
+    pub(crate) fn next_maintenance(&self) -> Option<Instant> {
+        self.next_maintenance.clone()
+    }
+
+    /// Invalidate all cached PMTU entries whose destination address falls
+    /// within `prefix`/`prefix_len`.
+    ///
+    /// This is useful when a route changes, since all PMTU data learned
+    /// while the old route was in effect may no longer be valid.
+    pub(crate) fn invalidate_prefix(&mut self, prefix: I::Addr, prefix_len: u8) {
+        let masked_prefix = prefix.mask(prefix_len);
+        self.cache.retain(|key, _| key.1.mask(prefix_len) != masked_prefix);
+    }
+
+    /// Empty the cache of all PMTU data.
+    ///
+    /// This only clears the cache's own state; it does not cancel a
+    /// scheduled maintenance timer, since doing so requires a `TimerContext`.
+    /// See [`clear_pmtu`] for the full operation, including timer
+    /// cancellation, useful on a major network event (e.g. the default route
+    /// changing) after which none of the cached PMTU data can be trusted.
+    ///
+    /// [`clear_pmtu`]: crate::ip::path_mtu::clear_pmtu
+    pub(crate) fn clear(&mut self) {
+        self.cache.clear();
+        self.timer_scheduled = false;
+        self.next_maintenance = None;
+    }
+
+    /// Update the PMTU between `src_ip` and `dst_ip` to `new_mtu`, attributed
+    /// to `source`, as of `now`.
+    ///
+    /// Returns `Err(UpdatePmtuError::BelowMinMtu { .. })` if `new_mtu` is less
+    /// than the minimum MTU for `I` (see [`min_mtu`]), without modifying the
+    /// cache. Otherwise returns `Ok(x)`, where `x` is the PMTU known by this
+    /// cache before being updated; `x` will be `None` if no PMTU was known
+    /// (i.e. this call created a brand new cache entry), else `Some(y)` where
+    /// `y` is the last estimate of the PMTU.
+    ///
+    /// This is the cache-only half of [`update_pmtu_inner`], split out so it
+    /// can be exercised with an injected `now` and without a full `Context`;
+    /// callers that need the side effects [`update_pmtu_inner`] layers on top
+    /// (scheduling maintenance, notifying [`PmtuEventDispatcher::on_new_pmtu`])
+    /// should go through that function instead.
+    pub(crate) fn update(
+        &mut self,
+        src_ip: I::Addr,
+        dst_ip: I::Addr,
+        new_mtu: u32,
+        source: PmtuSource,
+        now: Instant,
+    ) -> Result<Option<u32>, UpdatePmtuError> {
+        let min_mtu = min_mtu::<I>();
+        if new_mtu < min_mtu {
+            return Err(UpdatePmtuError::BelowMinMtu {
+                min_mtu,
+                prev_mtu: self.get_pmtu(src_ip, dst_ip),
+            });
+        }
+
+        let key = PathMtuCacheKey::new(src_ip, dst_ip);
+        if let Some(data) = self.cache.get_mut(&key) {
+            let prev_pmtu = data.pmtu;
+            data.pmtu = new_mtu;
+            data.last_updated = now;
+            data.source = source;
+            data.estimates_since_exact = match source {
+                PmtuSource::Exact | PmtuSource::Probed => 0,
+                PmtuSource::Estimated => data.estimates_since_exact + 1,
+            };
+            Ok(Some(prev_pmtu))
+        } else {
+            let val = PathMtuCacheData::new(new_mtu, now, source);
+            assert!(self.cache.insert(key, val).is_none());
+            Ok(None)
+        }
+    }
+
+    /// Perform PMTU maintenance, purging stale cached entries and probing
+    /// quiet ones (if enabled), as of `now`.
+    ///
+    /// This runs the same logic as [`handle_pmtu_timer`] without requiring a
+    /// timer to actually fire, which is useful for tests and for explicitly
+    /// triggering maintenance out-of-band. Returns the `(src_ip, dst_ip,
+    /// pmtu)` of every entry that was probed this pass, for the caller to
+    /// report via [`PmtuEventDispatcher::on_pmtu_probe`].
+    pub(crate) fn run_maintenance(&mut self, now: Instant) -> Vec<(I::Addr, I::Addr, u32)> {
+        // Remove all stale PMTU data to force restart the PMTU discovery process.
+        // This will be ok because the next time we try to send a packet to some
+        // node, we will update the PMTU with the first known potential PMTU (the
+        // first link's (connected to the node attempting PMTU discovery)) PMTU.
+        self.cache.retain(|_k, v| {
+            // We know the call to `duration_since` will not panic because all the
+            // entries in the cache should have been updated before this
+            // maintenance was run. Therefore, `now` will be greater than
+            // `v.last_updated` for all `v`.
+            //
+            // TODO(ghanan): Consider not simply deleting all stale PMTU data as
+            //               this may cause packets to be dropped every time the
+            //               data seems to get stale when really it is still valid.
+            //               Considering the use case, PMTU value changes may be
+            //               infrequent so it may be enough to just use a long stale
+            //               timer.
+            now.duration_since(v.last_updated) < PMTU_STALE_TIMEOUT
+        });
+
+        let probe_interval = match self.probe_interval {
+            Some(probe_interval) => probe_interval,
+            None => return Vec::new(),
+        };
+
+        let mut probed = Vec::new();
+        for (key, data) in self.cache.iter_mut() {
+            if now.duration_since(data.last_updated) < probe_interval {
+                continue;
+            }
+
+            // Bump the PMTU up to the next plateau to trigger rediscovery; if
+            // we are already at (or above) the highest plateau there is no
+            // higher estimate to probe with, so leave the entry alone.
+            if let Some(next_pmtu) = next_higher_pmtu_plateau(data.pmtu, &self.plateaus) {
+                data.pmtu = next_pmtu;
+                data.last_updated = now;
+                data.source = PmtuSource::Probed;
+                data.estimates_since_exact = 0;
+                probed.push((key.0, key.1, next_pmtu));
+            }
+        }
+
+        probed
+    }
 }
 
 /// Update the PMTU between `src_ip` and `dst_ip` if `new_mtu` does not violate
 /// IP specific minimum MTU requirements.
 ///
-/// Returns `Err(x)` if the `new_mtu` is less than the minimum MTU for an IP
-/// where the same `x` is returned in the success case (`Ok(x)`). `x` is the
-/// PMTU known by this `IpLayerPathMtuCache` before being updated. `x` will be
-/// `None` if no PMTU is known, else `Some(y)` where `y` is the last estimate of
-/// the PMTU.
+/// Returns `Err(UpdatePmtuError::BelowMinMtu { .. })` if `new_mtu` is less
+/// than the minimum MTU for an IP (see [`min_mtu`]). Otherwise returns
+/// `Ok(x)`, where `x` is the PMTU known by this `IpLayerPathMtuCache` before
+/// being updated; `x` will be `None` if no PMTU was known, else `Some(y)`
+/// where `y` is the last estimate of the PMTU.
 ///
 /// If there is no PMTU maintenance task scheduled yet, `update_pmtu` will
 /// schedule one to happen after a duration of `SCHEDULE_TIMEOUT` from the
 /// current time instant known by `dispatcher`.
-fn update_pmtu_inner<I: Ip, C: PmtuContext<I>>(
-    ctx: &mut C,
+///
+/// If this call creates a brand new cache entry (as opposed to updating an
+/// existing one), [`PmtuEventDispatcher::on_new_pmtu`] is invoked so that,
+/// e.g., routing can associate the newly-discovered PMTU with a route.
+fn update_pmtu_inner<I: Ip, D: EventDispatcher>(
+    ctx: &mut Context<D>,
     src_ip: I::Addr,
     dst_ip: I::Addr,
     new_mtu: u32,
-) -> Result<Option<u32>, Option<u32>> {
-    // New MTU must not be smaller than the minimum MTU for an IP.
-    if new_mtu < min_mtu::<I>() {
-        return Err(ctx.get_state_mut(()).get_pmtu(src_ip, dst_ip));
-    }
-
-    let key = PathMtuCacheKey::new(src_ip, dst_ip);
+    source: PmtuSource,
+) -> Result<Option<u32>, UpdatePmtuError> {
     let now = ctx.now();
-    let ret = if let Some(data) = ctx.get_state_mut(()).cache.get_mut(&key) {
-        let prev_pmtu = data.pmtu;
-        data.pmtu = new_mtu;
-        data.last_updated = now;
-        Ok(Some(prev_pmtu))
-    } else {
-        let val = PathMtuCacheData::new(new_mtu, ctx.now());
-        assert!(ctx.get_state_mut(()).cache.insert(key, val).is_none());
-        Ok(None)
-    };
+    let ret = ctx.get_state_mut(()).update(src_ip, dst_ip, new_mtu, source, now);
 
-    // Make sure we have a scheduled task to handle PMTU maintenance. If we
-    // don't, create one.
-    if !ctx.get_state(()).timer_scheduled {
-        // We are guaranteed that this call will not panic because a panic will
-        // only occur if there is already a PMTU maintenance task scheduled. We
-        // will only reach here if there is no maintenance task scheduled so we
-        // know the panic condition will not be triggered.
-        create_maintenance_timer(ctx);
+    if let Ok(prev_mtu) = ret {
+        if prev_mtu.is_none() {
+            ctx.dispatcher_mut().on_new_pmtu(src_ip.into_ip_addr(), dst_ip.into_ip_addr(), new_mtu);
+        }
+
+        // Make sure we have a scheduled task to handle PMTU maintenance. If we
+        // don't, create one.
+        if !ctx.get_state(()).timer_scheduled {
+            // We are guaranteed that this call will not panic because a panic will
+            // only occur if there is already a PMTU maintenance task scheduled. We
+            // will only reach here if there is no maintenance task scheduled so we
+            // know the panic condition will not be triggered.
+            create_maintenance_timer(ctx);
+        }
     }
 
     ret
@@ -319,8 +858,10 @@ fn update_pmtu_inner<I: Ip, C: PmtuContext<I>>(
 /// Handle a scheduled PMTU timer firing.
 ///
 /// This performs scheduled maintenance on PMTU data such as resetting PMTU
-/// values of stale cached values to restart the PMTU discovery process.
-pub(crate) fn handle_pmtu_timer<I: Ip, C: PmtuContext<I>>(ctx: &mut C) {
+/// values of stale cached values to restart the PMTU discovery process, and
+/// probing quiet entries for a possibly-increased PMTU if probing is
+/// enabled (see [`IpLayerPathMtuCache::set_probe_interval`]).
+pub(crate) fn handle_pmtu_timer<I: Ip, D: EventDispatcher>(ctx: &mut Context<D>) {
     let curr_time = ctx.now();
     let mut cache = ctx.get_state_mut(());
 
@@ -329,29 +870,11 @@ pub(crate) fn handle_pmtu_timer<I: Ip, C: PmtuContext<I>>(ctx: &mut C) {
 
     // Now that this timer has fired, no others should currently be scheduled.
     cache.timer_scheduled = false;
+    cache.next_maintenance = None;
 
-    // Remove all stale PMTU data to force restart the PMTU discovery process.
-    // This will be ok because the next time we try to send a packet to some
-    // node, we will update the PMTU with the first known potential PMTU (the
-    // first link's (connected to the node attempting PMTU discovery)) PMTU.
-    cache.cache.retain(|k, v| {
-        // We know the call to `duration_since` will not panic because all the
-        // entries in the cache should have been updated before this timer/PMTU
-        // maintenance task was run. Therefore, `curr_time` will be greater than
-        // `v.last_updated` for all `v`.
-        //
-        // TODO(ghanan): Add per-path options as per RFC 1981 section 5.3.
-        //               Specifically, some links/paths may not need to have
-        //               PMTU rediscovered as the PMTU will never change.
-        //
-        // TODO(ghanan): Consider not simply deleting all stale PMTU data as
-        //               this may cause packets to be dropped every time the
-        //               data seems to get stale when really it is still valid.
-        //               Considering the use case, PMTU value changes may be
-        //               infrequent so it may be enough to just use a long stale
-        //               timer.
-        (curr_time.duration_since(v.last_updated) < PMTU_STALE_TIMEOUT)
-    });
+    // Perform the actual maintenance, purging stale cache entries and
+    // probing quiet ones.
+    let probed = cache.run_maintenance(curr_time);
 
     // Only attempt to create the next maintenance task if we still have PMTU
     // entries in this cache. If we don't, it would be a waste to schedule the
@@ -365,6 +888,10 @@ pub(crate) fn handle_pmtu_timer<I: Ip, C: PmtuContext<I>>(ctx: &mut C) {
         // task's `TimerId` so the panic condition will not be triggered.
         create_maintenance_timer(ctx);
     }
+
+    for (src_ip, dst_ip, pmtu) in probed {
+        ctx.dispatcher_mut().on_pmtu_probe(src_ip.into_ip_addr(), dst_ip.into_ip_addr(), pmtu);
+    }
 }
 
 /// Create a PMTU maintenance task to occur after a duration of
@@ -375,12 +902,14 @@ pub(crate) fn handle_pmtu_timer<I: Ip, C: PmtuContext<I>>(ctx: &mut C) {
 /// Panics if there is already a maintenance task scheduled that has not yet
 /// run.
 fn create_maintenance_timer<I: Ip, C: PmtuContext<I>>(ctx: &mut C) {
+    let now = ctx.now();
     let mut cache = ctx.get_state_mut(());
     // Should not create a new job if we already have a maintenance job to be
     // run.
     assert!(!cache.timer_scheduled);
 
     cache.timer_scheduled = true;
+    cache.next_maintenance = Some(now.checked_add(MAINTENANCE_PERIOD).unwrap());
     assert!(ctx.schedule_timer(MAINTENANCE_PERIOD, PmtuTimerId(PhantomData)).is_none());
 }
 
@@ -392,7 +921,7 @@ mod tests {
     use specialize_ip_macro::specialize_ip_address;
 
     use crate::testutil::{
-        get_dummy_config, run_for, DummyEventDispatcher, DummyEventDispatcherBuilder,
+        get_dummy_config, run_for, DummyEventDispatcher, DummyEventDispatcherBuilder, DummyInstant,
     };
 
     /// Get the last updated [`Instant`] when the PMTU between `src_ip`
@@ -418,18 +947,32 @@ mod tests {
 
     #[test]
     fn test_next_lower_pmtu_plateau() {
-        assert_eq!(next_lower_pmtu_plateau(65536).unwrap(), 65535);
-        assert_eq!(next_lower_pmtu_plateau(65535).unwrap(), 32000);
-        assert_eq!(next_lower_pmtu_plateau(65534).unwrap(), 32000);
-        assert_eq!(next_lower_pmtu_plateau(32001).unwrap(), 32000);
-        assert_eq!(next_lower_pmtu_plateau(32000).unwrap(), 17914);
-        assert_eq!(next_lower_pmtu_plateau(31999).unwrap(), 17914);
-        assert_eq!(next_lower_pmtu_plateau(1281).unwrap(), 1280);
-        assert_eq!(next_lower_pmtu_plateau(1280).unwrap(), 1006);
-        assert_eq!(next_lower_pmtu_plateau(69).unwrap(), 68);
-        assert_eq!(next_lower_pmtu_plateau(68), None);
-        assert_eq!(next_lower_pmtu_plateau(67), None);
-        assert_eq!(next_lower_pmtu_plateau(0), None);
+        assert_eq!(next_lower_pmtu_plateau(65536, &PMTU_PLATEAUS).unwrap(), 65535);
+        assert_eq!(next_lower_pmtu_plateau(65535, &PMTU_PLATEAUS).unwrap(), 32000);
+        assert_eq!(next_lower_pmtu_plateau(65534, &PMTU_PLATEAUS).unwrap(), 32000);
+        assert_eq!(next_lower_pmtu_plateau(32001, &PMTU_PLATEAUS).unwrap(), 32000);
+        assert_eq!(next_lower_pmtu_plateau(32000, &PMTU_PLATEAUS).unwrap(), 17914);
+        assert_eq!(next_lower_pmtu_plateau(31999, &PMTU_PLATEAUS).unwrap(), 17914);
+        assert_eq!(next_lower_pmtu_plateau(1281, &PMTU_PLATEAUS).unwrap(), 1280);
+        assert_eq!(next_lower_pmtu_plateau(1280, &PMTU_PLATEAUS).unwrap(), 1006);
+        assert_eq!(next_lower_pmtu_plateau(69, &PMTU_PLATEAUS).unwrap(), 68);
+        assert_eq!(next_lower_pmtu_plateau(68, &PMTU_PLATEAUS), None);
+        assert_eq!(next_lower_pmtu_plateau(67, &PMTU_PLATEAUS), None);
+        assert_eq!(next_lower_pmtu_plateau(0, &PMTU_PLATEAUS), None);
+    }
+
+    #[test]
+    fn test_pmtu_plateaus() {
+        let dummy_config = get_dummy_config::<Ipv4Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        // Defaults to the built-in table.
+        assert_eq!(get_pmtu_plateaus::<Ipv4Addr, _>(&ctx), &PMTU_PLATEAUS[..]);
+
+        let custom_plateaus = vec![9000, 1500, 576];
+        set_pmtu_plateaus::<Ipv4Addr, _>(&mut ctx, custom_plateaus.clone());
+        assert_eq!(get_pmtu_plateaus::<Ipv4Addr, _>(&ctx), &custom_plateaus[..]);
     }
 
     fn test_ip_path_mtu_cache_ctx<I: Ip>() {
@@ -570,12 +1113,13 @@ mod tests {
         // Advance time to 9s.
         assert_eq!(run_for(&mut ctx, duration), 0);
 
-        // Updating with mtu value less than the minimum MTU should fail.
+        // Updating with mtu value less than the minimum MTU should fail. The
+        // floor defaults to the IP's minimum MTU, so this is rejected by the
+        // floor check before `update_pmtu_inner`'s own minimum MTU check is
+        // ever reached.
         assert_eq!(
-            update_pmtu_if_less(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, low_mtu)
-                .unwrap_err()
-                .unwrap(),
-            new_mtu3
+            update_pmtu_if_less(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, low_mtu),
+            Err(UpdatePmtuError::BelowFloor { floor: min_mtu::<I>(), prev_mtu: Some(new_mtu3) })
         );
 
         // Advance time to 10s.
@@ -603,6 +1147,45 @@ mod tests {
         test_ip_path_mtu_cache_ctx::<Ipv6>();
     }
 
+    #[test]
+    fn test_get_pmtu_minus_ext_hdrs() {
+        use crate::ip::IpProto;
+
+        let dummy_config = get_dummy_config::<Ipv6Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        // The serialized bytes of a minimal, 8-octet HopByHop Options
+        // extension header chain, as would be built and handed to the send
+        // path: Next Header, Hdr Ext Len, then 6 bytes of Pad1/PadN options.
+        #[rustfmt::skip]
+        let ext_hdrs: Vec<u8> = vec![
+            IpProto::Tcp.into(), 0,
+            0,
+            1, 0,
+            1, 1, 0,
+        ];
+
+        let pmtu = min_mtu::<Ipv6>() + 100;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, pmtu).unwrap(),
+            None
+        );
+
+        // The overhead subtracted should be exactly the extension header
+        // chain's own serialized length.
+        assert_eq!(
+            get_pmtu_minus_ext_hdrs(
+                &ctx,
+                dummy_config.local_ip,
+                dummy_config.remote_ip,
+                ext_hdrs.len(),
+            )
+            .unwrap(),
+            pmtu - ext_hdrs.len() as u32
+        );
+    }
+
     #[specialize_ip_address]
     fn get_other_ip<A: IpAddress>() -> A {
         #[ipv4addr]
@@ -751,4 +1334,545 @@ mod tests {
     fn test_ipv6_pmtu_task() {
         test_ip_pmtu_task::<Ipv6>();
     }
+
+    fn test_ip_pmtu_probe<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        // Probing is disabled by default; opt in for this path.
+        set_pmtu_probe_interval::<I::Addr, _>(&mut ctx, Some(DEFAULT_PMTU_PROBE_INTERVAL));
+
+        let new_mtu = min_mtu::<I>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu).unwrap(),
+            None
+        );
+        assert_eq!(ctx.dispatcher_mut().take_pmtu_probe_events(), []);
+
+        // The maintenance timer fires every `MAINTENANCE_PERIOD`, which is
+        // longer than `DEFAULT_PMTU_PROBE_INTERVAL`, so by the time it fires
+        // this entry has been quiet long enough to be probed.
+        assert_eq!(run_for(&mut ctx, MAINTENANCE_PERIOD), 1);
+
+        let expected_pmtu = next_higher_pmtu_plateau(new_mtu, &PMTU_PLATEAUS).unwrap();
+        assert_eq!(
+            ctx.dispatcher_mut().take_pmtu_probe_events(),
+            [(
+                dummy_config.local_ip.into_ip_addr(),
+                dummy_config.remote_ip.into_ip_addr(),
+                expected_pmtu
+            )]
+        );
+        assert_eq!(
+            get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).unwrap(),
+            expected_pmtu
+        );
+        assert_eq!(
+            get_pmtu_source(&ctx, dummy_config.local_ip, dummy_config.remote_ip),
+            Some(PmtuSource::Probed)
+        );
+    }
+
+    #[test]
+    fn test_ipv4_pmtu_probe() {
+        test_ip_pmtu_probe::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_pmtu_probe() {
+        test_ip_pmtu_probe::<Ipv6>();
+    }
+
+    #[specialize_ip_address]
+    fn get_next_maintenance<A: IpAddress, D: EventDispatcher>(ctx: &Context<D>) -> Option<D::Instant> {
+        #[ipv4addr]
+        let ret = ctx.state.ip.v4.path_mtu.next_maintenance();
+
+        #[ipv6addr]
+        let ret = ctx.state.ip.v6.path_mtu.next_maintenance();
+
+        ret
+    }
+
+    fn test_ip_next_maintenance<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        // No maintenance should be scheduled before any PMTU is known.
+        assert_eq!(get_next_maintenance::<I::Addr, _>(&ctx), None);
+
+        let now = ctx.dispatcher().now();
+        let new_mtu = min_mtu::<I>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu).unwrap(),
+            None
+        );
+
+        assert_eq!(
+            get_next_maintenance::<I::Addr, _>(&ctx).unwrap(),
+            now.checked_add(MAINTENANCE_PERIOD).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ipv4_next_maintenance() {
+        test_ip_next_maintenance::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_next_maintenance() {
+        test_ip_next_maintenance::<Ipv6>();
+    }
+
+    #[specialize_ip_address]
+    fn clear<A: IpAddress, D: EventDispatcher>(ctx: &mut Context<D>) {
+        #[ipv4addr]
+        clear_pmtu::<Ipv4, _>(ctx);
+
+        #[ipv6addr]
+        clear_pmtu::<Ipv6, _>(ctx);
+    }
+
+    fn test_ip_clear<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        let new_mtu = min_mtu::<I>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu).unwrap(),
+            None
+        );
+        assert_eq!(
+            get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).unwrap(),
+            new_mtu
+        );
+        assert!(get_next_maintenance::<I::Addr, _>(&ctx).is_some());
+
+        clear::<I::Addr, _>(&mut ctx);
+
+        assert!(get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).is_none());
+        assert_eq!(get_next_maintenance::<I::Addr, _>(&ctx), None);
+    }
+
+    #[test]
+    fn test_ipv4_clear() {
+        test_ip_clear::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_clear() {
+        test_ip_clear::<Ipv6>();
+    }
+
+    fn test_ip_run_maintenance<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        let new_mtu = min_mtu::<I>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu).unwrap(),
+            None
+        );
+        assert_eq!(
+            get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).unwrap(),
+            new_mtu
+        );
+
+        // Directly running maintenance past the stale timeout should purge the
+        // entry without requiring the timer to fire.
+        let now = ctx.dispatcher().now();
+        let stale_time = now.checked_add(PMTU_STALE_TIMEOUT).unwrap();
+        #[specialize_ip_address]
+        fn run_maintenance<A: IpAddress, D: EventDispatcher>(
+            ctx: &mut Context<D>,
+            now: D::Instant,
+        ) {
+            #[ipv4addr]
+            ctx.state.ip.v4.path_mtu.run_maintenance(now);
+
+            #[ipv6addr]
+            ctx.state.ip.v6.path_mtu.run_maintenance(now);
+        }
+        run_maintenance::<I::Addr, _>(&mut ctx, stale_time);
+
+        assert!(get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).is_none());
+    }
+
+    #[test]
+    fn test_ipv4_run_maintenance() {
+        test_ip_run_maintenance::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_run_maintenance() {
+        test_ip_run_maintenance::<Ipv6>();
+    }
+
+    #[specialize_ip_address]
+    fn test_ip_get_pmtu_many<A: IpAddress>() {
+        let dummy_config = get_dummy_config::<A>();
+        let mut cache = IpLayerPathMtuCache::<A::Version, DummyInstant>::new();
+        let now = DummyInstant::default();
+
+        #[ipv4addr]
+        let other_cached_ip = Ipv4Addr::new([192, 168, 0, 3]);
+        #[ipv4addr]
+        let uncached_ip = Ipv4Addr::new([192, 168, 0, 4]);
+
+        #[ipv6addr]
+        let other_cached_ip = Ipv6Addr::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 192, 168, 0, 3]);
+        #[ipv6addr]
+        let uncached_ip = Ipv6Addr::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 192, 168, 0, 4]);
+
+        let src_ip = dummy_config.local_ip;
+        let cached_ip = dummy_config.remote_ip;
+        let cached_ip_mtu = min_mtu::<A::Version>() + 50;
+        let other_cached_ip_mtu = min_mtu::<A::Version>() + 100;
+
+        assert_eq!(
+            cache.update(src_ip, cached_ip, cached_ip_mtu, PmtuSource::Exact, now).unwrap(),
+            None
+        );
+        assert_eq!(
+            cache
+                .update(src_ip, other_cached_ip, other_cached_ip_mtu, PmtuSource::Exact, now)
+                .unwrap(),
+            None
+        );
+
+        assert_eq!(
+            cache.get_pmtu_many(src_ip, &[cached_ip, uncached_ip, other_cached_ip]),
+            vec![Some(cached_ip_mtu), None, Some(other_cached_ip_mtu)]
+        );
+    }
+
+    #[test]
+    fn test_ipv4_get_pmtu_many() {
+        test_ip_get_pmtu_many::<Ipv4Addr>();
+    }
+
+    #[test]
+    fn test_ipv6_get_pmtu_many() {
+        test_ip_get_pmtu_many::<Ipv6Addr>();
+    }
+
+    fn test_ip_cache_update_and_stale_purge<I: Ip>() {
+        // Exercise `IpLayerPathMtuCache::update` and `run_maintenance` directly, with
+        // hand-injected `DummyInstant`s, without going through a `Context` or the
+        // `PmtuContext` trait machinery at all.
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut cache = IpLayerPathMtuCache::<I, DummyInstant>::new();
+        let now = DummyInstant::default();
+        let new_mtu = min_mtu::<I>() + 50;
+
+        assert_eq!(
+            cache
+                .update(
+                    dummy_config.local_ip,
+                    dummy_config.remote_ip,
+                    new_mtu,
+                    PmtuSource::Exact,
+                    now,
+                )
+                .unwrap(),
+            None
+        );
+        assert_eq!(cache.get_pmtu(dummy_config.local_ip, dummy_config.remote_ip), Some(new_mtu));
+
+        // A value below the minimum MTU is rejected and the cache is left alone.
+        assert_eq!(
+            cache
+                .update(
+                    dummy_config.local_ip,
+                    dummy_config.remote_ip,
+                    min_mtu::<I>() - 1,
+                    PmtuSource::Exact,
+                    now,
+                )
+                .unwrap_err(),
+            UpdatePmtuError::BelowMinMtu { min_mtu: min_mtu::<I>(), prev_mtu: Some(new_mtu) }
+        );
+        assert_eq!(cache.get_pmtu(dummy_config.local_ip, dummy_config.remote_ip), Some(new_mtu));
+
+        // Running maintenance past the stale timeout purges the entry, all without
+        // ever constructing a `Context`.
+        let stale_time = now.checked_add(PMTU_STALE_TIMEOUT).unwrap();
+        assert_eq!(cache.run_maintenance(stale_time), Vec::new());
+        assert_eq!(cache.get_pmtu(dummy_config.local_ip, dummy_config.remote_ip), None);
+    }
+
+    #[test]
+    fn test_ipv4_cache_update_and_stale_purge() {
+        test_ip_cache_update_and_stale_purge::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_cache_update_and_stale_purge() {
+        test_ip_cache_update_and_stale_purge::<Ipv6>();
+    }
+
+    fn test_ip_pmtu_floor<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        set_pmtu_floor::<I::Addr, _>(&mut ctx, 1280);
+
+        // Attempting to set a PMTU below the floor must be rejected, even
+        // though it is still above the IP minimum MTU.
+        assert_eq!(
+            update_pmtu_if_less(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, 576),
+            Err(UpdatePmtuError::BelowFloor { floor: 1280, prev_mtu: None })
+        );
+        assert_eq!(get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip), None);
+
+        // A PMTU at the floor should be accepted.
+        assert_eq!(
+            update_pmtu_if_less(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, 1280)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).unwrap(),
+            1280
+        );
+    }
+
+    #[test]
+    fn test_ipv4_pmtu_floor() {
+        test_ip_pmtu_floor::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_pmtu_floor() {
+        test_ip_pmtu_floor::<Ipv6>();
+    }
+
+    fn test_ip_update_pmtu_below_min_mtu<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        // `update_pmtu` (unlike `update_pmtu_if_less`) has no floor check to
+        // reject the value first, so this exercises `update_pmtu_inner`'s own
+        // minimum MTU check and its reported minimum.
+        let min_mtu = min_mtu::<I>();
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, min_mtu - 1),
+            Err(UpdatePmtuError::BelowMinMtu { min_mtu, prev_mtu: None })
+        );
+        assert_eq!(get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip), None);
+    }
+
+    #[test]
+    fn test_ipv4_update_pmtu_below_min_mtu() {
+        test_ip_update_pmtu_below_min_mtu::<Ipv4>();
+        assert_eq!(min_mtu::<Ipv4>(), 68);
+    }
+
+    #[test]
+    fn test_ipv6_update_pmtu_below_min_mtu() {
+        test_ip_update_pmtu_below_min_mtu::<Ipv6>();
+        assert_eq!(min_mtu::<Ipv6>(), 1280);
+    }
+
+    fn test_ip_pmtu_source<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        let new_mtu = min_mtu::<I>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu).unwrap(),
+            None
+        );
+        assert_eq!(
+            get_pmtu_source(&ctx, dummy_config.local_ip, dummy_config.remote_ip),
+            Some(PmtuSource::Exact)
+        );
+
+        let new_mtu2 = new_mtu - 10;
+        assert_eq!(
+            update_pmtu_next_lower(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu2)
+                .unwrap()
+                .0,
+            Some(new_mtu)
+        );
+        assert_eq!(
+            get_pmtu_source(&ctx, dummy_config.local_ip, dummy_config.remote_ip),
+            Some(PmtuSource::Estimated)
+        );
+    }
+
+    #[test]
+    fn test_ipv4_pmtu_source() {
+        test_ip_pmtu_source::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_pmtu_source() {
+        test_ip_pmtu_source::<Ipv6>();
+    }
+
+    #[test]
+    fn test_update_pmtu_next_lower_stops_after_plateaus_exhausted() {
+        // IPv4's minimum MTU (and so its default floor) is low enough that
+        // every plateau in `PMTU_PLATEAUS` is a valid PMTU, so this test can
+        // walk all the way down through them and confirm that
+        // `update_pmtu_next_lower` reports `EstimatesExhausted` once it
+        // reaches the bottom, rather than continuing to churn on every
+        // subsequent call. IPv6's higher minimum MTU means it naturally
+        // stops sooner, via `NoLowerPmtu`, before the estimate count would
+        // matter.
+        let dummy_config = get_dummy_config::<Ipv4Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        let mut from = u32::MAX;
+        for &plateau in PMTU_PLATEAUS.iter() {
+            let (_, next_pmtu) = update_pmtu_next_lower(
+                &mut ctx,
+                dummy_config.local_ip,
+                dummy_config.remote_ip,
+                from,
+            )
+            .unwrap();
+            assert_eq!(next_pmtu, plateau);
+            from = next_pmtu;
+        }
+
+        // Every plateau has now been visited once; further attempts should
+        // report that estimates have been exhausted rather than cycling.
+        assert_eq!(
+            update_pmtu_next_lower(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, from),
+            Err(UpdatePmtuNextLowerError::EstimatesExhausted(Some(
+                *PMTU_PLATEAUS.last().unwrap()
+            )))
+        );
+
+        // A fresh exact PMTU update should reset the count and allow
+        // downward estimates again.
+        let new_mtu = min_mtu::<Ipv4>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu)
+                .unwrap()
+                .unwrap(),
+            *PMTU_PLATEAUS.last().unwrap()
+        );
+        assert_eq!(
+            update_pmtu_next_lower(
+                &mut ctx,
+                dummy_config.local_ip,
+                dummy_config.remote_ip,
+                new_mtu
+            )
+            .unwrap()
+            .1,
+            *PMTU_PLATEAUS.last().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_update_pmtu_new_entry_event() {
+        let dummy_config = get_dummy_config::<Ipv4Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        let new_mtu1 = min_mtu::<Ipv4>() + 50;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu1)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            ctx.dispatcher_mut().take_new_pmtu_events(),
+            [(
+                dummy_config.local_ip.into_ip_addr(),
+                dummy_config.remote_ip.into_ip_addr(),
+                new_mtu1
+            )]
+        );
+
+        // Updating the same (src, dst) pair again should not fire another
+        // creation event, even though the PMTU itself changes.
+        let new_mtu2 = new_mtu1 - 1;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu2)
+                .unwrap(),
+            Some(new_mtu1)
+        );
+        assert_eq!(ctx.dispatcher_mut().take_new_pmtu_events(), []);
+
+        // A different (src, dst) pair gets its own creation event.
+        let new_mtu3 = min_mtu::<Ipv4>() + 100;
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.remote_ip, dummy_config.local_ip, new_mtu3)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            ctx.dispatcher_mut().take_new_pmtu_events(),
+            [(
+                dummy_config.remote_ip.into_ip_addr(),
+                dummy_config.local_ip.into_ip_addr(),
+                new_mtu3
+            )]
+        );
+    }
+
+    /// Get a prefix (and prefix length) that contains `dummy_config`'s
+    /// `remote_ip`, and an address that falls outside of it.
+    #[specialize_ip_address]
+    fn get_prefix_and_outside_ip<A: IpAddress>() -> (A, u8, A) {
+        #[ipv4addr]
+        let ret = (Ipv4Addr::new([192, 168, 0, 0]), 24, Ipv4Addr::new([10, 0, 0, 5]));
+
+        #[ipv6addr]
+        let ret = (
+            Ipv6Addr::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 192, 168, 0, 0]),
+            120,
+            Ipv6Addr::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 192, 168, 0, 5]),
+        );
+
+        ret
+    }
+
+    fn test_ip_invalidate_prefix<I: Ip>() {
+        let dummy_config = get_dummy_config::<I::Addr>();
+        let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
+            .build::<DummyEventDispatcher>();
+
+        let (prefix, prefix_len, outside_ip) = get_prefix_and_outside_ip::<I::Addr>();
+        let new_mtu = min_mtu::<I>() + 50;
+
+        // Inside the prefix.
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip, new_mtu).unwrap(),
+            None
+        );
+        // Outside the prefix.
+        assert_eq!(
+            update_pmtu(&mut ctx, dummy_config.local_ip, outside_ip, new_mtu).unwrap(),
+            None
+        );
+
+        invalidate_pmtu_prefix(&mut ctx, prefix, prefix_len);
+
+        assert!(get_pmtu(&mut ctx, dummy_config.local_ip, dummy_config.remote_ip).is_none());
+        assert_eq!(get_pmtu(&mut ctx, dummy_config.local_ip, outside_ip).unwrap(), new_mtu);
+    }
+
+    #[test]
+    fn test_ipv4_invalidate_prefix() {
+        test_ip_invalidate_prefix::<Ipv4>();
+    }
+
+    #[test]
+    fn test_ipv6_invalidate_prefix() {
+        test_ip_invalidate_prefix::<Ipv6>();
+    }
 }
@@ -31,18 +31,21 @@
 //! non-jumbogram packet, the packet should not be fragmented.
 
 use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
 use std::collections::{BTreeSet, BinaryHeap, HashMap};
 use std::convert::TryFrom;
+use std::hash::BuildHasher;
 use std::time::Duration;
 
 use byteorder::{ByteOrder, NetworkEndian};
 use internet_checksum::Checksum;
+use log::trace;
 use net_types::ip::{Ip, IpAddress};
 use packet::{BufferViewMut, ParsablePacket};
 use specialize_ip_macro::specialize_ip;
 use zerocopy::{ByteSlice, ByteSliceMut};
 
-use crate::context::{StateContext, TimerContext};
+use crate::context::{InstantContext, StateContext, TimerContext};
 use crate::ip::{IpExtByteSlice, IpPacket};
 use crate::wire::ipv4::{
     IPV4_CHECKSUM_BYTE_RANGE, IPV4_FRAGMENT_DATA_BYTE_RANGE, IPV4_TOTAL_LENGTH_BYTE_RANGE,
@@ -74,13 +77,15 @@ const MAX_FRAGMENT_BLOCKS: u16 = 8191;
 
 /// The execution context for the fragment cache.
 pub(crate) trait FragmentContext<I: Ip>:
-    TimerContext<FragmentCacheKey<I::Addr>> + StateContext<(), IpLayerFragmentCache<I>>
+    TimerContext<FragmentCacheKey<I::Addr>>
+    + StateContext<(), IpLayerFragmentCache<I, <Self as InstantContext>::Instant>>
 {
 }
 
 impl<
         I: Ip,
-        C: TimerContext<FragmentCacheKey<I::Addr>> + StateContext<(), IpLayerFragmentCache<I>>,
+        C: TimerContext<FragmentCacheKey<I::Addr>>
+            + StateContext<(), IpLayerFragmentCache<I, <C as InstantContext>::Instant>>,
     > FragmentContext<I> for C
 {
 }
@@ -107,6 +112,13 @@ pub(crate) trait FragmentablePacket {
     ///
     /// Panics if the packet has no fragment data.
     fn fragment_data(&self) -> (u32, u16, bool);
+
+    /// Return the length of the packet's body, in bytes.
+    ///
+    /// This is used to compute the number of fragment blocks `self`
+    /// contributes to a reassembled packet, so that callers do not need to
+    /// reach for a concrete packet type's body accessor directly.
+    fn fragment_body_len(&self) -> usize;
 }
 
 /// Possible return values for [`IpLayerFragmentCache::process_fragment`].
@@ -134,7 +146,14 @@ pub(crate) enum FragmentProcessingState<B: ByteSlice, I: Ip> {
     /// Successfully proccessed the provided fragment. We are still waiting on
     /// more fragments for a packet to arrive before being ready to reassemble the
     /// packet.
-    NeedMoreFragments,
+    ///
+    /// `packet_len` is the reassembled packet's exact final size in bytes, if
+    /// it is already known. This is possible once the last fragment (the one
+    /// with the more-fragments flag unset) and the first fragment (which
+    /// carries the header) have both been received, even if fragments in
+    /// between are still missing, so that the caller may allocate the final
+    /// buffer ahead of time instead of waiting for `Ready`.
+    NeedMoreFragments { packet_len: Option<usize> },
 
     /// Successfully processed the provided fragment. We now have all the fragments
     /// we need to reassemble the packet. The caller must create a buffer with capacity
@@ -170,11 +189,37 @@ impl<A: IpAddress> FragmentCacheKey<A> {
     fn new(src_ip: A, dst_ip: A, fragment_id: u32) -> Self {
         FragmentCacheKey(src_ip, dst_ip, fragment_id)
     }
+
+    /// Constructs the `FragmentCacheKey` that `packet` is associated with.
+    ///
+    /// Equivalent to extracting `packet`'s source address, destination address, and fragment
+    /// identification value by hand and passing them to [`FragmentCacheKey::new`], as is done in
+    /// [`process_fragment`] and [`process_fragment_into_buffer`].
+    pub(crate) fn from_packet<B: ByteSlice, I: Ip<Addr = A>>(
+        packet: &<I as IpExtByteSlice<B>>::Packet,
+    ) -> Self
+    where
+        <I as IpExtByteSlice<B>>::Packet: IpPacket<B, I> + FragmentablePacket,
+    {
+        let (fragment_id, _offset, _m_flag) = packet.fragment_data();
+        FragmentCacheKey::new(packet.src_ip(), packet.dst_ip(), fragment_id)
+    }
+
+    pub(crate) fn src_ip(&self) -> A {
+        self.0
+    }
+
+    pub(crate) fn dst_ip(&self) -> A {
+        self.1
+    }
+
+    pub(crate) fn fragment_id(&self) -> u32 {
+        self.2
+    }
 }
 
 /// Data required for fragmented packet reassembly.
-#[derive(Debug)]
-struct FragmentCacheData {
+struct FragmentCacheData<Instant> {
     /// List of non-overlapping inclusive ranges of fragment blocks required before
     /// being ready to reassemble a packet.
     ///
@@ -207,21 +252,52 @@ struct FragmentCacheData {
     /// for the final, reassembled packet.
     header: Option<Vec<u8>>,
 
+    /// Whether the fragment with offset 0 has been received yet.
+    ///
+    /// `header.is_some()` happens to be equivalent, since the offset-0
+    /// fragment is what populates `header`, but this is tracked explicitly
+    /// so that callers that only care about this fact (e.g. an
+    /// ICMP time-exceeded-on-reassembly-timeout check) don't need to know
+    /// that detail to ask the question.
+    first_fragment_received: bool,
+
     /// Total number of bytes in the reassembled packet.
     ///
     /// This is used so that we don't have to iterated through `body_fragments` and
     /// sum the partial body sizes to calculate the reassembled packet's size.
     total_size: usize,
+
+    /// The time at which the first fragment for this packet was received.
+    ///
+    /// Used by [`reassemble_packet`] to report how long the packet spent in
+    /// reassembly once it completes.
+    first_fragment_time: Instant,
+
+    /// The total number of body bytes the reassembled packet will have, once
+    /// known.
+    ///
+    /// Set as soon as the fragment with the more-fragments flag unset (i.e.
+    /// the last fragment of the packet) is received, regardless of whether
+    /// any fragment blocks before it are still missing. This lets
+    /// [`process_fragment`] report the reassembled packet's exact final size
+    /// early, via `FragmentProcessingState::NeedMoreFragments`, instead of
+    /// only once reassembly completes.
+    expected_body_len: Option<usize>,
 }
 
-impl FragmentCacheData {
+impl<Instant> FragmentCacheData<Instant> {
     /// Create a new `FragmentCacheData` with all fragments marked as missing.
-    fn new() -> Self {
+    ///
+    /// `first_fragment_time` will be set to `now`.
+    fn new(now: Instant) -> Self {
         let mut ret = FragmentCacheData {
             missing_blocks: BTreeSet::new(),
             body_fragments: BinaryHeap::new(),
             header: None,
+            first_fragment_received: false,
             total_size: 0,
+            first_fragment_time: now,
+            expected_body_len: None,
         };
         ret.missing_blocks.insert((0, std::u16::MAX));
         ret
@@ -234,23 +310,79 @@ impl FragmentCacheData {
 /// a `FragmentCacheData`.
 ///
 /// See [`FragmentCacheKey`] and [`FragmentCacheData`].
-type FragmentCache<A> = HashMap<FragmentCacheKey<A>, FragmentCacheData>;
+type FragmentCache<A, Instant, S> = HashMap<FragmentCacheKey<A>, FragmentCacheData<Instant>, S>;
+
+/// How [`process_fragment`] should handle a newly received fragment whose
+/// blocks overlap fragment blocks already received for the same packet.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum OverlapMode {
+    /// Reject the whole reassembly on any overlap, dropping all reassembly
+    /// data for the packet. This is the default, and the only mode used for
+    /// IPv6, per RFC 8200 section 4.5 (see also RFC 5722).
+    Strict,
+
+    /// IPv4-only "favor newer data" mode, for interop with legacy hosts that
+    /// produce overlapping fragments. RFC 791 does not mandate strict overlap
+    /// rejection for IPv4, so instead of discarding all reassembly data, the
+    /// incoming fragment is trimmed down to just the gap of fragment blocks
+    /// that is still missing.
+    FavorNewer,
+
+    /// Like `Strict`, except that a fragment which exactly duplicates a
+    /// fragment already received for the same packet - same range of
+    /// fragment blocks, byte-for-byte identical body - is treated as a
+    /// no-op rather than as a conflicting overlap: reassembly state is left
+    /// untouched and the duplicate fragment is silently discarded. Any
+    /// overlap that is not an exact duplicate still tears down all
+    /// reassembly data for the packet, as under `Strict`. Useful for
+    /// networks where retransmitted or duplicated fragments are expected,
+    /// without relaxing handling of genuinely conflicting overlaps.
+    AllowDuplicates,
+}
 
 /// Type to process fragments and handle reassembly.
 ///
 /// To keep track of partial fragments, we use a hash table. The key will be
 /// composed of the (remote) source address, (local) destination address and
 /// 32-bit identifier of a packet.
-#[derive(Debug)]
-pub(crate) struct IpLayerFragmentCache<I: Ip> {
-    cache: FragmentCache<I::Addr>,
+///
+/// Since the key is entirely attacker-influenced (an off-path attacker can
+/// choose any (src, dst, id) triple it likes), the hash table is keyed by a
+/// pluggable hasher `S` rather than always using a fixed one, so that a
+/// hasher seeded with a secret (such as the default [`RandomState`], which
+/// is randomly seeded per process) can be used to prevent an attacker from
+/// forcing hash collisions. [`IpLayerFragmentCache::new`] defaults to
+/// `RandomState` for this reason; use [`IpLayerFragmentCache::with_hasher`]
+/// to supply a different one.
+pub(crate) struct IpLayerFragmentCache<I: Ip, Instant, S = RandomState> {
+    cache: FragmentCache<I::Addr, Instant, S>,
+    overlap_mode: OverlapMode,
 }
 
-impl<I: Ip> IpLayerFragmentCache<I> {
+impl<I: Ip, Instant, S: BuildHasher + Default> IpLayerFragmentCache<I, Instant, S> {
     pub(crate) fn new() -> Self {
-        IpLayerFragmentCache { cache: FragmentCache::new() }
+        Self::with_overlap_mode(OverlapMode::Strict)
     }
 
+    pub(crate) fn with_overlap_mode(overlap_mode: OverlapMode) -> Self {
+        IpLayerFragmentCache { cache: FragmentCache::default(), overlap_mode }
+    }
+
+    /// Constructs a new `IpLayerFragmentCache` that hashes its keys with
+    /// `hasher` instead of the default [`RandomState`].
+    ///
+    /// This is useful for tests that need deterministic hashing, or for
+    /// callers that want to seed the hasher from their own source of
+    /// randomness.
+    pub(crate) fn with_hasher(hasher: S) -> Self {
+        IpLayerFragmentCache {
+            cache: HashMap::with_hasher(hasher),
+            overlap_mode: OverlapMode::Strict,
+        }
+    }
+}
+
+impl<I: Ip, Instant, S: BuildHasher> IpLayerFragmentCache<I, Instant, S> {
     /// Handle a reassembly timer.
     ///
     /// Removes reassembly data associated with a given `FragmentCacheKey`,
@@ -259,6 +391,65 @@ impl<I: Ip> IpLayerFragmentCache<I> {
         // If a timer fired, the `key` must still exist in our fragment cache.
         assert!(self.cache.remove(&key).is_some());
     }
+
+    /// Counts the number of in-flight reassemblies, grouped by source
+    /// address.
+    ///
+    /// This can be used to rate-limit reassembly on a per-source basis, as an
+    /// anti-DoS measure.
+    pub(crate) fn pending_by_source(&self) -> HashMap<I::Addr, usize> {
+        let mut counts = HashMap::new();
+        for key in self.cache.keys() {
+            *counts.entry(key.src_ip()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Returns a snapshot of the fragment blocks still missing for the in-flight reassembly
+    /// identified by `key`, or `None` if `key` has no reassembly in progress.
+    ///
+    /// Intended for debugging reassemblies that appear stuck; the returned ranges are a copy and
+    /// do not reflect subsequent changes to the cache.
+    pub(crate) fn missing_blocks(&self, key: FragmentCacheKey<I::Addr>) -> Option<Vec<(u16, u16)>> {
+        self.cache.get(&key).map(|data| data.missing_blocks.iter().copied().collect())
+    }
+
+    /// Returns whether the fragment with offset 0 has been received yet for
+    /// the in-flight reassembly identified by `key`, or `None` if `key` has
+    /// no reassembly in progress.
+    pub(crate) fn has_first_fragment(&self, key: FragmentCacheKey<I::Addr>) -> Option<bool> {
+        self.cache.get(&key).map(|data| data.first_fragment_received)
+    }
+
+    /// Returns an iterator over the keys of all in-flight reassemblies.
+    ///
+    /// This complements [`pending_by_source`], and lets tooling enumerate
+    /// every partial reassembly currently held in the cache.
+    ///
+    /// [`pending_by_source`]: IpLayerFragmentCache::pending_by_source
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &FragmentCacheKey<I::Addr>> {
+        self.cache.keys()
+    }
+}
+
+impl<I: Ip, Instant: crate::Instant, S: BuildHasher> IpLayerFragmentCache<I, Instant, S> {
+    /// Reclaims memory held by entries that have timed out but have not yet
+    /// been purged by their reassembly timer, as of `now`.
+    ///
+    /// Between the time an entry times out and the time its reassembly timer
+    /// actually fires, its body buffers stay resident. This lets a caller
+    /// (e.g. one under memory pressure) reclaim that memory immediately
+    /// instead of waiting for the timer, without disturbing entries that
+    /// have not yet timed out. Unlike [`handle_reassembly_timer`], this does
+    /// not touch or cancel any scheduled timers, so a timer may still fire
+    /// (as a no-op, since its entry will already be gone) for an entry this
+    /// removes.
+    ///
+    /// [`handle_reassembly_timer`]: IpLayerFragmentCache::handle_reassembly_timer
+    pub(crate) fn compact(&mut self, now: Instant) {
+        let timeout = Duration::from_secs(REASSEMBLY_TIMEOUT_SECONDS);
+        self.cache.retain(|_k, v| now.duration_since(v.first_fragment_time) < timeout);
+    }
 }
 
 /// Attempts to process a packet fragment.
@@ -289,7 +480,7 @@ where
     // `true`), we simply let the caller know we need more fragments. This
     // should never happen, but just in case :).
     if packet.body().is_empty() {
-        return FragmentProcessingState::NeedMoreFragments;
+        return FragmentProcessingState::NeedMoreFragments { packet_len: None };
     }
 
     // Make sure body is a multiple of `FRAGMENT_BLOCK_SIZE` bytes, or `packet`
@@ -302,6 +493,10 @@ where
     // Key used to find this connection's fragment cache data.
     let key = FragmentCacheKey::new(packet.src_ip(), packet.dst_ip(), id);
 
+    // Read the configured overlap handling mode before taking a mutable
+    // borrow of the fragment cache below.
+    let overlap_mode = ctx.get_state(()).overlap_mode;
+
     // Get (or create) the fragment cache data.
     let fragment_data = get_or_create(ctx, &key);
 
@@ -314,10 +509,11 @@ where
     // fragment block for a reassembled packet is allowed to contain less than
     // `FRAGMENT_BLOCK_SIZE` bytes.
     //
-    // We know `packet.body().len() - 1` will never be less than 0 because we
-    // already made sure that `packet`'s body is not empty, and it is impossible
-    // to have a negative body size.
-    let num_fragment_blocks = 1 + ((packet.body().len() - 1) / (FRAGMENT_BLOCK_SIZE as usize));
+    // We know `packet.fragment_body_len() - 1` will never be less than 0
+    // because we already made sure that `packet`'s body is not empty, and it
+    // is impossible to have a negative body size.
+    let num_fragment_blocks =
+        1 + ((packet.fragment_body_len() - 1) / (FRAGMENT_BLOCK_SIZE as usize));
     assert!(num_fragment_blocks > 0);
 
     // The range of fragment blocks `packet` contains.
@@ -336,14 +532,69 @@ where
             return FragmentProcessingState::InvalidFragment;
         };
 
-    // Find the gap where `packet` belongs.
-    let found_gap = match find_gap(&fragment_data.missing_blocks, fragment_blocks_range) {
-        // We did not find a potential gap `packet` fits in so some of the
-        // fragment blocks in `packet` overlaps with fragment blocks we already
-        // received.
-        None => {
-            // Drop all reassembly data as per RFC 8200 section 4.5 (IPv6). See
-            // RFC 5722 for more information.
+    // If this is the last fragment (the more-fragments flag is unset), we now
+    // know the reassembled packet's exact body length, even if fragments
+    // before it are still missing.
+    if !m_flag {
+        fragment_data.expected_body_len =
+            Some((offset as usize) * (FRAGMENT_BLOCK_SIZE as usize) + packet.body().len());
+    }
+
+    // Account for the received fragment blocks. In `OverlapMode::Strict`
+    // (the only mode used for IPv6), this drops all reassembly data for
+    // `key` if the fragment overlaps with fragment blocks we already
+    // received; in `OverlapMode::FavorNewer` (IPv4 only), an overlapping
+    // fragment is instead trimmed down to the range of blocks it shares with
+    // the gap it overlaps, if that can be determined unambiguously.
+    let accepted_range = match remove_from_missing_blocks(
+        &mut fragment_data.missing_blocks,
+        fragment_blocks_range,
+        m_flag,
+        overlap_mode,
+    ) {
+        Ok(accepted_range) => {
+            trace!(
+                "process_fragment: {:?} found a gap for fragment blocks {:?}; accepted {:?}",
+                key,
+                fragment_blocks_range,
+                accepted_range
+            );
+            accepted_range
+        }
+        Err(()) => {
+            // RFC 8200 section 4.5 (IPv6, see also RFC 5722) and, by our own
+            // choice, IPv4 both call for tearing down all reassembly data on
+            // a conflicting overlap. RFC 8200 mentions an implementation
+            // *may choose* to first check whether the overlap is actually an
+            // exact duplicate of data already received, rather than
+            // unconditionally tearing down. `OverlapMode::AllowDuplicates`
+            // opts into that leniency.
+            if overlap_mode == OverlapMode::AllowDuplicates
+                && is_duplicate_fragment(
+                    &fragment_data.body_fragments,
+                    fragment_blocks_range,
+                    packet.body(),
+                )
+            {
+                trace!(
+                    "process_fragment: {:?} ignoring exact duplicate of already-received \
+                     fragment blocks {:?}",
+                    key,
+                    fragment_blocks_range
+                );
+
+                return if fragment_data.missing_blocks.is_empty() {
+                    FragmentProcessingState::Ready { key, packet_len: fragment_data.total_size }
+                } else {
+                    let packet_len = fragment_data.header.as_ref().and_then(|header| {
+                        fragment_data.expected_body_len.map(|len| header.len() + len)
+                    });
+                    FragmentProcessingState::NeedMoreFragments { packet_len }
+                };
+            }
+
+            // Drop all reassembly data as per RFC 8200 section 4.5 (IPv6).
+            // See RFC 5722 for more information.
             //
             // IPv4 (RFC 791) does not specify what to do for overlapped
             // fragments. RFC 1858 section 4.2 outlines a way to prevent an
@@ -351,117 +602,71 @@ where
             // IP filtering since "no standard requires that an overlap-safe
             // reassemble algorithm be used" on hosts. In practice,
             // non-malicious nodes should not intentionally send data for the
-            // same fragment block multiple times, so we will do the same thing
-            // as IPv6 in this case.
-            //
-            // TODO(ghanan): Check to see if the fragment block's data is
-            //               identical to already received data before dropping
-            //               the reassembly data as packets may be duplicated in
-            //               the network. Duplicate packets which are also
-            //               fragmented are probably rare, so we should first
-            //               determine if it is even worthwhile to do this check
-            //               first. Note, we can choose to simply not do this
-            //               check as RFC 8200 section 4.5 mentions an
-            //               implementation *may choose* to do this check. It
-            //               does not say we MUST, so we would not be violating
-            //               the RFC if we don't check for this case and just
-            //               drop the packet.
+            // same fragment block multiple times, so we will do the same
+            // thing as IPv6 in this case unless `OverlapMode::FavorNewer` is
+            // in use and the overlap can be resolved by trimming, or
+            // `OverlapMode::AllowDuplicates` is in use and the overlap is an
+            // exact duplicate, as handled above.
+            trace!(
+                "process_fragment: no gap fits fragment blocks {:?}; tearing down reassembly \
+                 state for {:?}",
+                fragment_blocks_range,
+                key
+            );
             assert!(ctx.get_state_mut(()).cache.remove(&key).is_some());
             assert!(ctx.cancel_timer(key).is_some());
 
             return FragmentProcessingState::InvalidFragment;
         }
-        Some(f) => f,
     };
 
-    // Remove `found_gap` since the gap as it exists will no longer be valid.
-    fragment_data.missing_blocks.remove(&found_gap);
-
-    // If the received fragment blocks start after the beginning of `found_gap`,
-    // create a new gap between the beginning of `found_gap` and the first
-    // fragment block contained in `packet`.
-    //
-    // Example:
-    //   `packet` w/ fragments [4, 7]
-    //                 |-----|-----|-----|-----|
-    //                    4     5     6     7
-    //
-    //   `found_gap` w/ fragments [X, 7] where 0 <= X < 4
-    //     |-----| ... |-----|-----|-----|-----|
-    //        X    ...    4     5     6     7
-    //
-    //   Here we can see that with a `found_gap` of [2, 7], `packet` covers [4,
-    //   7] but we are still missing [X, 3] so we create a new gap of [X, 3].
-    if found_gap.0 < fragment_blocks_range.0 {
-        fragment_data.missing_blocks.insert((found_gap.0, fragment_blocks_range.0 - 1));
-    }
-
-    // If the received fragment blocks end before the end of `found_gap` and we
-    // expect more fragments, create a new gap between the last fragment block
-    // contained in `packet` and the end of `found_gap`.
-    //
-    // Example 1:
-    //   `packet` w/ fragments [4, 7] & m_flag = true
-    //     |-----|-----|-----|-----|
-    //        4     5     6     7
-    //
-    //   `found_gap` w/ fragments [4, Y] where 7 < Y <= `MAX_FRAGMENT_BLOCKS`.
-    //     |-----|-----|-----|-----| ... |-----|
-    //        4     5     6     7    ...    Y
-    //
-    //   Here we can see that with a `found_gap` of [4, Y], `packet` covers [4,
-    //   7] but we still expect more fragment blocks after the blocks in
-    //   `packet` (as noted by `m_flag`) so we are still missing [8, Y] so we
-    //   create a new gap of [8, Y].
-    //
-    // Example 2:
-    //   `packet` w/ fragments [4, 7] & m_flag = false
-    //     |-----|-----|-----|-----|
-    //        4     5     6     7
-    //
-    //   `found_gap` w/ fragments [4, Y] where MAX = `MAX_FRAGMENT_BLOCKS`.
-    //     |-----|-----|-----|-----| ... |-----|
-    //        4     5     6     7    ...   MAX
-    //
-    //   Here we can see that with a `found_gap` of [4, MAX], `packet` covers
-    //   [4, 7] and we don't expect more fragment blocks after the blocks in
-    //   `packet` (as noted by `m_flag`) so we dont create a new gap. Note, if
-    //   we encounter a `packet` where `m_flag` is false, `found_gap`'s end
-    //   value must be MAX because we should only ever not create a new gap
-    //   where the end is MAX when we are processing a packet with the last
-    //   fragment block.
-    if (found_gap.1 > fragment_blocks_range.1) && m_flag {
-        fragment_data.missing_blocks.insert((fragment_blocks_range.1 + 1, found_gap.1));
-    } else {
-        // Make sure that if we are not adding a fragment after the packet, it
-        // is because `packet` goes up to the `found_gap`'s end boundary, or
-        // this is the last fragment. If it is the last fragment for a packet,
-        // we make sure that `found_gap`'s end value is `std::u16::MAX`.
-        assert!(found_gap.1 == fragment_blocks_range.1 || !m_flag && found_gap.1 == std::u16::MAX);
-    }
-
     // Get header buffer from `packet` if its fragment offset equals to 0.
     if offset == 0 {
         assert!(fragment_data.header.is_none());
         let header = get_header::<B, I>(&packet);
         fragment_data.total_size += header.len();
         fragment_data.header = Some(header);
+        fragment_data.first_fragment_received = true;
     }
 
-    // Add our `packet`'s body to the store of body fragments.
-    let mut body = Vec::with_capacity(packet.body().len());
-    body.extend_from_slice(packet.body());
+    // Add our `packet`'s body to the store of body fragments. When
+    // `accepted_range` is a trimmed-down sub-range of `fragment_blocks_range`
+    // (only possible under `OverlapMode::FavorNewer`), only the portion of
+    // the body that falls within it is kept, since the rest overlaps data we
+    // already have.
+    let body = if accepted_range == fragment_blocks_range {
+        let mut body = Vec::with_capacity(packet.body().len());
+        body.extend_from_slice(packet.body());
+        body
+    } else {
+        let block_size = FRAGMENT_BLOCK_SIZE as usize;
+        let start = usize::from(accepted_range.0 - fragment_blocks_range.0) * block_size;
+        let end = std::cmp::min(
+            packet.body().len(),
+            usize::from(accepted_range.1 - fragment_blocks_range.0 + 1) * block_size,
+        );
+        packet.body()[start..end].to_vec()
+    };
     fragment_data.total_size += body.len();
-    fragment_data.body_fragments.push(PacketBodyFragment::new(offset, body));
+    fragment_data.body_fragments.push(PacketBodyFragment::new(accepted_range.0, body));
 
     // If we still have missing fragments, let the caller know that we are still
     // waiting on some fragments. Otherwise, we let them know we are ready to
     // reassemble and give them a key and the final packet length so they can
     // allocate a sufficient buffer and call `reassemble_packet`.
     if fragment_data.missing_blocks.is_empty() {
+        trace!(
+            "process_fragment: {:?} is ready for reassembly ({} bytes)",
+            key,
+            fragment_data.total_size
+        );
         FragmentProcessingState::Ready { key, packet_len: fragment_data.total_size }
     } else {
-        FragmentProcessingState::NeedMoreFragments
+        let packet_len = fragment_data
+            .header
+            .as_ref()
+            .and_then(|header| fragment_data.expected_body_len.map(|len| header.len() + len));
+        FragmentProcessingState::NeedMoreFragments { packet_len }
     }
 }
 
@@ -474,6 +679,10 @@ where
 /// and provide it to `reassemble_packet` as `buffer` where the packet will be
 /// reassembled into.
 ///
+/// On success, in addition to the reassembled packet, `reassemble_packet`
+/// returns how long the packet spent in reassembly, measured from the receipt
+/// of its first fragment to this call.
+///
 /// # Panics
 ///
 /// Panics if the provided `buffer` does not have enough capacity for the
@@ -490,7 +699,7 @@ pub(crate) fn reassemble_packet<
     ctx: &mut C,
     key: &FragmentCacheKey<I::Addr>,
     buffer: BV,
-) -> Result<<I as IpExtByteSlice<B>>::Packet, FragmentReassemblyError> {
+) -> Result<(<I as IpExtByteSlice<B>>::Packet, Duration), FragmentReassemblyError> {
     // Get the fragment cache data.
     let fragment_data = match ctx.get_state_mut(()).cache.get_mut(key) {
         // Either there are no fragments for the given `key`, or we timed out
@@ -507,6 +716,10 @@ pub(crate) fn reassemble_packet<
     // If we are not missing fragments, we must have header data.
     assert!(fragment_data.header.is_some());
 
+    // Note how long the packet spent in reassembly before we cancel the timer
+    // and lose track of when its first fragment was received.
+    let reassembly_duration = ctx.now().duration_since(fragment_data.first_fragment_time);
+
     // Cancel the reassembly timer now that we know we have all the data
     // required for reassembly and are attempting to do so.
     assert!(ctx.cancel_timer(*key).is_some());
@@ -518,6 +731,7 @@ pub(crate) fn reassemble_packet<
 
     // Attempt to actually reassemble the packet.
     reassemble_packet_helper::<B, BV, I>(buffer, header, body_fragments)
+        .map(|packet| (packet, reassembly_duration))
 }
 
 /// Gets or creates a new entry in the cache for a given `key`.
@@ -526,15 +740,17 @@ pub(crate) fn reassemble_packet<
 fn get_or_create<'a, I: Ip, C: FragmentContext<I>>(
     ctx: &'a mut C,
     key: &FragmentCacheKey<I::Addr>,
-) -> &'a mut FragmentCacheData {
+) -> &'a mut FragmentCacheData<C::Instant> {
     if ctx.get_state(()).cache.contains_key(key) {
         ctx.get_state_mut(()).cache.get_mut(key).unwrap()
     } else {
         // We have no reassembly data yet so this fragment is the first one
-        // associated with the given `key`. Create a new entry in the hash table
-        // and schedule a timer to reset the entry after
+        // associated with the given `key`. Create a new entry in the hash table,
+        // recording the current time as the time of receipt of the first
+        // fragment, and schedule a timer to reset the entry after
         // `REASSEMBLY_TIMEOUT_SECONDS` seconds.
-        ctx.get_state_mut(()).cache.insert(key.clone(), FragmentCacheData::new());
+        let now = ctx.now();
+        ctx.get_state_mut(()).cache.insert(key.clone(), FragmentCacheData::new(now));
         ctx.schedule_timer(Duration::from_secs(REASSEMBLY_TIMEOUT_SECONDS), *key);
         ctx.get_state_mut(()).cache.get_mut(key).unwrap()
     }
@@ -584,6 +800,377 @@ fn find_gap(
     None
 }
 
+/// Like [`find_gap`], but returns the single gap `fragment_blocks_range`
+/// overlaps with, without requiring `fragment_blocks_range` to fit purely
+/// within it. Used to implement [`OverlapMode::FavorNewer`], where an
+/// overlapping fragment is trimmed down to the blocks it shares with a gap
+/// instead of being rejected outright.
+///
+/// Returns `None` if `fragment_blocks_range` does not overlap with exactly
+/// one gap in `missing_blocks`, since trimming down to a single gap is not
+/// well-defined otherwise.
+fn find_overlapping_gap(
+    missing_blocks: &BTreeSet<(u16, u16)>,
+    fragment_blocks_range: (u16, u16),
+) -> Option<(u16, u16)> {
+    let mut found = None;
+    for gap in missing_blocks.iter() {
+        if fragment_blocks_range.1 < gap.0 || fragment_blocks_range.0 > gap.1 {
+            continue;
+        }
+
+        if found.is_some() {
+            return None;
+        }
+
+        found = Some(*gap);
+    }
+
+    found
+}
+
+/// Returns whether `body_fragments` already holds a fragment covering exactly
+/// `fragment_blocks_range` with a byte-for-byte identical body to `body`.
+/// Used to implement [`OverlapMode::AllowDuplicates`], where such an exact
+/// duplicate is treated as a no-op instead of a conflicting overlap.
+fn is_duplicate_fragment(
+    body_fragments: &BinaryHeap<PacketBodyFragment>,
+    fragment_blocks_range: (u16, u16),
+    body: &[u8],
+) -> bool {
+    body_fragments.iter().any(|PacketBodyFragment(offset, data)| {
+        u16::try_from(-offset).map_or(false, |offset| offset == fragment_blocks_range.0)
+            && data.as_slice() == body
+    })
+}
+
+/// Removes `gap` from `missing_blocks` given that `accepted` (a sub-range of
+/// `gap`) was just received, splitting `gap` into the sub-ranges not covered
+/// by `accepted`, as appropriate given whether more fragments are expected
+/// after `accepted` (`m_flag`). See the examples in
+/// [`remove_from_missing_blocks`], which calls this with `accepted` equal to
+/// the whole received fragment in `OverlapMode::Strict`, and a trimmed-down
+/// sub-range of it in `OverlapMode::FavorNewer`.
+///
+/// Afterwards, `missing_blocks` is normalized via [`merge_adjacent_gaps`] so that any gaps left
+/// contiguous by this modification are kept merged into a single entry, rather than accumulating
+/// as separate but logically-contiguous ranges that `find_gap` would need to account for.
+fn remove_gap(
+    missing_blocks: &mut BTreeSet<(u16, u16)>,
+    gap: (u16, u16),
+    accepted: (u16, u16),
+    m_flag: bool,
+) {
+    missing_blocks.remove(&gap);
+
+    if gap.0 < accepted.0 {
+        missing_blocks.insert((gap.0, accepted.0 - 1));
+    }
+
+    if gap.1 > accepted.1 && m_flag {
+        missing_blocks.insert((accepted.1 + 1, gap.1));
+    } else {
+        // Make sure that if we are not adding a gap after `accepted`, it is
+        // because `accepted` goes up to `gap`'s end boundary, or this is the
+        // last fragment. If it is the last fragment for a packet, we make
+        // sure that `gap`'s end value is `std::u16::MAX`.
+        assert!(gap.1 == accepted.1 || !m_flag && gap.1 == std::u16::MAX);
+    }
+
+    merge_adjacent_gaps(missing_blocks);
+}
+
+/// Merges contiguous (adjacent) gaps in `missing_blocks`, keeping the set minimal.
+///
+/// Two gaps `(a, b)` and `(c, d)`, with `(a, b)` ordered before `(c, d)`, are contiguous if `c ==
+/// b + 1`; such gaps describe a single missing range of fragment blocks and are merged into `(a,
+/// d)`.
+fn merge_adjacent_gaps(missing_blocks: &mut BTreeSet<(u16, u16)>) {
+    let mut merged = BTreeSet::new();
+    let mut gaps = missing_blocks.iter().copied();
+
+    if let Some(mut current) = gaps.next() {
+        for gap in gaps {
+            if current.1.checked_add(1) == Some(gap.0) {
+                current = (current.0, gap.1);
+            } else {
+                merged.insert(current);
+                current = gap;
+            }
+        }
+        merged.insert(current);
+    }
+
+    *missing_blocks = merged;
+}
+
+/// Updates `missing_blocks` to account for a newly received fragment covering
+/// `fragment_blocks_range`, given whether more fragments are expected after
+/// it (`m_flag`) and how overlapping fragments should be handled
+/// (`overlap_mode`).
+///
+/// On success, returns the sub-range of `fragment_blocks_range` that was
+/// actually still missing and has now been accounted for; in
+/// [`OverlapMode::Strict`], this is always `fragment_blocks_range` itself.
+/// The caller is expected to only store the part of the fragment's body that
+/// falls within the returned range.
+///
+/// Returns `Err(())` if `fragment_blocks_range` overlaps with fragment blocks
+/// that were already received in a way `overlap_mode` cannot resolve, in
+/// which case `missing_blocks` is left unmodified and the caller is expected
+/// to drop all reassembly data for the packet (see the overlapping fragment
+/// handling in [`process_fragment`]).
+///
+/// # Examples
+///
+/// The following examples apply when `fragment_blocks_range` fits purely
+/// within the gap it is being accounted against (always true in
+/// `OverlapMode::Strict`; the trimmed-down `OverlapMode::FavorNewer` case
+/// works the same way, but against the trimmed sub-range).
+///
+/// If the received fragment blocks start after the beginning of the gap,
+/// create a new gap between the beginning of the gap and the first fragment
+/// block contained in the packet.
+///
+///   fragment w/ fragments [4, 7]
+///                 |-----|-----|-----|-----|
+///                    4     5     6     7
+///
+///   gap w/ fragments [X, 7] where 0 <= X < 4
+///     |-----| ... |-----|-----|-----|-----|
+///        X    ...    4     5     6     7
+///
+///   Here we can see that with a gap of [2, 7], the fragment covers [4, 7]
+///   but we are still missing [X, 3] so we create a new gap of [X, 3].
+///
+/// If the received fragment blocks end before the end of the gap and we
+/// expect more fragments, create a new gap between the last fragment block
+/// contained in the packet and the end of the gap.
+///
+///   fragment w/ fragments [4, 7] & m_flag = true
+///     |-----|-----|-----|-----|
+///        4     5     6     7
+///
+///   gap w/ fragments [4, Y] where 7 < Y <= `MAX_FRAGMENT_BLOCKS`.
+///     |-----|-----|-----|-----| ... |-----|
+///        4     5     6     7    ...    Y
+///
+///   Here we can see that with a gap of [4, Y], the fragment covers [4, 7]
+///   but we still expect more fragment blocks after the blocks in the
+///   fragment (as noted by `m_flag`) so we are still missing [8, Y] so we
+///   create a new gap of [8, Y].
+fn remove_from_missing_blocks(
+    missing_blocks: &mut BTreeSet<(u16, u16)>,
+    fragment_blocks_range: (u16, u16),
+    m_flag: bool,
+    overlap_mode: OverlapMode,
+) -> Result<(u16, u16), ()> {
+    if let Some(gap) = find_gap(missing_blocks, fragment_blocks_range) {
+        remove_gap(missing_blocks, gap, fragment_blocks_range, m_flag);
+        return Ok(fragment_blocks_range);
+    }
+
+    if overlap_mode != OverlapMode::FavorNewer {
+        return Err(());
+    }
+
+    // `fragment_blocks_range` doesn't fit purely within a single gap, so it
+    // overlaps with fragment blocks we already received. In
+    // `OverlapMode::FavorNewer`, trim it down to just the blocks it shares
+    // with the single gap it overlaps, rather than rejecting it outright.
+    let gap = find_overlapping_gap(missing_blocks, fragment_blocks_range).ok_or(())?;
+    let accepted = (gap.0.max(fragment_blocks_range.0), gap.1.min(fragment_blocks_range.1));
+
+    // If the fragment's original range extends past `accepted`'s end, then
+    // the blocks after `accepted` were already received via some other
+    // fragment, so we should not treat them as still missing regardless of
+    // `m_flag`.
+    let still_expecting_more = m_flag && accepted.1 == fragment_blocks_range.1;
+    remove_gap(missing_blocks, gap, accepted, still_expecting_more);
+
+    Ok(accepted)
+}
+
+/// Attempts to process a packet fragment, writing its body directly into a
+/// caller-supplied buffer instead of buffering it internally.
+///
+/// This is an alternative to [`process_fragment`] for callers that want to
+/// avoid the extra copy (and the `Vec`/`BinaryHeap` allocations that come with
+/// it) of buffering each fragment's body in the fragment cache.
+/// `process_fragment_into_buffer` instead writes a fragment's body directly at
+/// its final offset in `body_buffer` as soon as it is received. The caller
+/// must allocate `body_buffer` with at least `((MAX_FRAGMENT_BLOCKS as usize)
+/// + 1) * (FRAGMENT_BLOCK_SIZE as usize)` bytes of capacity - a fragment at
+/// the maximum legal offset, `MAX_FRAGMENT_BLOCKS`, still needs room for its
+/// own body past that offset - and pass the exact same buffer to every call
+/// to `process_fragment_into_buffer` and to the eventual call to
+/// [`reassemble_packet_from_buffer`] for a given packet's `FragmentCacheKey`.
+///
+/// # Panics
+///
+/// Panics if the packet has no fragment data, or if `body_buffer` is not
+/// large enough to hold the fragment's body at its offset.
+pub(crate) fn process_fragment_into_buffer<I: Ip, C: FragmentContext<I>, B: ByteSlice>(
+    ctx: &mut C,
+    packet: <I as IpExtByteSlice<B>>::Packet,
+    body_buffer: &mut [u8],
+) -> FragmentProcessingState<B, I>
+where
+    <I as IpExtByteSlice<B>>::Packet: FragmentablePacket,
+{
+    let (id, offset, m_flag) = packet.fragment_data();
+
+    if offset == 0 && !m_flag {
+        return FragmentProcessingState::NotNeeded(packet);
+    }
+
+    if packet.body().is_empty() {
+        return FragmentProcessingState::NeedMoreFragments { packet_len: None };
+    }
+
+    if m_flag && (packet.body().len() % (FRAGMENT_BLOCK_SIZE as usize) != 0) {
+        return FragmentProcessingState::InvalidFragment;
+    }
+
+    let key = FragmentCacheKey::new(packet.src_ip(), packet.dst_ip(), id);
+    let fragment_data = get_or_create(ctx, &key);
+
+    let num_fragment_blocks =
+        1 + ((packet.fragment_body_len() - 1) / (FRAGMENT_BLOCK_SIZE as usize));
+    assert!(num_fragment_blocks > 0);
+
+    let fragment_blocks_range =
+        if let Ok(offset_end) = u16::try_from((offset as usize) + num_fragment_blocks - 1) {
+            if offset_end <= MAX_FRAGMENT_BLOCKS {
+                (offset, offset_end)
+            } else {
+                return FragmentProcessingState::InvalidFragment;
+            }
+        } else {
+            return FragmentProcessingState::InvalidFragment;
+        };
+
+    // If this is the last fragment (the more-fragments flag is unset), we now
+    // know the reassembled packet's exact body length, even if fragments
+    // before it are still missing.
+    if !m_flag {
+        fragment_data.expected_body_len =
+            Some((offset as usize) * (FRAGMENT_BLOCK_SIZE as usize) + packet.body().len());
+    }
+
+    if remove_from_missing_blocks(
+        &mut fragment_data.missing_blocks,
+        fragment_blocks_range,
+        m_flag,
+        OverlapMode::Strict,
+    )
+    .is_err()
+    {
+        // See the identically handled case in `process_fragment` for why we
+        // drop all reassembly data for `key` on an overlapping fragment.
+        assert!(ctx.get_state_mut(()).cache.remove(&key).is_some());
+        assert!(ctx.cancel_timer(key).is_some());
+
+        return FragmentProcessingState::InvalidFragment;
+    }
+
+    // Get header buffer from `packet` if its fragment offset equals to 0.
+    if offset == 0 {
+        assert!(fragment_data.header.is_none());
+        let header = get_header::<B, I>(&packet);
+        fragment_data.total_size += header.len();
+        fragment_data.header = Some(header);
+        fragment_data.first_fragment_received = true;
+    }
+
+    // Write our `packet`'s body directly into its final position in
+    // `body_buffer` instead of buffering it in `fragment_data`.
+    let body_start = (offset as usize) * (FRAGMENT_BLOCK_SIZE as usize);
+    body_buffer[body_start..body_start + packet.body().len()].copy_from_slice(packet.body());
+    fragment_data.total_size += packet.body().len();
+
+    if fragment_data.missing_blocks.is_empty() {
+        FragmentProcessingState::Ready { key, packet_len: fragment_data.total_size }
+    } else {
+        let packet_len = fragment_data
+            .header
+            .as_ref()
+            .and_then(|header| fragment_data.expected_body_len.map(|len| header.len() + len));
+        FragmentProcessingState::NeedMoreFragments { packet_len }
+    }
+}
+
+/// Attempts to reassemble a packet previously processed with
+/// [`process_fragment_into_buffer`].
+///
+/// Unlike [`reassemble_packet`], the packet's body does not need to be copied
+/// out of the fragment cache since [`process_fragment_into_buffer`] already
+/// wrote it directly into `body_buffer` at its final offset; `body_buffer`
+/// must be the same buffer that was passed to every call to
+/// `process_fragment_into_buffer` for `key`. This leaves
+/// `reassemble_packet_from_buffer` with only the packet's header left to fix
+/// up before copying it in front of the already-assembled body.
+///
+/// # Panics
+///
+/// Panics if the provided `buffer` does not have enough capacity for the
+/// reassembled packet, or if `body_buffer` is not large enough to hold the
+/// reassembled body. Also panics if a different `ctx` is passed to
+/// `reassemble_packet_from_buffer` from the one passed to
+/// `process_fragment_into_buffer` when processing a packet with a given `key`
+/// as `reassemble_packet_from_buffer` will fail to cancel the reassembly
+/// timer.
+///
+/// On success, in addition to the reassembled packet, `reassemble_packet_from_buffer`
+/// returns how long the packet spent in reassembly, measured from the receipt
+/// of its first fragment to this call.
+pub(crate) fn reassemble_packet_from_buffer<
+    I: Ip,
+    C: FragmentContext<I>,
+    B: ByteSliceMut,
+    BV: BufferViewMut<B>,
+>(
+    ctx: &mut C,
+    key: &FragmentCacheKey<I::Addr>,
+    body_buffer: &[u8],
+    buffer: BV,
+) -> Result<(<I as IpExtByteSlice<B>>::Packet, Duration), FragmentReassemblyError> {
+    // Get the fragment cache data.
+    let fragment_data = match ctx.get_state_mut(()).cache.get_mut(key) {
+        // Either there are no fragments for the given `key`, or we timed out
+        // and removed all fragment data for `key`.
+        None => return Err(FragmentReassemblyError::InvalidKey),
+        Some(d) => d,
+    };
+
+    // Make sure we are not missing fragments.
+    if !fragment_data.missing_blocks.is_empty() {
+        return Err(FragmentReassemblyError::MissingFragments);
+    }
+
+    // If we are not missing fragments, we must have header data.
+    assert!(fragment_data.header.is_some());
+
+    // Note how long the packet spent in reassembly before we cancel the timer
+    // and lose track of when its first fragment was received.
+    let reassembly_duration = ctx.now().duration_since(fragment_data.first_fragment_time);
+
+    // Cancel the reassembly timer now that we know we have all the data
+    // required for reassembly and are attempting to do so.
+    assert!(ctx.cancel_timer(*key).is_some());
+
+    // Take the header from the cache data and remove the cache data
+    // associated with `key` since it will no longer be needed. The body was
+    // already written into `body_buffer` by `process_fragment_into_buffer`.
+    let data = ctx.get_state_mut(()).cache.remove(key).unwrap();
+    let header = data.header.unwrap();
+    let body_len = data.total_size - header.len();
+
+    // Attempt to actually reassemble the packet.
+    reassemble_packet_from_buffer_helper::<B, BV, I>(buffer, header, &body_buffer[..body_len])
+        .map(|packet| (packet, reassembly_duration))
+}
+
 /// Attempts to reassemble a packet.
 ///
 /// Given a header buffer (`header`), body fragments (`body_fragments`), and a
@@ -671,20 +1258,100 @@ fn reassemble_packet_helper<B: ByteSliceMut, BV: BufferViewMut<B>, I: Ip>(
     }
 }
 
-/// Get the header bytes for a packet.
+/// Attempts to reassemble a packet whose body is already laid out
+/// contiguously in `body`.
+///
+/// This mirrors [`reassemble_packet_helper`], except that the body has
+/// already been written into its final position (by
+/// [`process_fragment_into_buffer`]) so there is no body fragments to pop off
+/// a `BinaryHeap`; only the header needs to be copied into `buffer` and fixed
+/// up.
 #[specialize_ip]
-fn get_header<B: ByteSlice, I: Ip>(packet: &<I as IpExtByteSlice<B>>::Packet) -> Vec<u8> {
+fn reassemble_packet_from_buffer_helper<B: ByteSliceMut, BV: BufferViewMut<B>, I: Ip>(
+    mut buffer: BV,
+    header: Vec<u8>,
+    body: &[u8],
+) -> Result<<I as IpExtByteSlice<B>>::Packet, FragmentReassemblyError> {
+    let bytes = buffer.as_mut();
+
+    // Copy over the header data, followed by the already-assembled body.
+    bytes[0..header.len()].copy_from_slice(&header[..]);
+    bytes[header.len()..header.len() + body.len()].copy_from_slice(body);
+    let byte_count = header.len() + body.len();
+
     #[ipv4]
     {
-        packet.copy_header_bytes_for_fragment()
-    }
+        //
+        // Fix up the IPv4 header
+        //
 
-    #[ipv6]
-    {
-        // We are guaranteed not to panic here because we will only panic if
-        // `packet` does not have a fragment extension header. We can only get
-        // here if `packet` is a fragment packet, so we know that `packet` has a
-        // fragment extension header.
+        // Make sure that the packet length is not more than the maximum
+        // possible IPv4 packet length.
+        if byte_count > (std::u16::MAX as usize) {
+            return Err(FragmentReassemblyError::PacketParsingError);
+        }
+
+        // Update the total length field.
+        NetworkEndian::write_u16(&mut bytes[IPV4_TOTAL_LENGTH_BYTE_RANGE], byte_count as u16);
+
+        // Zero out fragment related data since we will now have a reassembled
+        // packet that does not need reassembly.
+        NetworkEndian::write_u32(&mut bytes[IPV4_FRAGMENT_DATA_BYTE_RANGE], 0);
+
+        // Update header checksum. The header checksum field is at bytes 10 and
+        // 11 so do not include them in the checksum calculation.
+        let mut c = Checksum::new();
+        c.add_bytes(&bytes[..IPV4_CHECKSUM_BYTE_RANGE.start]);
+        c.add_bytes(&bytes[IPV4_CHECKSUM_BYTE_RANGE.end..header.len()]);
+        NetworkEndian::write_u16(&mut bytes[IPV4_CHECKSUM_BYTE_RANGE], c.checksum());
+    }
+
+    #[ipv6]
+    {
+        //
+        // Fix up the IPv6 header
+        //
+
+        // For IPv6, the payload length is the sum of the length of the
+        // extension headers and the packet body. The header as it is stored
+        // includes the IPv6 fixed header and all extension headers, so
+        // `bytes_count` is the sum of the size of the fixed header, extension
+        // headers and packet body. To calculate the payload length we subtract
+        // the size of the fixed header from the total byte count of a
+        // reassembled packet.
+        let payload_length = byte_count - IPV6_FIXED_HDR_LEN;
+
+        // Make sure that the payload length is not more than the maximum
+        // possible IPv4 packet length.
+        if payload_length > (std::u16::MAX as usize) {
+            return Err(FragmentReassemblyError::PacketParsingError);
+        }
+
+        // Update the payload length field.
+        NetworkEndian::write_u16(&mut bytes[IPV6_PAYLOAD_LEN_BYTE_RANGE], payload_length as u16);
+    }
+
+    // Parse the packet.
+    match <<I as IpExtByteSlice<B>>::Packet as ParsablePacket<B, _>>::parse_mut(buffer, ()) {
+        Ok(p) => Ok(p),
+        _ => Err(FragmentReassemblyError::PacketParsingError),
+    }
+}
+
+/// Get the header bytes for a packet.
+#[specialize_ip]
+fn get_header<B: ByteSlice, I: Ip>(packet: &<I as IpExtByteSlice<B>>::Packet) -> Vec<u8> {
+    #[ipv4]
+    {
+        packet.copy_header_bytes_for_fragment()
+    }
+
+    #[ipv6]
+    {
+        // We are guaranteed not to panic here because we will only panic if
+        // `packet` does not have a fragment extension header. We can only get
+        // here if `packet` is a fragment packet, so we know that `packet` has a
+        // fragment extension header.
         packet.copy_header_bytes_for_fragment()
     }
 }
@@ -721,25 +1388,30 @@ impl Ord for PacketBodyFragment {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::BuildHasherDefault;
+
     use net_types::ip::{IpAddress, Ipv4, Ipv6};
     use packet::{Buf, ParseBuffer, Serializer};
     use specialize_ip_macro::specialize_ip;
 
     use super::*;
+    use crate::context::testutil::DummyInstant;
     use crate::ip::{IpProto, Ipv6ExtHdrType};
     use crate::testutil::{
-        get_dummy_config, run_for, trigger_next_timer, DummyEventDispatcher,
-        DummyEventDispatcherBuilder, DUMMY_CONFIG_V4, DUMMY_CONFIG_V6,
+        get_dummy_config, run_for, set_logger_for_test, take_captured_logs, trigger_next_timer,
+        DummyEventDispatcher, DummyEventDispatcherBuilder, DUMMY_CONFIG_V4, DUMMY_CONFIG_V6,
     };
     use crate::wire::ipv4::{Ipv4Packet, Ipv4PacketBuilder};
     use crate::wire::ipv6::{Ipv6Packet, Ipv6PacketBuilder};
-    use crate::{Context, EventDispatcher};
+    use crate::{Context, EventDispatcher, StackStateBuilder};
 
     macro_rules! assert_frag_proc_state_need_more {
         ($lhs:expr) => {{
             let lhs_val = $lhs;
             match lhs_val {
-                FragmentProcessingState::NeedMoreFragments => lhs_val,
+                FragmentProcessingState::NeedMoreFragments { .. } => lhs_val,
                 _ => panic!("{:?} is not `NeedMoreFragments`", lhs_val),
             }
         }};
@@ -883,7 +1555,7 @@ mod tests {
                 if expected_result == ExpectedResult::ReadyReassemble {
                     let mut buffer: Vec<u8> = vec![0; packet_len];
                     let mut buffer = &mut buffer[..];
-                    let packet =
+                    let (packet, _) =
                         reassemble_packet::<Ipv4, _, &mut [u8], _>(ctx, &key, &mut buffer).unwrap();
                     let mut expected_body: Vec<u8> = Vec::new();
                     expected_body
@@ -955,7 +1627,7 @@ mod tests {
                 if expected_result == ExpectedResult::ReadyReassemble {
                     let mut buffer: Vec<u8> = vec![0; packet_len];
                     let mut buffer = &mut buffer[..];
-                    let packet =
+                    let (packet, _) =
                         reassemble_packet::<Ipv6, _, &mut [u8], _>(ctx, &key, &mut buffer).unwrap();
                     let mut expected_body: Vec<u8> = Vec::new();
                     expected_body
@@ -972,6 +1644,310 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fragment_cache_key_accessors() {
+        let key = FragmentCacheKey::new(DUMMY_CONFIG_V4.remote_ip, DUMMY_CONFIG_V4.local_ip, 5);
+        assert_eq!(key.src_ip(), DUMMY_CONFIG_V4.remote_ip);
+        assert_eq!(key.dst_ip(), DUMMY_CONFIG_V4.local_ip);
+        assert_eq!(key.fragment_id(), 5);
+    }
+
+    #[test]
+    fn test_fragment_cache_key_from_packet() {
+        let fragment_id = 42;
+
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = (0..FRAGMENT_BLOCK_SIZE).collect();
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+
+        assert_eq!(
+            FragmentCacheKey::from_packet::<&[u8], Ipv4>(&packet),
+            FragmentCacheKey::new(
+                DUMMY_CONFIG_V4.remote_ip,
+                DUMMY_CONFIG_V4.local_ip,
+                fragment_id as u32,
+            ),
+        );
+    }
+
+    #[test]
+    fn test_process_fragment_logs_trace_messages_on_successful_reassembly() {
+        set_logger_for_test();
+
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 42;
+
+        let make_packet = |fragment_offset: u16, m_flag: bool| {
+            let mut builder = get_ipv4_builder();
+            builder.id(fragment_id);
+            builder.fragment_offset(fragment_offset);
+            builder.mf_flag(m_flag);
+            let body: Vec<u8> = (0..FRAGMENT_BLOCK_SIZE).collect();
+            Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap()
+        };
+
+        // First fragment: more fragments still to come, so we expect a "found a gap" message,
+        // but not a "ready for reassembly" one.
+        let mut buffer = make_packet(0, true);
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let _ = take_captured_logs();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+        let logs = take_captured_logs();
+        assert!(
+            logs.iter().any(|m| m.contains("process_fragment") && m.contains("found a gap")),
+            "expected a 'found a gap' trace message, got {:?}",
+            logs
+        );
+        assert!(!logs.iter().any(|m| m.contains("is ready for reassembly")), "{:?}", logs);
+
+        // Second, final fragment completes the reassembly, so we now expect both messages.
+        let mut buffer = make_packet(1, false);
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let _ = take_captured_logs();
+        assert_frag_proc_state_ready!(
+            process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id,
+            (FRAGMENT_BLOCK_SIZE as usize) * 2 + 20
+        );
+        let logs = take_captured_logs();
+        assert!(
+            logs.iter().any(|m| m.contains("process_fragment") && m.contains("found a gap")),
+            "expected a 'found a gap' trace message, got {:?}",
+            logs
+        );
+        assert!(
+            logs.iter().any(|m| m.contains("is ready for reassembly")),
+            "expected an 'is ready for reassembly' trace message, got {:?}",
+            logs
+        );
+    }
+
+    #[test]
+    fn test_merge_adjacent_gaps() {
+        // Contiguous gaps get merged into a single entry.
+        let mut missing_blocks = BTreeSet::new();
+        missing_blocks.insert((0, 3));
+        missing_blocks.insert((4, 7));
+        missing_blocks.insert((20, 25));
+        merge_adjacent_gaps(&mut missing_blocks);
+        let mut expected = BTreeSet::new();
+        expected.insert((0, 7));
+        expected.insert((20, 25));
+        assert_eq!(missing_blocks, expected);
+
+        // A chain of more than two contiguous gaps all get merged together.
+        let mut missing_blocks = BTreeSet::new();
+        missing_blocks.insert((0, 1));
+        missing_blocks.insert((2, 3));
+        missing_blocks.insert((4, 5));
+        merge_adjacent_gaps(&mut missing_blocks);
+        let mut expected = BTreeSet::new();
+        expected.insert((0, 5));
+        assert_eq!(missing_blocks, expected);
+
+        // Gaps separated by at least one already-received block are left alone.
+        let mut missing_blocks = BTreeSet::new();
+        missing_blocks.insert((0, 3));
+        missing_blocks.insert((5, 7));
+        merge_adjacent_gaps(&mut missing_blocks);
+        let mut expected = BTreeSet::new();
+        expected.insert((0, 3));
+        expected.insert((5, 7));
+        assert_eq!(missing_blocks, expected);
+    }
+
+    #[test]
+    fn test_remove_gap_merges_resulting_gap_with_an_already_adjacent_gap() {
+        // `remove_gap` only ever splits a single gap around the accepted range, and the accepted
+        // range always separates the two resulting sub-gaps by at least one already-received
+        // block, so a single call can never produce two gaps that are adjacent *to each other*.
+        // A gap split off by `remove_gap` can still end up adjacent to an unrelated gap that was
+        // already present in `missing_blocks` (e.g. left behind by an earlier fragment), so we
+        // set up that situation directly here rather than through `process_fragment`.
+        let mut missing_blocks = BTreeSet::new();
+        missing_blocks.insert((20, 23));
+
+        // Accept blocks 8 through 11 out of a (8, 19) gap, leaving (12, 19) behind, which is
+        // adjacent to the pre-existing (20, 23) gap and should be merged with it.
+        remove_gap(&mut missing_blocks, (8, 19), (8, 11), true);
+
+        let mut expected = BTreeSet::new();
+        expected.insert((12, 23));
+        assert_eq!(missing_blocks, expected);
+    }
+
+    #[test]
+    fn test_missing_blocks() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 5;
+        let key = FragmentCacheKey::new(
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id as u32,
+        );
+
+        let get_missing_blocks = |ctx: &Context<DummyEventDispatcher>| {
+            let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+            cache.missing_blocks(key)
+        };
+
+        // No reassembly in-flight for `key` yet.
+        assert_eq!(get_missing_blocks(&ctx), None);
+
+        // After the first of 3 fragments, block 0 is received and everything else is missing.
+        process_ipv4_fragment(&mut ctx, fragment_id, 0, 3, ExpectedResult::NeedMore);
+        assert_eq!(get_missing_blocks(&ctx), Some(vec![(1, std::u16::MAX)]));
+
+        // After the second of 3 fragments, blocks 0 and 1 are received.
+        process_ipv4_fragment(&mut ctx, fragment_id, 1, 3, ExpectedResult::NeedMore);
+        assert_eq!(get_missing_blocks(&ctx), Some(vec![(2, std::u16::MAX)]));
+
+        // Once reassembly completes, the key is no longer present in the cache.
+        process_ipv4_fragment(&mut ctx, fragment_id, 2, 3, ExpectedResult::ReadyReassemble);
+        assert_eq!(get_missing_blocks(&ctx), None);
+    }
+
+    #[test]
+    fn test_has_first_fragment() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 6;
+        let key = FragmentCacheKey::new(
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id as u32,
+        );
+
+        let has_first_fragment = |ctx: &Context<DummyEventDispatcher>| {
+            let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+            cache.has_first_fragment(key)
+        };
+
+        // No reassembly in-flight for `key` yet.
+        assert_eq!(has_first_fragment(&ctx), None);
+
+        // The fragment with offset 1 arrives before the one with offset 0;
+        // the first (offset 0) fragment still hasn't been seen.
+        process_ipv4_fragment(&mut ctx, fragment_id, 1, 3, ExpectedResult::NeedMore);
+        assert_eq!(has_first_fragment(&ctx), Some(false));
+
+        // Once the offset-0 fragment arrives, it's reflected immediately.
+        process_ipv4_fragment(&mut ctx, fragment_id, 0, 3, ExpectedResult::NeedMore);
+        assert_eq!(has_first_fragment(&ctx), Some(true));
+
+        // Once reassembly completes, the key is no longer present in the cache.
+        process_ipv4_fragment(&mut ctx, fragment_id, 2, 3, ExpectedResult::ReadyReassemble);
+        assert_eq!(has_first_fragment(&ctx), None);
+    }
+
+    #[test]
+    fn test_compact_reclaims_timed_out_entries() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 8;
+        let key = FragmentCacheKey::new(
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id as u32,
+        );
+
+        // Insert a partial reassembly; this schedules a reassembly timer, but
+        // we never let it fire.
+        process_ipv4_fragment(&mut ctx, fragment_id, 0, 3, ExpectedResult::NeedMore);
+        let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+        assert_eq!(cache.has_first_fragment(key), Some(true));
+
+        // Compacting before the entry has timed out should not remove it.
+        let cache: &mut IpLayerFragmentCache<Ipv4, _> = ctx.get_state_mut(());
+        cache.compact(ctx.now());
+        let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+        assert_eq!(cache.has_first_fragment(key), Some(true));
+
+        // Compacting as of a time past the timeout should remove the entry,
+        // without ever firing the reassembly timer.
+        let past_timeout = ctx.now() + Duration::from_secs(REASSEMBLY_TIMEOUT_SECONDS);
+        let cache: &mut IpLayerFragmentCache<Ipv4, _> = ctx.get_state_mut(());
+        cache.compact(past_timeout);
+        let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+        assert_eq!(cache.has_first_fragment(key), None);
+    }
+
+    #[test]
+    fn test_pending_by_source() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let other_source = DUMMY_CONFIG_V4.local_ip;
+
+        let get_pending_by_source = |ctx: &Context<DummyEventDispatcher>| {
+            let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+            cache.pending_by_source()
+        };
+
+        // No reassemblies in-flight yet.
+        assert_eq!(get_pending_by_source(&ctx).len(), 0);
+
+        // Two in-flight reassemblies from `DUMMY_CONFIG_V4.remote_ip`.
+        process_ipv4_fragment(&mut ctx, 0, 0, 2, ExpectedResult::NeedMore);
+        process_ipv4_fragment(&mut ctx, 1, 0, 2, ExpectedResult::NeedMore);
+
+        // One in-flight reassembly from `other_source`, with a fragment built
+        // by hand since `process_ipv4_fragment` always uses
+        // `DUMMY_CONFIG_V4.remote_ip` as the source.
+        let mut builder =
+            Ipv4PacketBuilder::new(other_source, DUMMY_CONFIG_V4.local_ip, 10, IpProto::Tcp);
+        builder.id(2);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = (0..FRAGMENT_BLOCK_SIZE).collect();
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        let pending = get_pending_by_source(&ctx);
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[&DUMMY_CONFIG_V4.remote_ip], 2);
+        assert_eq!(pending[&other_source], 1);
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+
+        let get_keys = |ctx: &Context<DummyEventDispatcher>| {
+            let cache: &IpLayerFragmentCache<Ipv4, _> = ctx.get_state(());
+            cache.keys().copied().collect::<HashSet<_>>()
+        };
+
+        // No reassemblies in-flight yet.
+        assert_eq!(get_keys(&ctx), HashSet::new());
+
+        // Two in-flight reassemblies, for two different fragment IDs.
+        process_ipv4_fragment(&mut ctx, 0, 0, 2, ExpectedResult::NeedMore);
+        process_ipv4_fragment(&mut ctx, 1, 0, 2, ExpectedResult::NeedMore);
+
+        let mut expected = HashSet::new();
+        expected.insert(FragmentCacheKey::new(
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            0,
+        ));
+        expected.insert(FragmentCacheKey::new(
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            1,
+        ));
+        assert_eq!(get_keys(&ctx), expected);
+    }
+
     #[test]
     fn test_ipv4_reassembly_not_needed() {
         let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
@@ -1036,6 +2012,218 @@ mod tests {
         test_ip_reassembly::<Ipv6>();
     }
 
+    #[test]
+    fn test_ipv4_reassembly_into_buffer_matches_copy_based() {
+        let fragment_id = 5;
+        let fragment_count = 3;
+        // Process fragments out of order to make sure `process_fragment_into_buffer`
+        // writes each fragment's body at the correct offset regardless of arrival
+        // order.
+        let order = [1u8, 0, 2];
+
+        let get_bytes = |offset: u8| -> Vec<u8> {
+            let m_flag = offset < (fragment_count - 1);
+            let body_offset = fragment_id as u8;
+            let mut builder = get_ipv4_builder();
+            builder.id(fragment_id);
+            builder.fragment_offset(offset as u16);
+            builder.mf_flag(m_flag);
+            let mut body: Vec<u8> = Vec::new();
+            body.extend(
+                body_offset + offset * FRAGMENT_BLOCK_SIZE
+                    ..body_offset + offset * FRAGMENT_BLOCK_SIZE + FRAGMENT_BLOCK_SIZE,
+            );
+            Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap().as_ref().to_vec()
+        };
+
+        // Reassemble via the existing copy-based flow.
+        let mut copy_ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let mut ready = None;
+        for &offset in order.iter() {
+            let mut buffer = Buf::new(get_bytes(offset), ..);
+            let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+            match process_fragment::<Ipv4, _, &[u8]>(&mut copy_ctx, packet) {
+                FragmentProcessingState::NeedMoreFragments { .. } => {}
+                FragmentProcessingState::Ready { key, packet_len } => {
+                    ready = Some((key, packet_len))
+                }
+                other => panic!("unexpected state processing fragment {}: {:?}", offset, other),
+            }
+        }
+        let (key, packet_len) = ready.unwrap();
+        let mut copy_buffer: Vec<u8> = vec![0; packet_len];
+        let mut copy_buffer_ref = &mut copy_buffer[..];
+        let (copy_packet, _) =
+            reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut copy_ctx, &key, &mut copy_buffer_ref)
+                .unwrap();
+        let copy_body = copy_packet.body().to_vec();
+
+        // Reassemble via the buffer-based flow, writing each fragment directly
+        // into a preallocated buffer as it arrives.
+        let mut buffer_ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let mut body_buffer =
+            vec![0; ((MAX_FRAGMENT_BLOCKS as usize) + 1) * (FRAGMENT_BLOCK_SIZE as usize)];
+        let mut ready = None;
+        for &offset in order.iter() {
+            let mut buffer = Buf::new(get_bytes(offset), ..);
+            let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+            match process_fragment_into_buffer::<Ipv4, _, &[u8]>(
+                &mut buffer_ctx,
+                packet,
+                &mut body_buffer[..],
+            ) {
+                FragmentProcessingState::NeedMoreFragments { .. } => {}
+                FragmentProcessingState::Ready { key, packet_len } => {
+                    ready = Some((key, packet_len))
+                }
+                other => panic!("unexpected state processing fragment {}: {:?}", offset, other),
+            }
+        }
+        let (key, packet_len) = ready.unwrap();
+        let mut final_buffer: Vec<u8> = vec![0; packet_len];
+        let mut final_buffer_ref = &mut final_buffer[..];
+        let (reassembled_packet, _) = reassemble_packet_from_buffer::<Ipv4, _, &mut [u8], _>(
+            &mut buffer_ctx,
+            &key,
+            &body_buffer[..],
+            &mut final_buffer_ref,
+        )
+        .unwrap();
+
+        assert_eq!(reassembled_packet.body(), &copy_body[..]);
+    }
+
+    #[test]
+    fn test_ipv6_reassembly_into_buffer_matches_copy_based() {
+        let fragment_id = 5;
+        let fragment_count = 3;
+        // Process fragments out of order to make sure `process_fragment_into_buffer`
+        // writes each fragment's body at the correct offset regardless of arrival
+        // order.
+        let order = [1u8, 0, 2];
+
+        let get_bytes = |offset: u8| -> Vec<u8> {
+            let m_flag = offset < (fragment_count - 1);
+            let body_offset = fragment_id as u8;
+            let mut bytes = vec![0; 48];
+            bytes[..4].copy_from_slice(&[0x60, 0x20, 0x00, 0x77][..]);
+            bytes[6] = Ipv6ExtHdrType::Fragment.into(); // Next Header
+            bytes[7] = 64;
+            bytes[8..24].copy_from_slice(DUMMY_CONFIG_V6.remote_ip.bytes());
+            bytes[24..40].copy_from_slice(DUMMY_CONFIG_V6.local_ip.bytes());
+            bytes[40] = IpProto::Tcp.into();
+            bytes[42] = offset >> 5;
+            bytes[43] = ((offset & 0x1F) << 3) | if m_flag { 1 } else { 0 };
+            NetworkEndian::write_u32(&mut bytes[44..48], fragment_id as u32);
+            bytes.extend(
+                body_offset + offset * FRAGMENT_BLOCK_SIZE
+                    ..body_offset + offset * FRAGMENT_BLOCK_SIZE + FRAGMENT_BLOCK_SIZE,
+            );
+            let payload_len = (bytes.len() - 40) as u16;
+            NetworkEndian::write_u16(&mut bytes[4..6], payload_len);
+            bytes
+        };
+
+        // Reassemble via the existing copy-based flow.
+        let mut copy_ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V6)
+            .build::<DummyEventDispatcher>();
+        let mut ready = None;
+        for &offset in order.iter() {
+            let mut buffer = Buf::new(get_bytes(offset), ..);
+            let packet = buffer.parse::<Ipv6Packet<_>>().unwrap();
+            match process_fragment::<Ipv6, _, &[u8]>(&mut copy_ctx, packet) {
+                FragmentProcessingState::NeedMoreFragments { .. } => {}
+                FragmentProcessingState::Ready { key, packet_len } => {
+                    ready = Some((key, packet_len))
+                }
+                other => panic!("unexpected state processing fragment {}: {:?}", offset, other),
+            }
+        }
+        let (key, packet_len) = ready.unwrap();
+        let mut copy_buffer: Vec<u8> = vec![0; packet_len];
+        let mut copy_buffer_ref = &mut copy_buffer[..];
+        let (copy_packet, _) =
+            reassemble_packet::<Ipv6, _, &mut [u8], _>(&mut copy_ctx, &key, &mut copy_buffer_ref)
+                .unwrap();
+        let copy_body = copy_packet.body().to_vec();
+
+        // Reassemble via the buffer-based flow, writing each fragment directly
+        // into a preallocated buffer as it arrives.
+        let mut buffer_ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V6)
+            .build::<DummyEventDispatcher>();
+        let mut body_buffer =
+            vec![0; ((MAX_FRAGMENT_BLOCKS as usize) + 1) * (FRAGMENT_BLOCK_SIZE as usize)];
+        let mut ready = None;
+        for &offset in order.iter() {
+            let mut buffer = Buf::new(get_bytes(offset), ..);
+            let packet = buffer.parse::<Ipv6Packet<_>>().unwrap();
+            match process_fragment_into_buffer::<Ipv6, _, &[u8]>(
+                &mut buffer_ctx,
+                packet,
+                &mut body_buffer[..],
+            ) {
+                FragmentProcessingState::NeedMoreFragments { .. } => {}
+                FragmentProcessingState::Ready { key, packet_len } => {
+                    ready = Some((key, packet_len))
+                }
+                other => panic!("unexpected state processing fragment {}: {:?}", offset, other),
+            }
+        }
+        let (key, packet_len) = ready.unwrap();
+        let mut final_buffer: Vec<u8> = vec![0; packet_len];
+        let mut final_buffer_ref = &mut final_buffer[..];
+        let (reassembled_packet, _) = reassemble_packet_from_buffer::<Ipv6, _, &mut [u8], _>(
+            &mut buffer_ctx,
+            &key,
+            &body_buffer[..],
+            &mut final_buffer_ref,
+        )
+        .unwrap();
+
+        assert_eq!(reassembled_packet.body(), &copy_body[..]);
+    }
+
+    #[test]
+    fn test_ipv4_reassembly_into_buffer_at_max_offset() {
+        // Regression test: a fragment at the maximum legal 13-bit offset,
+        // `MAX_FRAGMENT_BLOCKS`, writes its body starting at
+        // `MAX_FRAGMENT_BLOCKS * FRAGMENT_BLOCK_SIZE`, so `body_buffer` must
+        // have room past that offset. A buffer sized to exactly
+        // `MAX_FRAGMENT_BLOCKS * FRAGMENT_BLOCK_SIZE` bytes would panic here.
+        let fragment_id = 5;
+        let offset = MAX_FRAGMENT_BLOCKS;
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(offset);
+        builder.mf_flag(false);
+        let body = vec![1, 2, 3, 4];
+        let bytes = Buf::new(body.clone(), ..)
+            .encapsulate(builder)
+            .serialize_vec_outer()
+            .unwrap()
+            .as_ref()
+            .to_vec();
+
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let mut body_buffer =
+            vec![0; ((MAX_FRAGMENT_BLOCKS as usize) + 1) * (FRAGMENT_BLOCK_SIZE as usize)];
+        let mut buffer = Buf::new(bytes, ..);
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        // This fragment alone doesn't complete the packet, but it must not
+        // panic while writing its body into `body_buffer` at the maximum
+        // legal offset.
+        match process_fragment_into_buffer::<Ipv4, _, &[u8]>(&mut ctx, packet, &mut body_buffer[..])
+        {
+            FragmentProcessingState::NeedMoreFragments { .. } => {}
+            other => panic!("unexpected state: {:?}", other),
+        }
+        let body_start = (offset as usize) * (FRAGMENT_BLOCK_SIZE as usize);
+        assert_eq!(&body_buffer[body_start..body_start + body.len()], &body[..]);
+    }
+
     fn test_ip_reassemble_with_missing_blocks<I: Ip>() {
         let dummy_config = get_dummy_config::<I::Addr>();
         let mut ctx = DummyEventDispatcherBuilder::from_config(dummy_config.clone())
@@ -1162,6 +2350,185 @@ mod tests {
         test_ip_overlapping_single_fragment::<Ipv6>();
     }
 
+    #[test]
+    fn test_ipv4_favor_newer_overlap_mode_reassembles_overlapping_fragments() {
+        let mut stack_builder = StackStateBuilder::default();
+        stack_builder.ip_builder().ipv4_fragment_overlap_mode(OverlapMode::FavorNewer);
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build_with(stack_builder, DummyEventDispatcher::default());
+        let fragment_id = 6;
+
+        // Fragment #0 covers blocks 0 and 1.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16, 17];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        // Fragment #1 covers blocks 1 and 2, overlapping fragment #0's block 1
+        // with stale data. Under `OverlapMode::Strict`, this would be rejected
+        // outright; under `OverlapMode::FavorNewer`, the overlapping block 1
+        // is dropped and only block 2's data (which fills the remaining gap)
+        // is kept.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(1);
+        builder.mf_flag(false);
+        let body: Vec<u8> = vec![90, 91, 92, 93, 94, 95, 96, 97, 20, 21, 22, 23, 24, 25, 26, 27];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let (key, packet_len) = assert_frag_proc_state_ready!(
+            process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id,
+            44
+        );
+
+        let mut buffer: Vec<u8> = vec![0; packet_len];
+        let mut buffer = &mut buffer[..];
+        let (packet, _) =
+            reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut ctx, &key, &mut buffer).unwrap();
+        let expected_body: Vec<u8> = vec![
+            0, 1, 2, 3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16, 17, 20, 21, 22, 23, 24, 25, 26, 27,
+        ];
+        assert_eq!(packet.body(), &expected_body[..]);
+    }
+
+    #[test]
+    fn test_allow_duplicates_overlap_mode_ignores_exact_duplicate_fragment() {
+        let mut stack_builder = StackStateBuilder::default();
+        stack_builder.ip_builder().ipv4_fragment_overlap_mode(OverlapMode::AllowDuplicates);
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build_with(stack_builder, DummyEventDispatcher::default());
+        let fragment_id = 7;
+
+        // Fragment #0 covers block 0.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        // Deliver the exact same fragment #0 again. Since it is byte-for-byte
+        // identical to the one already received, it is treated as a no-op
+        // rather than tearing down reassembly state.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        // Fragment #1 completes the packet.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(1);
+        builder.mf_flag(false);
+        let body: Vec<u8> = vec![10, 11, 12, 13, 14, 15, 16, 17];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let (key, packet_len) = assert_frag_proc_state_ready!(
+            process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id,
+            36
+        );
+
+        let mut buffer: Vec<u8> = vec![0; packet_len];
+        let mut buffer = &mut buffer[..];
+        let (packet, _) =
+            reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut ctx, &key, &mut buffer).unwrap();
+        let expected_body: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16, 17];
+        assert_eq!(packet.body(), &expected_body[..]);
+    }
+
+    #[test]
+    fn test_allow_duplicates_overlap_mode_still_tears_down_on_conflicting_overlap() {
+        let mut stack_builder = StackStateBuilder::default();
+        stack_builder.ip_builder().ipv4_fragment_overlap_mode(OverlapMode::AllowDuplicates);
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build_with(stack_builder, DummyEventDispatcher::default());
+        let fragment_id = 9;
+
+        // Fragment #0 covers block 0.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        // Deliver a different fragment #0, with the same range of fragment
+        // blocks but conflicting data. This is a genuine overlap, not a
+        // duplicate, so reassembly state is torn down even under
+        // `OverlapMode::AllowDuplicates`.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = vec![90, 91, 92, 93, 94, 95, 96, 97];
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_invalid!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+    }
+
+    #[test]
+    fn test_ipv4_last_fragment_before_middle_reports_exact_final_size() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 8;
+
+        // Deliver fragment #0 (blocks 0 and 1, the header) first. The last
+        // fragment has not been received yet, so the final size cannot be
+        // reported yet.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(0);
+        builder.mf_flag(true);
+        let body: Vec<u8> = (0..FRAGMENT_BLOCK_SIZE * 2).collect();
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        match process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet) {
+            FragmentProcessingState::NeedMoreFragments { packet_len } => {
+                assert_eq!(packet_len, None);
+            }
+            other => panic!("{:?} is not `NeedMoreFragments`", other),
+        }
+
+        // Deliver the last fragment (block 3, with the more-fragments flag
+        // unset) before block 2 arrives. Even though a fragment is still
+        // missing, the reassembled packet's exact final size should now be
+        // known, since we have both the header (from fragment #0) and the
+        // last fragment.
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(3);
+        builder.mf_flag(false);
+        let body: Vec<u8> = (0..FRAGMENT_BLOCK_SIZE).collect();
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        match process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet) {
+            FragmentProcessingState::NeedMoreFragments { packet_len } => {
+                // 4 blocks of `FRAGMENT_BLOCK_SIZE` bytes, plus a 20 byte
+                // IPv4 header.
+                assert_eq!(packet_len, Some((FRAGMENT_BLOCK_SIZE as usize) * 4 + 20));
+            }
+            other => panic!("{:?} is not `NeedMoreFragments`", other),
+        }
+    }
+
     #[test]
     fn test_ipv4_fragment_not_multiple_of_offset_unit() {
         let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
@@ -1212,7 +2579,7 @@ mod tests {
         );
         let mut buffer: Vec<u8> = vec![0; packet_len];
         let mut buffer = &mut buffer[..];
-        let packet =
+        let (packet, _) =
             reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut ctx, &key, &mut buffer).unwrap();
         let mut expected_body: Vec<u8> = Vec::new();
         expected_body.extend(0..15);
@@ -1279,7 +2646,7 @@ mod tests {
         );
         let mut buffer: Vec<u8> = vec![0; packet_len];
         let mut buffer = &mut buffer[..];
-        let packet =
+        let (packet, _) =
             reassemble_packet::<Ipv6, _, &mut [u8], _>(&mut ctx, &key, &mut buffer).unwrap();
         let mut expected_body: Vec<u8> = Vec::new();
         expected_body.extend(0..15);
@@ -1414,4 +2781,257 @@ mod tests {
     fn test_ipv6_reassembly_timer_with_multiple_intertwined_packets() {
         test_ip_reassembly_timer_with_multiple_intertwined_packets::<Ipv6>();
     }
+
+    #[test]
+    fn test_ipv4_reassembly_duration() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V4)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 5;
+
+        // Process fragment #0, establishing the packet's `first_fragment_time`.
+        process_ipv4_fragment(&mut ctx, fragment_id, 0, 3, ExpectedResult::NeedMore);
+
+        // Advance the dummy clock before the next fragment arrives.
+        assert_eq!(run_for(&mut ctx, Duration::from_secs(10)), 0);
+        process_ipv4_fragment(&mut ctx, fragment_id, 1, 3, ExpectedResult::NeedMore);
+
+        // Advance the dummy clock again before the final fragment arrives and
+        // reassembly becomes possible.
+        assert_eq!(run_for(&mut ctx, Duration::from_secs(15)), 0);
+        let mut builder = get_ipv4_builder();
+        builder.id(fragment_id);
+        builder.fragment_offset(2);
+        builder.mf_flag(false);
+        let mut body: Vec<u8> = Vec::new();
+        let body_offset = fragment_id as u8;
+        body.extend(
+            body_offset + 2 * FRAGMENT_BLOCK_SIZE..body_offset + 3 * FRAGMENT_BLOCK_SIZE,
+        );
+        let mut buffer = Buf::new(body, ..).encapsulate(builder).serialize_vec_outer().unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let (key, packet_len) = assert_frag_proc_state_ready!(
+            process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            fragment_id,
+            (FRAGMENT_BLOCK_SIZE as usize) * 3 + 20
+        );
+
+        let mut reassembly_buffer: Vec<u8> = vec![0; packet_len];
+        let mut reassembly_buffer = &mut reassembly_buffer[..];
+        let (_packet, reassembly_duration) =
+            reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut ctx, &key, &mut reassembly_buffer)
+                .unwrap();
+
+        // The first fragment arrived at T=0s and the last (unblocking) fragment
+        // arrived at T=25s, so reassembly should report 25s elapsed.
+        assert_eq!(reassembly_duration, Duration::from_secs(25));
+    }
+
+    #[test]
+    fn test_ipv6_reassembly_duration() {
+        let mut ctx = DummyEventDispatcherBuilder::from_config(DUMMY_CONFIG_V6)
+            .build::<DummyEventDispatcher>();
+        let fragment_id = 5;
+
+        // Process fragment #0, establishing the packet's `first_fragment_time`.
+        process_ipv6_fragment(&mut ctx, fragment_id, 0, 3, ExpectedResult::NeedMore);
+
+        // Advance the dummy clock before the next fragment arrives.
+        assert_eq!(run_for(&mut ctx, Duration::from_secs(10)), 0);
+        process_ipv6_fragment(&mut ctx, fragment_id, 1, 3, ExpectedResult::NeedMore);
+
+        // Advance the dummy clock again before the final fragment arrives and
+        // reassembly becomes possible.
+        assert_eq!(run_for(&mut ctx, Duration::from_secs(15)), 0);
+        let body_offset = fragment_id as u8;
+        let mut bytes = vec![0; 48];
+        bytes[..4].copy_from_slice(&[0x60, 0x20, 0x00, 0x77][..]);
+        bytes[6] = Ipv6ExtHdrType::Fragment.into(); // Next Header
+        bytes[7] = 64;
+        bytes[8..24].copy_from_slice(DUMMY_CONFIG_V6.remote_ip.bytes());
+        bytes[24..40].copy_from_slice(DUMMY_CONFIG_V6.local_ip.bytes());
+        bytes[40] = IpProto::Tcp.into();
+        bytes[42] = 2 >> 5;
+        bytes[43] = (2 & 0x1F) << 3;
+        NetworkEndian::write_u32(&mut bytes[44..48], fragment_id as u32);
+        bytes.extend(
+            body_offset + 2 * FRAGMENT_BLOCK_SIZE..body_offset + 3 * FRAGMENT_BLOCK_SIZE,
+        );
+        let payload_len = (bytes.len() - 40) as u16;
+        NetworkEndian::write_u16(&mut bytes[4..6], payload_len);
+        let mut buf = Buf::new(bytes, ..);
+        let packet = buf.parse::<Ipv6Packet<_>>().unwrap();
+        let (key, packet_len) = assert_frag_proc_state_ready!(
+            process_fragment::<Ipv6, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V6.remote_ip,
+            DUMMY_CONFIG_V6.local_ip,
+            fragment_id,
+            (FRAGMENT_BLOCK_SIZE as usize) * 3 + 40
+        );
+
+        let mut reassembly_buffer: Vec<u8> = vec![0; packet_len];
+        let mut reassembly_buffer = &mut reassembly_buffer[..];
+        let (_packet, reassembly_duration) =
+            reassemble_packet::<Ipv6, _, &mut [u8], _>(&mut ctx, &key, &mut reassembly_buffer)
+                .unwrap();
+
+        // The first fragment arrived at T=0s and the last (unblocking) fragment
+        // arrived at T=25s, so reassembly should report 25s elapsed.
+        assert_eq!(reassembly_duration, Duration::from_secs(25));
+    }
+
+    /// A `FragmentablePacket` whose `fragment_body_len` is independent of its
+    /// (nonexistent) body, to confirm the real `Ipv4Packet`/`Ipv6Packet` impls
+    /// aren't the only thing standing in for a body-length query.
+    struct DummyFragmentablePacket {
+        fragment_data: (u32, u16, bool),
+        fragment_body_len: usize,
+    }
+
+    impl FragmentablePacket for DummyFragmentablePacket {
+        fn fragment_data(&self) -> (u32, u16, bool) {
+            self.fragment_data
+        }
+
+        fn fragment_body_len(&self) -> usize {
+            self.fragment_body_len
+        }
+    }
+
+    #[test]
+    fn test_fragmentable_packet_fragment_body_len() {
+        let packet = DummyFragmentablePacket { fragment_data: (1, 0, true), fragment_body_len: 8 };
+        assert_eq!(packet.fragment_data(), (1, 0, true));
+        assert_eq!(packet.fragment_body_len(), 8);
+    }
+
+    // `process_fragment`/`reassemble_packet` are already generic over
+    // `FragmentContext<I>` (just `TimerContext<FragmentCacheKey<I::Addr>> +
+    // StateContext<(), IpLayerFragmentCache<..>>`) rather than the full
+    // `EventDispatcher`, so a mock only needs to provide those two pieces.
+    // `crate::context::testutil::DummyContext` already provides a
+    // `TimerContext` (and the `InstantContext` it requires) for free; we only
+    // need to hook up the fragment cache as its state.
+
+    // Generic over the fragment cache's hasher `H` (defaulting to the
+    // production `RandomState`) so the same mock can be reused to confirm
+    // that reassembly works the same way when a non-default hasher is
+    // plugged in; see `test_process_and_reassemble_fragment_with_custom_hasher`.
+    struct DummyFragmentContextState<I: Ip, H = RandomState> {
+        cache: IpLayerFragmentCache<I, DummyInstant, H>,
+    }
+
+    impl<I: Ip, H: BuildHasher + Default> Default for DummyFragmentContextState<I, H> {
+        fn default() -> DummyFragmentContextState<I, H> {
+            DummyFragmentContextState { cache: IpLayerFragmentCache::new() }
+        }
+    }
+
+    type DummyContext<I, H = RandomState> = crate::context::testutil::DummyContext<
+        DummyFragmentContextState<I, H>,
+        FragmentCacheKey<<I as Ip>::Addr>,
+    >;
+
+    impl<I: Ip, H: BuildHasher> AsRef<IpLayerFragmentCache<I, DummyInstant, H>>
+        for DummyContext<I, H>
+    {
+        fn as_ref(&self) -> &IpLayerFragmentCache<I, DummyInstant, H> {
+            &self.get_ref().cache
+        }
+    }
+
+    impl<I: Ip, H: BuildHasher> AsMut<IpLayerFragmentCache<I, DummyInstant, H>>
+        for DummyContext<I, H>
+    {
+        fn as_mut(&mut self) -> &mut IpLayerFragmentCache<I, DummyInstant, H> {
+            &mut self.get_mut().cache
+        }
+    }
+
+    #[test]
+    fn test_process_and_reassemble_fragment_with_minimal_mock_context() {
+        let mut ctx = DummyContext::<Ipv4>::default();
+
+        let mut first = get_ipv4_builder();
+        first.id(1);
+        first.fragment_offset(0);
+        first.mf_flag(true);
+        let mut buffer = Buf::new(vec![0; FRAGMENT_BLOCK_SIZE as usize], ..)
+            .encapsulate(first)
+            .serialize_vec_outer()
+            .unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        let mut last = get_ipv4_builder();
+        last.id(1);
+        last.fragment_offset(1);
+        last.mf_flag(false);
+        let mut buffer = Buf::new(vec![1; FRAGMENT_BLOCK_SIZE as usize], ..)
+            .encapsulate(last)
+            .serialize_vec_outer()
+            .unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let (key, packet_len) = assert_frag_proc_state_ready!(
+            process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            1u16,
+            (FRAGMENT_BLOCK_SIZE as usize) * 2 + 20
+        );
+
+        let mut reassembly_buffer: Vec<u8> = vec![0; packet_len];
+        let mut reassembly_buffer = &mut reassembly_buffer[..];
+        let (packet, _) =
+            reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut ctx, &key, &mut reassembly_buffer)
+                .unwrap();
+        assert_eq!(packet.body(), &[0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1][..]);
+    }
+
+    #[test]
+    fn test_process_and_reassemble_fragment_with_custom_hasher() {
+        // A non-default `BuildHasher`, to confirm that `IpLayerFragmentCache`
+        // works the same whether it's keyed with the default `RandomState`
+        // or with a hasher plugged in by the caller.
+        type CustomHasher = BuildHasherDefault<DefaultHasher>;
+
+        let mut ctx = DummyContext::<Ipv4, CustomHasher>::default();
+        ctx.get_mut().cache = IpLayerFragmentCache::with_hasher(CustomHasher::default());
+
+        let mut first = get_ipv4_builder();
+        first.id(1);
+        first.fragment_offset(0);
+        first.mf_flag(true);
+        let mut buffer = Buf::new(vec![0; FRAGMENT_BLOCK_SIZE as usize], ..)
+            .encapsulate(first)
+            .serialize_vec_outer()
+            .unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        assert_frag_proc_state_need_more!(process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet));
+
+        let mut last = get_ipv4_builder();
+        last.id(1);
+        last.fragment_offset(1);
+        last.mf_flag(false);
+        let mut buffer = Buf::new(vec![1; FRAGMENT_BLOCK_SIZE as usize], ..)
+            .encapsulate(last)
+            .serialize_vec_outer()
+            .unwrap();
+        let packet = buffer.parse::<Ipv4Packet<_>>().unwrap();
+        let (key, packet_len) = assert_frag_proc_state_ready!(
+            process_fragment::<Ipv4, _, &[u8]>(&mut ctx, packet),
+            DUMMY_CONFIG_V4.remote_ip,
+            DUMMY_CONFIG_V4.local_ip,
+            1u16,
+            (FRAGMENT_BLOCK_SIZE as usize) * 2 + 20
+        );
+
+        let mut reassembly_buffer: Vec<u8> = vec![0; packet_len];
+        let mut reassembly_buffer = &mut reassembly_buffer[..];
+        let (packet, _) =
+            reassemble_packet::<Ipv4, _, &mut [u8], _>(&mut ctx, &key, &mut reassembly_buffer)
+                .unwrap();
+        assert_eq!(packet.body(), &[0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1][..]);
+    }
 }
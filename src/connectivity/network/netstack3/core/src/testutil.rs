@@ -4,6 +4,7 @@
 
 //! Testing-related utilities.
 
+use std::cell::RefCell;
 use std::collections::{BinaryHeap, HashMap};
 use std::fmt::{self, Debug, Formatter};
 use std::hash::Hash;
@@ -24,6 +25,7 @@ use crate::device::ethernet::EtherType;
 use crate::device::{DeviceId, DeviceLayerEventDispatcher};
 use crate::error::{IpParseResult, ParseError, ParseResult};
 use crate::ip::icmp::{IcmpConnId, IcmpEventDispatcher};
+use crate::ip::path_mtu::PmtuEventDispatcher;
 use crate::ip::{IpExtByteSlice, IpLayerEventDispatcher, IpProto, IPV6_MIN_MTU};
 use crate::transport::tcp::TcpOption;
 use crate::transport::udp::UdpEventDispatcher;
@@ -135,6 +137,13 @@ impl TestCounters {
     }
 }
 
+thread_local! {
+    /// Log messages captured on the current thread since the last call to
+    /// [`take_captured_logs`], for tests that want to assert on specific messages having been
+    /// logged rather than just eyeballing stdout.
+    static CAPTURED_LOGS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
 /// log::Log implementation that uses stdout.
 ///
 /// Useful when debugging tests.
@@ -146,7 +155,9 @@ impl log::Log for Logger {
     }
 
     fn log(&self, record: &log::Record) {
-        println!("{}", record.args())
+        let message = format!("{}", record.args());
+        println!("{}", message);
+        CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(message));
     }
 
     fn flush(&self) {}
@@ -170,6 +181,14 @@ pub(crate) fn set_logger_for_test() {
     })
 }
 
+/// Returns the log messages captured on the current thread since the last call to
+/// `take_captured_logs`, clearing the capture buffer.
+///
+/// [`set_logger_for_test`] must have been called first.
+pub(crate) fn take_captured_logs() -> Vec<String> {
+    CAPTURED_LOGS.with(|logs| logs.replace(Vec::new()))
+}
+
 /// Skip current time forward to trigger the next timer event.
 ///
 /// Returns true if a timer was triggered, false if there were no timers waiting
@@ -843,6 +862,8 @@ pub(crate) struct DummyEventDispatcher {
     current_time: DummyInstant,
     rng: FakeCryptoRng<XorShiftRng>,
     icmp_replies: HashMap<IcmpConnId, Vec<(u16, Vec<u8>)>>,
+    new_pmtu_events: Vec<(IpAddr, IpAddr, u32)>,
+    pmtu_probe_events: Vec<(IpAddr, IpAddr, u32)>,
 }
 
 impl Default for DummyEventDispatcher {
@@ -853,6 +874,8 @@ impl Default for DummyEventDispatcher {
             current_time: Default::default(),
             rng: FakeCryptoRng(new_rng(0)),
             icmp_replies: Default::default(),
+            new_pmtu_events: Default::default(),
+            pmtu_probe_events: Default::default(),
         }
     }
 }
@@ -891,6 +914,16 @@ impl DummyEventDispatcher {
     pub(crate) fn take_icmp_replies(&mut self, conn: IcmpConnId) -> Vec<(u16, Vec<u8>)> {
         self.icmp_replies.remove(&conn).unwrap_or_else(Vec::default)
     }
+
+    /// Takes all the recorded new-PMTU events.
+    pub(crate) fn take_new_pmtu_events(&mut self) -> Vec<(IpAddr, IpAddr, u32)> {
+        std::mem::replace(&mut self.new_pmtu_events, Vec::default())
+    }
+
+    /// Takes all the recorded PMTU-probe events.
+    pub(crate) fn take_pmtu_probe_events(&mut self) -> Vec<(IpAddr, IpAddr, u32)> {
+        std::mem::replace(&mut self.pmtu_probe_events, Vec::default())
+    }
 }
 
 impl UdpEventDispatcher for DummyEventDispatcher {}
@@ -904,6 +937,16 @@ impl IcmpEventDispatcher for DummyEventDispatcher {
     }
 }
 
+impl PmtuEventDispatcher for DummyEventDispatcher {
+    fn on_new_pmtu(&mut self, src_ip: IpAddr, dst_ip: IpAddr, pmtu: u32) {
+        self.new_pmtu_events.push((src_ip, dst_ip, pmtu));
+    }
+
+    fn on_pmtu_probe(&mut self, src_ip: IpAddr, dst_ip: IpAddr, pmtu: u32) {
+        self.pmtu_probe_events.push((src_ip, dst_ip, pmtu));
+    }
+}
+
 impl IpLayerEventDispatcher for DummyEventDispatcher {}
 
 impl<B: BufferMut> DeviceLayerEventDispatcher<B> for DummyEventDispatcher {
@@ -314,6 +314,10 @@ impl<B: ByteSlice> FragmentablePacket for Ipv4Packet<B> {
     fn fragment_data(&self) -> (u32, u16, bool) {
         (u32::from(self.id()), self.fragment_offset(), self.mf_flag())
     }
+
+    fn fragment_body_len(&self) -> usize {
+        self.body().len()
+    }
 }
 
 impl<B> Ipv4Packet<B>
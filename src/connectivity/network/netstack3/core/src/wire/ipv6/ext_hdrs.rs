@@ -4,10 +4,12 @@
 
 //! Parsing of IPv6 Extension Headers.
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::marker::PhantomData;
 
 use byteorder::{ByteOrder, NetworkEndian};
+use net_types::ip::Ipv6Addr;
 use packet::BufferView;
 
 use crate::ip::{IpProto, Ipv6ExtHdrType};
@@ -31,6 +33,38 @@ impl<'a> Ipv6ExtensionHeader<'a> {
     pub(crate) fn data(&self) -> &Ipv6ExtensionHeaderData<'a> {
         &self.data
     }
+
+    /// Creates an owned, borrow-free snapshot of this extension header.
+    ///
+    /// Unlike `Ipv6ExtensionHeader` itself, the returned summary does not
+    /// borrow from the packet buffer, so it can outlive the buffer and be
+    /// collected into a `Vec` for callers (e.g. diagnostics tooling) that
+    /// want to serialize a full extension header chain.
+    pub(crate) fn summarize(&self) -> Ipv6ExtensionHeaderSummary {
+        let data = match &self.data {
+            Ipv6ExtensionHeaderData::HopByHopOptions { .. } => {
+                Ipv6ExtensionHeaderDataSummary::HopByHopOptions
+            }
+            Ipv6ExtensionHeaderData::Routing { routing_data } => {
+                Ipv6ExtensionHeaderDataSummary::Routing {
+                    routing_type: routing_data.routing_type(),
+                    segments_left: routing_data.segments_left(),
+                }
+            }
+            Ipv6ExtensionHeaderData::Fragment { fragment_data } => {
+                Ipv6ExtensionHeaderDataSummary::Fragment {
+                    fragment_offset: fragment_data.fragment_offset(),
+                    m_flag: fragment_data.m_flag(),
+                    identification: fragment_data.identification(),
+                }
+            }
+            Ipv6ExtensionHeaderData::DestinationOptions { .. } => {
+                Ipv6ExtensionHeaderDataSummary::DestinationOptions
+            }
+        };
+
+        Ipv6ExtensionHeaderSummary { next_header: self.next_header, data }
+    }
 }
 
 /// The data associated with an IPv6 Extension Header.
@@ -42,6 +76,53 @@ pub(crate) enum Ipv6ExtensionHeaderData<'a> {
     DestinationOptions { options: Records<&'a [u8], DestinationOptionsImpl> },
 }
 
+impl<'a> Ipv6ExtensionHeaderData<'a> {
+    /// Returns whether this extension header may change en route to the
+    /// packet's destination.
+    ///
+    /// This is needed when computing or verifying an Authentication header's
+    /// authenticating value, which per RFC 8200 section 4.2 must treat
+    /// mutable data as zeroes. A Routing header's `segments_left` field is
+    /// itself mutated by every intermediate node that processes it (see
+    /// [`RoutingData::process_at_node`]), so Routing headers are always
+    /// mutable; a Hop-by-Hop or Destination Options header is mutable if and
+    /// only if it holds at least one option whose own `mutable` bit is set.
+    /// Fragment headers are never mutable.
+    pub(crate) fn is_mutable_en_route(&self) -> bool {
+        match self {
+            Ipv6ExtensionHeaderData::Routing { .. } => true,
+            Ipv6ExtensionHeaderData::HopByHopOptions { options } => {
+                options.iter().any(|o| o.mutable)
+            }
+            Ipv6ExtensionHeaderData::DestinationOptions { options } => {
+                options.iter().any(|o| o.mutable)
+            }
+            Ipv6ExtensionHeaderData::Fragment { .. } => false,
+        }
+    }
+}
+
+/// An owned snapshot of an [`Ipv6ExtensionHeader`].
+///
+/// `Ipv6ExtensionHeaderSummary` holds no references into the packet buffer,
+/// so a full extension header chain can be collected into a `Vec` of these
+/// and handed to a caller that wants to serialize it (e.g. to JSON) without
+/// being tied to the lifetime of the parsed packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Ipv6ExtensionHeaderSummary {
+    pub(crate) next_header: u8,
+    pub(crate) data: Ipv6ExtensionHeaderDataSummary,
+}
+
+/// An owned snapshot of an [`Ipv6ExtensionHeaderData`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Ipv6ExtensionHeaderDataSummary {
+    HopByHopOptions,
+    Routing { routing_type: u8, segments_left: u8 },
+    Fragment { fragment_offset: u16, m_flag: bool, identification: u32 },
+    DestinationOptions,
+}
+
 //
 // Records parsing for IPv6 Extension Header
 //
@@ -58,20 +139,54 @@ pub(crate) enum Ipv6ExtensionHeaderParsingError {
         pointer: u32,
         must_send_icmp: bool,
         header_len: usize,
+        // The type of extension header being parsed when the error was
+        // found, if known. This is `None` only for errors raised before any
+        // particular extension header type could be determined.
+        header_type: Option<Ipv6ExtHdrType>,
     },
     UnrecognizedNextHeader {
         pointer: u32,
         must_send_icmp: bool,
         header_len: usize,
+        header_type: Option<Ipv6ExtHdrType>,
     },
     UnrecognizedOption {
         pointer: u32,
         must_send_icmp: bool,
         header_len: usize,
         action: ExtensionHeaderOptionAction,
+        // Any other unrecognized options found in the same header after this
+        // one, collected for diagnostics by headers that opt into
+        // aggregation (currently Destination Options only; see
+        // `ExtensionHeaderOptionContext::new_collect_unrecognized`). `action`
+        // above always reflects the first unrecognized option found, which
+        // is what actually governs how the packet is handled; this list does
+        // not change that.
+        additional: Vec<(u32, ExtensionHeaderOptionAction)>,
+        header_type: Option<Ipv6ExtHdrType>,
+    },
+    // A Routing extension header's address list (the type-specific data following
+    // its 4-byte reserved field) was shorter than `hdr_ext_len` promised.
+    // `expected` and `got` are both counted in bytes of that address list, i.e.
+    // excluding the reserved field, so that a shortfall there can be told apart
+    // from a Routing header that didn't even have its reserved field.
+    TruncatedRoutingAddresses {
+        expected: usize,
+        got: usize,
+        header_type: Option<Ipv6ExtHdrType>,
+    },
+    // The Next Header a context was constructed to expect extension headers
+    // to follow turned out to already be an upper-layer protocol (e.g. TCP).
+    // Returned by `Ipv6ExtensionHeaderParsingContext::new_expecting_ext_header`.
+    NotAnExtensionHeader {
+        next_header: u8,
+    },
+    BufferExhausted {
+        header_type: Option<Ipv6ExtHdrType>,
+    },
+    MalformedData {
+        header_type: Option<Ipv6ExtHdrType>,
     },
-    BufferExhausted,
-    MalformedData,
 }
 
 /// Context that gets passed around when parsing IPv6 Extension Headers.
@@ -90,6 +205,18 @@ pub(crate) struct Ipv6ExtensionHeaderParsingContext {
 
     // Byte count of successfully parsed extension headers.
     pub(super) bytes_parsed: usize,
+
+    // Whether reserved fields (e.g. the Fragment header's reserved byte and
+    // reserved bits) must be zero. When `false` (the default), non-zero
+    // reserved fields are silently ignored, as recommended by RFC 8200's
+    // general guidance that reserved fields are "ignored on receipt".
+    strict: bool,
+
+    // The fixed header's Payload Length, defaulting to 0. Threaded through
+    // to the Hop-By-Hop options parser so a Jumbo Payload option (RFC 2675)
+    // can be cross-checked against it: a jumbogram requires the fixed
+    // header's Payload Length to be zero. See `with_payload_len`.
+    pub(super) payload_len: u16,
 }
 
 impl Ipv6ExtensionHeaderParsingContext {
@@ -99,7 +226,55 @@ impl Ipv6ExtensionHeaderParsingContext {
             headers_parsed: 0,
             next_header,
             bytes_parsed: 0,
+            strict: false,
+            payload_len: 0,
+        }
+    }
+
+    /// Creates a new context that rejects extension headers with non-zero
+    /// reserved fields instead of silently ignoring them.
+    pub(crate) fn new_strict(next_header: u8) -> Ipv6ExtensionHeaderParsingContext {
+        Ipv6ExtensionHeaderParsingContext { strict: true, ..Self::new(next_header) }
+    }
+
+    /// Sets the fixed header's Payload Length, for Jumbo Payload option
+    /// (RFC 2675) cross-checking. See `payload_len`.
+    pub(crate) fn with_payload_len(self, payload_len: u16) -> Ipv6ExtensionHeaderParsingContext {
+        Ipv6ExtensionHeaderParsingContext { payload_len, ..self }
+    }
+
+    /// Creates a new context for a Next Header that is expected to be
+    /// followed by at least one extension header, returning
+    /// `NotAnExtensionHeader` immediately if `next_header` is already an
+    /// upper-layer protocol.
+    ///
+    /// A caller that mistakenly seeds `new` with an upper-layer protocol
+    /// (e.g. TCP) gets back a context that parses zero extension headers
+    /// (`parse_with_context` returns `Ok(None)` right away), which can mask
+    /// that mistake. Callers that know they should only ever be given a
+    /// Next Header with extension headers following it can use this
+    /// constructor instead to catch that bug eagerly.
+    pub(crate) fn new_expecting_ext_header(
+        next_header: u8,
+    ) -> Result<Ipv6ExtensionHeaderParsingContext, Ipv6ExtensionHeaderParsingError> {
+        if is_valid_next_header_upper_layer(next_header) {
+            return Err(Ipv6ExtensionHeaderParsingError::NotAnExtensionHeader { next_header });
         }
+        Ok(Self::new(next_header))
+    }
+}
+
+/// Does the fixed IPv6 header's Next Header field, `first_next_header`,
+/// indicate the presence of at least one extension header?
+///
+/// If this returns `false`, `first_next_header` is already an upper-layer
+/// protocol number, so callers can skip creating an
+/// [`Ipv6ExtensionHeaderParsingContext`] and invoking the `Records` parsing
+/// machinery entirely, going straight to the upper-layer payload.
+pub(crate) fn has_ext_headers(first_next_header: u8) -> bool {
+    match Ipv6ExtHdrType::from(first_next_header) {
+        Ipv6ExtHdrType::Other(_) => false,
+        _ => true,
     }
 }
 
@@ -133,10 +308,11 @@ impl Ipv6ExtensionHeaderImpl {
     fn get_next_hdr_and_len<'a, BV: BufferView<&'a [u8]>>(
         data: &mut BV,
         context: &Ipv6ExtensionHeaderParsingContext,
+        header_type: Ipv6ExtHdrType,
     ) -> Result<(u8, u8), Ipv6ExtensionHeaderParsingError> {
-        let next_header = data
-            .take_byte_front()
-            .ok_or_else(|| Ipv6ExtensionHeaderParsingError::BufferExhausted)?;
+        let next_header = data.take_byte_front().ok_or(
+            Ipv6ExtensionHeaderParsingError::BufferExhausted { header_type: Some(header_type) },
+        )?;
 
         // Make sure we recognize the next header.
         // When parsing headers, if we encounter a next header value we don't
@@ -147,25 +323,26 @@ impl Ipv6ExtensionHeaderImpl {
                 pointer: context.bytes_parsed as u32,
                 must_send_icmp: false,
                 header_len: context.bytes_parsed,
+                header_type: Some(header_type),
             });
         }
 
-        let hdr_ext_len = data
-            .take_byte_front()
-            .ok_or_else(|| Ipv6ExtensionHeaderParsingError::BufferExhausted)?;
+        let hdr_ext_len = data.take_byte_front().ok_or(
+            Ipv6ExtensionHeaderParsingError::BufferExhausted { header_type: Some(header_type) },
+        )?;
 
         Ok((next_header, hdr_ext_len))
     }
 
     /// Parse Hop By Hop Options Extension Header.
-    // TODO(ghanan): Look into implementing the IPv6 Jumbo Payload option
-    //               (https://tools.ietf.org/html/rfc2675) and the router
-    //               alert option (https://tools.ietf.org/html/rfc2711).
+    // TODO(ghanan): Look into implementing the router alert option
+    //               (https://tools.ietf.org/html/rfc2711).
     fn parse_hop_by_hop_options<'a, BV: BufferView<&'a [u8]>>(
         data: &mut BV,
         context: &mut Ipv6ExtensionHeaderParsingContext,
     ) -> Result<Option<Option<Ipv6ExtensionHeader<'a>>>, Ipv6ExtensionHeaderParsingError> {
-        let (next_header, hdr_ext_len) = Self::get_next_hdr_and_len(data, context)?;
+        let header_type = Ipv6ExtHdrType::HopByHopOptions;
+        let (next_header, hdr_ext_len) = Self::get_next_hdr_and_len(data, context, header_type)?;
 
         // As per RFC 8200 section 4.3, Hdr Ext Len is the length of this extension
         // header in  8-octect units, not including the first 8 octets (where 2 of
@@ -174,11 +351,13 @@ impl Ipv6ExtensionHeaderImpl {
         // we have (Hdr Ext Len) * 8 + 6 bytes bytes in `data`.
         let expected_len = (hdr_ext_len as usize) * 8 + 6;
 
-        let options = data
-            .take_front(expected_len)
-            .ok_or_else(|| Ipv6ExtensionHeaderParsingError::BufferExhausted)?;
+        let options = data.take_front(expected_len).ok_or(
+            Ipv6ExtensionHeaderParsingError::BufferExhausted { header_type: Some(header_type) },
+        )?;
 
-        let options_context = ExtensionHeaderOptionContext::new();
+        let options_context = ExtensionHeaderOptionContext::with_specific_context(
+            HopByHopOptionContext { payload_len: context.payload_len, ..Default::default() },
+        );
         let options = Records::parse_with_context(options, options_context).map_err(|e| {
             // We know the below `try_from` call will not result in a `None` value because
             // the maximum size of an IPv6 packet's payload (extension headers + body) is
@@ -194,9 +373,27 @@ impl Ipv6ExtensionHeaderImpl {
                 u32::try_from(context.bytes_parsed + 2).unwrap(),
                 context.bytes_parsed,
                 e,
+                header_type,
             )
         })?;
 
+        // RFC 2675 requires a jumbogram's fixed header Payload Length to be
+        // zero; a Jumbo Payload option alongside a non-zero Payload Length
+        // is a contradiction between the two, so reject it the same way we
+        // reject other erroneous header fields. `options.context()` is the
+        // context as it was before parsing, so walk the options once to get
+        // the state that parsing them leaves behind.
+        let mut options_iter = options.iter();
+        while options_iter.next().is_some() {}
+        if options_iter.context().specific_context().jumbo_payload_inconsistent {
+            return Err(Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+                pointer: u32::try_from(context.bytes_parsed + 2).unwrap(),
+                must_send_icmp: true,
+                header_len: context.bytes_parsed,
+                header_type: Some(header_type),
+            });
+        }
+
         // Update context
         context.next_header = next_header;
         context.headers_parsed += 1;
@@ -215,9 +412,11 @@ impl Ipv6ExtensionHeaderImpl {
     ) -> Result<Option<Option<Ipv6ExtensionHeader<'a>>>, Ipv6ExtensionHeaderParsingError> {
         // All routing extension headers (regardless of type) will have
         // 4 bytes worth of data we need to look at.
-        let (next_header, hdr_ext_len) = Self::get_next_hdr_and_len(data, context)?;
-        let routing_data =
-            data.take_front(2).ok_or_else(|| Ipv6ExtensionHeaderParsingError::BufferExhausted)?;;
+        let header_type = Ipv6ExtHdrType::Routing;
+        let (next_header, hdr_ext_len) = Self::get_next_hdr_and_len(data, context, header_type)?;
+        let routing_data = data.take_front(2).ok_or(
+            Ipv6ExtensionHeaderParsingError::BufferExhausted { header_type: Some(header_type) },
+        )?;
         let routing_type = routing_data[0];
         let segments_left = routing_data[1];
 
@@ -235,10 +434,28 @@ impl Ipv6ExtensionHeaderImpl {
         if segments_left == 0 {
             // Take the next 4 and 8 * `hdr_ext_len` bytes to exhaust this extension header's
             // data so that that `data` will be at the front of the next header when this
-            // function returns.
-            let expected_len = (hdr_ext_len as usize) * 8 + 4;
-            data.take_front(expected_len)
-                .ok_or_else(|| Ipv6ExtensionHeaderParsingError::BufferExhausted)?;
+            // function returns. The first 4 of those bytes are the generic Routing header's
+            // reserved field; the remaining `hdr_ext_len * 8` bytes are the type-specific
+            // data (e.g. the address list for routing type 0), so we check them separately to
+            // be able to tell a missing reserved field apart from a truncated address list.
+            if data.len() < 4 {
+                return Err(Ipv6ExtensionHeaderParsingError::BufferExhausted {
+                    header_type: Some(header_type),
+                });
+            }
+
+            let expected_addresses_len = (hdr_ext_len as usize) * 8;
+            let got_addresses_len = data.len() - 4;
+            if got_addresses_len < expected_addresses_len {
+                return Err(Ipv6ExtensionHeaderParsingError::TruncatedRoutingAddresses {
+                    expected: expected_addresses_len,
+                    got: got_addresses_len,
+                    header_type: Some(header_type),
+                });
+            }
+
+            let expected_len = expected_addresses_len + 4;
+            data.take_front(expected_len).unwrap();
 
             // Update context
             context.next_header = next_header;
@@ -254,6 +471,7 @@ impl Ipv6ExtensionHeaderImpl {
                 pointer: (context.bytes_parsed as u32) + 2,
                 must_send_icmp: true,
                 header_len: context.bytes_parsed,
+                header_type: Some(header_type),
             })
         }
     }
@@ -268,14 +486,40 @@ impl Ipv6ExtensionHeaderImpl {
         // we are guaranteed that all `take_front` calls done by this
         // method will succeed since we will never attempt to call `take_front`
         // with more than 8 bytes total.
+        let header_type = Ipv6ExtHdrType::Fragment;
+
         if data.len() < 8 {
-            return Err(Ipv6ExtensionHeaderParsingError::BufferExhausted);
+            return Err(Ipv6ExtensionHeaderParsingError::BufferExhausted {
+                header_type: Some(header_type),
+            });
         }
 
         // For Fragment headers, we do not actually have a HdrExtLen field. Instead,
         // the second byte in the header (where HdrExtLen would normally exist), is
-        // a reserved field, so we can simply ignore it for now.
-        let (next_header, _) = Self::get_next_hdr_and_len(data, context)?;
+        // a reserved field. In strict mode, we require it to be zero.
+        let (next_header, reserved) = Self::get_next_hdr_and_len(data, context, header_type)?;
+
+        if context.strict && reserved != 0 {
+            return Err(Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+                pointer: (context.bytes_parsed + 1) as u32,
+                must_send_icmp: false,
+                header_len: context.bytes_parsed,
+                header_type: Some(header_type),
+            });
+        }
+
+        let fragment_data_bytes = data.take_front(6).unwrap();
+
+        // The 2 bits directly preceding the M flag in the Fragment Offset
+        // field are reserved. In strict mode, we require them to be zero.
+        if context.strict && (fragment_data_bytes[1] & 0x06) != 0 {
+            return Err(Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+                pointer: (context.bytes_parsed + 3) as u32,
+                must_send_icmp: false,
+                header_len: context.bytes_parsed,
+                header_type: Some(header_type),
+            });
+        }
 
         // Update context
         context.next_header = next_header;
@@ -285,7 +529,7 @@ impl Ipv6ExtensionHeaderImpl {
         Ok(Some(Some(Ipv6ExtensionHeader {
             next_header,
             data: Ipv6ExtensionHeaderData::Fragment {
-                fragment_data: FragmentData { bytes: data.take_front(6).unwrap() },
+                fragment_data: FragmentData { bytes: fragment_data_bytes },
             },
         })))
     }
@@ -295,18 +539,19 @@ impl Ipv6ExtensionHeaderImpl {
         data: &mut BV,
         context: &mut Ipv6ExtensionHeaderParsingContext,
     ) -> Result<Option<Option<Ipv6ExtensionHeader<'a>>>, Ipv6ExtensionHeaderParsingError> {
-        let (next_header, hdr_ext_len) = Self::get_next_hdr_and_len(data, context)?;
+        let header_type = Ipv6ExtHdrType::DestinationOptions;
+        let (next_header, hdr_ext_len) = Self::get_next_hdr_and_len(data, context, header_type)?;
 
         // As per RFC 8200 section 4.6, Hdr Ext Len is the length of this extension
         // header in  8-octet units, not including the first 8 octets (where 2 of
         // them are the Next Header and the Hdr Ext Len fields).
         let expected_len = (hdr_ext_len as usize) * 8 + 6;
 
-        let options = data
-            .take_front(expected_len)
-            .ok_or_else(|| Ipv6ExtensionHeaderParsingError::BufferExhausted)?;
+        let options = data.take_front(expected_len).ok_or(
+            Ipv6ExtensionHeaderParsingError::BufferExhausted { header_type: Some(header_type) },
+        )?;
 
-        let options_context = ExtensionHeaderOptionContext::new();
+        let options_context = ExtensionHeaderOptionContext::new_collect_unrecognized();
         let options = Records::parse_with_context(options, options_context).map_err(|e| {
             // We know the below `try_from` call will not result in a `None` value because
             // the maximum size of an IPv6 packet's payload (extension headers + body) is
@@ -322,9 +567,27 @@ impl Ipv6ExtensionHeaderImpl {
                 u32::try_from(context.bytes_parsed + 2).unwrap(),
                 context.bytes_parsed,
                 e,
+                header_type,
             )
         })?;
 
+        // Unlike Hop-By-Hop Options, Destination Options collects every unrecognized
+        // option in the header (up to a cap) instead of aborting at the first one, so
+        // that they can all be reported together for diagnostics. The first one found
+        // is still what determines how the packet is actually handled.
+        // `options.context()` is the context as it was before parsing, so walk
+        // the options once to collect them again.
+        let mut options_iter = options.iter();
+        while options_iter.next().is_some() {}
+        if let Some(err) = collected_unrecognized_options_to_ext_hdr_err(
+            u32::try_from(context.bytes_parsed + 2).unwrap(),
+            context.bytes_parsed,
+            options_iter.context().collected_unrecognized_options(),
+            header_type,
+        ) {
+            return Err(err);
+        }
+
         // Update context
         context.next_header = next_header;
         context.headers_parsed += 1;
@@ -387,7 +650,8 @@ impl<'a> RecordsRawImpl<'a> for Ipv6ExtensionHeaderImpl {
         if is_valid_next_header_upper_layer(context.next_header) {
             Ok(false)
         } else {
-            let (next, skip) = match Ipv6ExtHdrType::from(context.next_header) {
+            let header_type = Ipv6ExtHdrType::from(context.next_header);
+            let (next, skip) = match header_type {
                 Ipv6ExtHdrType::HopByHopOptions
                 | Ipv6ExtHdrType::Routing
                 | Ipv6ExtHdrType::DestinationOptions
@@ -397,15 +661,20 @@ impl<'a> RecordsRawImpl<'a> for Ipv6ExtensionHeaderImpl {
                     // NOTE: we can assume that Other will be parsed
                     //  as such based on the extensibility note in
                     //  RFC 8200 Section-4.8
-                    data.take_front(2)
-                        .map(|x| (x[0], (x[1] as usize) * 8 + 6))
-                        .ok_or(Ipv6ExtensionHeaderParsingError::BufferExhausted)?
+                    data.take_front(2).map(|x| (x[0], (x[1] as usize) * 8 + 6)).ok_or(
+                        Ipv6ExtensionHeaderParsingError::BufferExhausted {
+                            header_type: Some(header_type),
+                        },
+                    )?
                 }
                 Ipv6ExtHdrType::Fragment => {
                     // take next header from first, then skip next 7
                     (
-                        data.take_byte_front()
-                            .ok_or(Ipv6ExtensionHeaderParsingError::BufferExhausted)?,
+                        data.take_byte_front().ok_or(
+                            Ipv6ExtensionHeaderParsingError::BufferExhausted {
+                                header_type: Some(header_type),
+                            },
+                        )?,
                         7,
                     )
                 }
@@ -414,7 +683,9 @@ impl<'a> RecordsRawImpl<'a> for Ipv6ExtensionHeaderImpl {
                     //  an error instead of panicking "unimplemented" to avoid
                     //  having a panic-path that can be remotely triggered.
                     return debug_err!(
-                        Err(Ipv6ExtensionHeaderParsingError::MalformedData),
+                        Err(Ipv6ExtensionHeaderParsingError::MalformedData {
+                            header_type: Some(header_type),
+                        }),
                         "ESP extension header not supported"
                     );
                 }
@@ -422,18 +693,129 @@ impl<'a> RecordsRawImpl<'a> for Ipv6ExtensionHeaderImpl {
                     // take next header and payload len, and skip the next
                     // (payload_len + 2) 32 bit words, minus the 2 octets
                     // already consumed.
-                    data.take_front(2)
-                        .map(|x| (x[0], (x[1] as usize + 2) * 4 - 2))
-                        .ok_or(Ipv6ExtensionHeaderParsingError::BufferExhausted)?
+                    data.take_front(2).map(|x| (x[0], (x[1] as usize + 2) * 4 - 2)).ok_or(
+                        Ipv6ExtensionHeaderParsingError::BufferExhausted {
+                            header_type: Some(header_type),
+                        },
+                    )?
                 }
             };
-            data.take_front(skip).ok_or(Ipv6ExtensionHeaderParsingError::BufferExhausted)?;
+            data.take_front(skip).ok_or(Ipv6ExtensionHeaderParsingError::BufferExhausted {
+                header_type: Some(header_type),
+            })?;
             context.next_header = next;
             Ok(true)
         }
     }
 }
 
+/// Parses a sequence of IPv6 Extension Headers without ever panicking.
+///
+/// Several of the parsing paths above rely on `unwrap`/`assert`/`unreachable`
+/// that are only justified by invariants we believe hold for well-formed
+/// input (e.g. that lengths fit in a `u32`). This entry point is meant to be
+/// driven by a fuzzer over arbitrary `data`/`first_next_header` pairs: any
+/// panic triggered while parsing is caught and reported as an `Err` instead
+/// of aborting the process, so a fuzz harness can assert that parsing never
+/// panics regardless of input.
+pub(crate) fn fuzz_parse_ext_headers(data: &[u8], first_next_header: u8) -> Result<(), String> {
+    std::panic::catch_unwind(|| {
+        let context = Ipv6ExtensionHeaderParsingContext::new(first_next_header);
+        let records = Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(data, context)
+            .map_err(|e| format!("{:?}", e))?;
+        // Iteration is lazy, so force it to completion to exercise any
+        // panics hiding in the per-record parsers as well.
+        for _ in records.iter() {}
+        Ok(())
+    })
+    .unwrap_or_else(|_| Err("extension header parser panicked on malformed input".to_string()))
+}
+
+/// Rewrites the Next Header field of the header at `at_header_index` in `buf`'s IPv6 header
+/// chain to `new_next_header`, returning the value that was previously stored there.
+///
+/// `buf` must hold the full fixed IPv6 header, immediately followed by the chain of extension
+/// headers that the fixed header's Next Header field points to, with no other bytes mixed in.
+/// `at_header_index` of `0` refers to the fixed header's own Next Header field, at
+/// `buf[NEXT_HEADER_OFFSET]`; `at_header_index` of `i`, for `i > 0`, refers to the Next Header
+/// field of the `i`th extension header in the chain (1-indexed), which always lives in the first
+/// byte of that extension header.
+///
+/// This is useful when forwarding a packet whose header chain is being edited in place - for
+/// example, removing a Fragment header once reassembly is done, or decrementing a Routing
+/// header's segments left down to zero - since the header preceding the one removed or consumed
+/// needs to be patched to skip over it.
+///
+/// # Panics
+///
+/// Panics if `buf` does not hold a well-formed IPv6 extension header chain, or if
+/// `at_header_index` is greater than the number of extension headers present in `buf`.
+pub(crate) fn rewrite_next_header(
+    buf: &mut [u8],
+    at_header_index: usize,
+    new_next_header: u8,
+) -> u8 {
+    use super::{IPV6_FIXED_HDR_LEN, NEXT_HEADER_OFFSET};
+
+    if at_header_index == 0 {
+        return std::mem::replace(&mut buf[NEXT_HEADER_OFFSET], new_next_header);
+    }
+
+    let context = Ipv6ExtensionHeaderParsingContext::new(buf[NEXT_HEADER_OFFSET]);
+    let records = Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(
+        &buf[IPV6_FIXED_HDR_LEN..],
+        context,
+    )
+    .expect("rewrite_next_header: malformed IPv6 extension header chain");
+
+    let mut iter = records.iter();
+    let mut header_start = 0;
+    for header_index in 1..at_header_index {
+        iter.next().unwrap_or_else(|| {
+            panic!(
+                "rewrite_next_header: at_header_index {} is past the header at index {}, \
+                 which does not exist",
+                at_header_index, header_index
+            )
+        });
+        header_start = iter.context().bytes_parsed;
+    }
+    iter.next().unwrap_or_else(|| {
+        panic!(
+            "rewrite_next_header: at_header_index {} does not exist in this chain",
+            at_header_index
+        )
+    });
+
+    std::mem::replace(&mut buf[IPV6_FIXED_HDR_LEN + header_start], new_next_header)
+}
+
+/// Parses the chain of IPv6 extension headers that begins at the front of `data`, given that
+/// `first_next_header` is the Next Header value that led to it (i.e. the fixed header's Next
+/// Header field, or the Next Header field of whatever header `data` logically follows), and
+/// splits off the upper-layer payload that follows the chain.
+///
+/// On success, returns the parsed extension headers in order, the upper-layer protocol the
+/// chain resolved to, and the suffix of `data` holding that protocol's payload. This saves a
+/// caller that already has `first_next_header` and a payload in hand - for example, when
+/// handling the packet excerpt carried in an ICMPv6 error - from separately running the
+/// extension header `Records` parse and then re-deriving where the transport payload starts
+/// from `bytes_parsed` itself.
+pub(crate) fn parse_ipv6_payload<'a>(
+    first_next_header: u8,
+    data: &'a [u8],
+) -> Result<(Vec<Ipv6ExtensionHeader<'a>>, u8, &'a [u8]), Ipv6ExtensionHeaderParsingError> {
+    let mut context = Ipv6ExtensionHeaderParsingContext::new(first_next_header);
+    let records =
+        Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_mut_context(data, &mut context)?;
+
+    let ext_hdrs = records.iter().collect();
+    let upper_layer_proto = context.next_header;
+    let upper_layer_payload = &data[context.bytes_parsed..];
+
+    Ok((ext_hdrs, upper_layer_proto, upper_layer_payload))
+}
+
 //
 // Hop-By-Hop Options
 //
@@ -445,14 +827,43 @@ type HopByHopOptionsImpl = ExtensionHeaderOptionImpl<HopByHopOptionDataImpl>;
 #[derive(Debug)]
 pub(crate) enum HopByHopOptionData<'a> {
     Unrecognized { kind: u8, len: u8, data: &'a [u8] },
+
+    /// The Jumbo Payload option, defined by RFC 2675. `0` is the jumbogram's
+    /// actual payload length, in bytes.
+    JumboPayload(u32),
 }
 
+/// Hop-By-Hop options parsing context.
+///
+/// Carries the fixed header's Payload Length so a Jumbo Payload option can
+/// be cross-checked against it; see `jumbo_payload_inconsistent`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HopByHopOptionContext {
+    payload_len: u16,
+
+    // Set by `HopByHopOptionDataImpl::parse_option` upon finding a Jumbo
+    // Payload option alongside a non-zero `payload_len`, which RFC 2675
+    // forbids (a jumbogram's fixed header Payload Length must be zero).
+    // Checked by `Ipv6ExtensionHeaderImpl::parse_hop_by_hop_options` once
+    // parsing finishes.
+    jumbo_payload_inconsistent: bool,
+}
+
+impl RecordsContext for HopByHopOptionContext {}
+
 /// Impl for Hop By Hop Options parsing.
 #[derive(Debug)]
 pub(crate) struct HopByHopOptionDataImpl;
 
+impl HopByHopOptionDataImpl {
+    // The Jumbo Payload option's kind, as registered by RFC 2675 (option
+    // type 0xC2; the low-order 5 bits used here exclude the action and
+    // mutable bits already stripped off by `ExtensionHeaderOptionImpl`).
+    const JUMBO_PAYLOAD_KIND: u8 = 0xC2 & 0x1F;
+}
+
 impl ExtensionHeaderOptionDataImplLayout for HopByHopOptionDataImpl {
-    type Context = ();
+    type Context = HopByHopOptionContext;
 }
 
 impl<'a> ExtensionHeaderOptionDataImpl<'a> for HopByHopOptionDataImpl {
@@ -464,6 +875,14 @@ impl<'a> ExtensionHeaderOptionDataImpl<'a> for HopByHopOptionDataImpl {
         context: &mut Self::Context,
         allow_unrecognized: bool,
     ) -> Option<Self::OptionData> {
+        if kind == Self::JUMBO_PAYLOAD_KIND && data.len() == 4 {
+            let jumbo_payload_len = NetworkEndian::read_u32(data);
+            if context.payload_len != 0 {
+                context.jumbo_payload_inconsistent = true;
+            }
+            return Some(HopByHopOptionData::JumboPayload(jumbo_payload_len));
+        }
+
         if allow_unrecognized {
             Some(HopByHopOptionData::Unrecognized { kind, len: data.len() as u8, data })
         } else {
@@ -497,6 +916,51 @@ impl<'a> RoutingData<'a> {
     pub(crate) fn type_specific_data(&self) -> &RoutingTypeSpecificData<'a> {
         &self.type_specific_data
     }
+
+    /// Collects any addresses carried by this routing header into owned,
+    /// buffer-independent values so that they can outlive the buffer this
+    /// `RoutingData` borrows from.
+    ///
+    /// Routing Type 0, the only routing type that historically carried a
+    /// list of intermediate addresses, was deprecated by RFC 5095 for
+    /// security reasons and is not parsed by this stack (see
+    /// `Ipv6ExtensionHeaderImpl::parse_routing`), so there is currently no
+    /// recognized routing type from which to collect addresses.
+    pub(crate) fn collect_addresses(&self) -> Vec<Ipv6Addr> {
+        match &self.type_specific_data {
+            RoutingTypeSpecificData::Other(_) => Vec::new(),
+        }
+    }
+
+    /// Computes the result of processing this Routing header at an intermediate node: the
+    /// address that would become the packet's new destination, or `None` if `segments_left`
+    /// is already `0`.
+    ///
+    /// Per RFC 8200 section 4.4, a node forwarding a packet with a non-exhausted Routing
+    /// header decrements `segments_left` and swaps in the next address from the header's
+    /// route; once `segments_left` reaches `0` the header is exhausted and the packet is
+    /// delivered as addressed. As documented on [`RoutingData::collect_addresses`], this
+    /// stack does not parse any routing type that carries such a list of addresses (Routing
+    /// Type 0, the one historical type that did, was deprecated by RFC 5095 for security
+    /// reasons and is rejected during parsing), so there is currently never an address to
+    /// swap in, and this method always returns `None`. It is provided so that a caller
+    /// walking a header chain does not need to special-case routing type support itself, and
+    /// so it has somewhere to plug in once this stack recognizes a routing type worth acting
+    /// on.
+    ///
+    /// This does not mutate `segments_left` on the wire - like the rest of `RoutingData`,
+    /// `self` borrows immutably from the packet buffer. A caller that needs to reflect the
+    /// decrement back into the buffer should patch the relevant byte directly, the same way
+    /// [`rewrite_next_header`] patches Next Header fields.
+    pub(crate) fn process_at_node(&self) -> Option<Ipv6Addr> {
+        if self.segments_left() == 0 {
+            return None;
+        }
+
+        match &self.type_specific_data {
+            RoutingTypeSpecificData::Other(_) => None,
+        }
+    }
 }
 
 /// Routing Type specific data.
@@ -505,6 +969,53 @@ pub(crate) enum RoutingTypeSpecificData<'a> {
     Other(&'a u8),
 }
 
+/// Serializes an IPv6 Routing Type 0 header into `out`.
+///
+/// Writes `next_header`, an Hdr Ext Len of `2 * addresses.len()` (Routing
+/// Type 0's only type-specific data is its address list, at 16 bytes - 2
+/// 8-octet units - per address), the Routing Type 0 tag, `segments_left`,
+/// the 4-byte reserved field, and `addresses` in order.
+///
+/// As documented on [`RoutingData::collect_addresses`], this stack does not
+/// parse Routing Type 0 on receipt - it was deprecated by RFC 5095 for
+/// security reasons, and `Ipv6ExtensionHeaderImpl::parse_routing` rejects it
+/// like any other routing type it does not recognize - but being able to
+/// build well-formed Type 0 bytes is useful for exercising that rejection
+/// path from fuzzing and tests.
+///
+/// # Panics
+///
+/// Panics if `addresses.len()` is greater than 127 (the address list would
+/// not fit in the 1-byte Hdr Ext Len field) or if `segments_left` is greater
+/// than `addresses.len()`.
+pub(crate) fn build_routing_type0(
+    next_header: u8,
+    segments_left: u8,
+    addresses: &[Ipv6Addr],
+    out: &mut Vec<u8>,
+) {
+    assert!(
+        addresses.len() <= 127,
+        "too many addresses for a Routing Type 0 header: {}",
+        addresses.len()
+    );
+    assert!(
+        (segments_left as usize) <= addresses.len(),
+        "segments_left ({}) exceeds the number of addresses ({})",
+        segments_left,
+        addresses.len()
+    );
+
+    out.push(next_header);
+    out.push((addresses.len() * 2) as u8);
+    out.push(0); // Routing Type 0.
+    out.push(segments_left);
+    out.extend_from_slice(&[0; 4]); // Reserved.
+    for address in addresses {
+        out.extend_from_slice(&address.ipv6_bytes());
+    }
+}
+
 //
 // Fragment
 //
@@ -525,12 +1036,22 @@ pub(crate) struct FragmentData<'a> {
     bytes: &'a [u8],
 }
 
+/// The size, in bytes, of a fragment block, the unit that
+/// [`FragmentData::fragment_offset`] is expressed in.
+const FRAGMENT_BLOCK_SIZE: u32 = 8;
+
 impl<'a> FragmentData<'a> {
     pub(crate) fn fragment_offset(&self) -> u16 {
         debug_assert!(self.bytes.len() == 6);
         ((u16::from(self.bytes[0]) << 5) | (u16::from(self.bytes[1]) >> 3))
     }
 
+    /// Like [`fragment_offset`], but expressed as a byte offset rather than a
+    /// count of `FRAGMENT_BLOCK_SIZE`-byte blocks.
+    pub(crate) fn fragment_offset_bytes(&self) -> u32 {
+        u32::from(self.fragment_offset()) * FRAGMENT_BLOCK_SIZE
+    }
+
     pub(crate) fn m_flag(&self) -> bool {
         debug_assert!(self.bytes.len() == 6);
         ((self.bytes[1] & 0x1) == 0x01)
@@ -542,6 +1063,62 @@ impl<'a> FragmentData<'a> {
     }
 }
 
+/// Walks an extension header chain looking for a Fragment header, without
+/// materializing any options.
+///
+/// This is a fast path for callers, such as the reassembly code, that only
+/// care about whether a packet is a fragment and, if so, the contents of its
+/// Fragment header. Unlike [`Ipv6ExtensionHeaderImpl::parse_with_context`],
+/// this does not build up `Ipv6ExtensionHeaderOption`s for headers that carry
+/// them (Hop-By-Hop/Destination Options); it only reads each extension
+/// header's Next Header and, where applicable, Hdr Ext Len so it can skip to
+/// the next one.
+///
+/// Returns `None` if the chain reaches an upper-layer protocol before a
+/// Fragment header is found, or if the chain is malformed (buffer exhausted
+/// early, or an unsupported/unrecognized extension header that we cannot
+/// safely skip, such as Encapsulating Security Payload).
+pub(crate) fn extract_fragment_data<'a, BV: BufferView<&'a [u8]>>(
+    data: &mut BV,
+    first_next_header: u8,
+) -> Option<FragmentData<'a>> {
+    let mut next_header = first_next_header;
+
+    while !is_valid_next_header_upper_layer(next_header) {
+        match Ipv6ExtHdrType::from(next_header) {
+            Ipv6ExtHdrType::Fragment => {
+                // Take Next Header and Reserved, same as `get_next_hdr_and_len`.
+                let _next_header_and_reserved = data.take_front(2)?;
+                return Some(FragmentData { bytes: data.take_front(6)? });
+            }
+            Ipv6ExtHdrType::HopByHopOptions
+            | Ipv6ExtHdrType::Routing
+            | Ipv6ExtHdrType::DestinationOptions
+            | Ipv6ExtHdrType::Other(_) => {
+                // Take next header and header len, and skip the next 6
+                // octets plus the number of 64 bit words in header len, same
+                // as `Ipv6ExtensionHeaderImpl::parse_raw_with_context`.
+                let (next, skip) =
+                    data.take_front(2).map(|x| (x[0], (x[1] as usize) * 8 + 6))?;
+                data.take_front(skip)?;
+                next_header = next;
+            }
+            Ipv6ExtHdrType::EncapsulatingSecurityPayload => {
+                // We don't support ESP, so we can't safely skip over it.
+                return None;
+            }
+            Ipv6ExtHdrType::Authentication => {
+                let (next, skip) =
+                    data.take_front(2).map(|x| (x[0], (x[1] as usize + 2) * 4 - 2))?;
+                data.take_front(skip)?;
+                next_header = next;
+            }
+        }
+    }
+
+    None
+}
+
 //
 // Destination Options
 //
@@ -584,6 +1161,47 @@ impl<'a> ExtensionHeaderOptionDataImpl<'a> for DestinationOptionDataImpl {
 // Generic Extension Header who's data are options.
 //
 
+/// The maximum number of unrecognized options collected by a context created
+/// with [`ExtensionHeaderOptionContext::new_collect_unrecognized`]. Bounds
+/// how much diagnostic state a single header can force us to hold on to.
+const MAX_COLLECTED_UNRECOGNIZED_OPTIONS: usize = 8;
+
+/// Per-option-kind counts accumulated while parsing a sequence of extension
+/// header options, for use by callers that want to monitor which kinds of
+/// options actually appear in traffic.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExtensionHeaderOptionCounts {
+    pad1: usize,
+    padn: usize,
+    recognized: HashMap<u8, usize>,
+    unrecognized: usize,
+}
+
+impl ExtensionHeaderOptionCounts {
+    /// The number of Pad1 options parsed.
+    fn pad1(&self) -> usize {
+        self.pad1
+    }
+
+    /// The number of PadN options parsed.
+    fn padn(&self) -> usize {
+        self.padn
+    }
+
+    /// The number of options of the given `kind` that were recognized and
+    /// parsed by `O::parse_option`.
+    fn recognized(&self, kind: u8) -> usize {
+        *self.recognized.get(&kind).unwrap_or(&0)
+    }
+
+    /// The number of options that were not recognized by `O::parse_option`
+    /// (i.e. whose `kind` was not understood by the extension header in
+    /// question).
+    fn unrecognized(&self) -> usize {
+        self.unrecognized
+    }
+}
+
 /// Context that gets passed around when parsing IPv6 Extension Header options.
 #[derive(Debug, Clone)]
 pub(crate) struct ExtensionHeaderOptionContext<C: Sized + Clone> {
@@ -593,8 +1211,23 @@ pub(crate) struct ExtensionHeaderOptionContext<C: Sized + Clone> {
     // Byte count of successfully parsed options.
     bytes_parsed: usize,
 
+    // Per-option-kind counts. See `ExtensionHeaderOptionCounts`.
+    option_counts: ExtensionHeaderOptionCounts,
+
     // Extension header specific context data.
     specific_context: C,
+
+    // When `Some`, every unrecognized option whose action would otherwise
+    // abort parsing with an error is instead appended here (up to
+    // `MAX_COLLECTED_UNRECOGNIZED_OPTIONS`) and parsing continues, so that
+    // all such options present in the header can be reported together. See
+    // `new_collect_unrecognized`.
+    collected_unrecognized_options: Option<Vec<(u32, ExtensionHeaderOptionAction)>>,
+
+    // When `Some`, overrides the action taken for an unrecognized option,
+    // regardless of the action bits encoded in the option's kind octet. See
+    // `new_with_unrecognized_option_action_override`.
+    unrecognized_option_action_override: Option<ExtensionHeaderOptionAction>,
 }
 
 impl<C: Sized + Clone + Default> ExtensionHeaderOptionContext<C> {
@@ -602,9 +1235,79 @@ impl<C: Sized + Clone + Default> ExtensionHeaderOptionContext<C> {
         ExtensionHeaderOptionContext {
             options_parsed: 0,
             bytes_parsed: 0,
+            option_counts: ExtensionHeaderOptionCounts::default(),
             specific_context: C::default(),
+            collected_unrecognized_options: None,
+            unrecognized_option_action_override: None,
+        }
+    }
+
+    /// Like [`new`], but instead of aborting at the first unrecognized
+    /// option whose action would otherwise do so, collects it (up to
+    /// `MAX_COLLECTED_UNRECOGNIZED_OPTIONS`) and keeps parsing the rest of
+    /// the header's options, so that all of the unrecognized ones can be
+    /// reported together for diagnostics.
+    fn new_collect_unrecognized() -> Self {
+        ExtensionHeaderOptionContext {
+            collected_unrecognized_options: Some(Vec::new()),
+            ..Self::new()
+        }
+    }
+
+    /// Like [`new`], but overrides the action taken for every unrecognized
+    /// option to `action_override`, regardless of the action bits encoded
+    /// in the option's kind octet.
+    ///
+    /// Useful for a lenient policy (e.g. debugging) that wants to keep
+    /// parsing past unrecognized options that would otherwise abort
+    /// parsing with an error.
+    fn new_with_unrecognized_option_action_override(
+        action_override: ExtensionHeaderOptionAction,
+    ) -> Self {
+        ExtensionHeaderOptionContext {
+            unrecognized_option_action_override: Some(action_override),
+            ..Self::new()
+        }
+    }
+}
+
+impl<C: Sized + Clone> ExtensionHeaderOptionContext<C> {
+    /// Creates a new context seeded with the given extension-header-specific
+    /// context data, for headers whose specific context must be seeded with
+    /// something other than `C::default()` (see [`new`]).
+    fn with_specific_context(specific_context: C) -> Self {
+        ExtensionHeaderOptionContext {
+            options_parsed: 0,
+            bytes_parsed: 0,
+            option_counts: ExtensionHeaderOptionCounts::default(),
+            specific_context,
+            collected_unrecognized_options: None,
+            unrecognized_option_action_override: None,
+        }
+    }
+
+    /// The unrecognized options collected so far, in the order encountered.
+    ///
+    /// Always empty unless this context was created with
+    /// [`new_collect_unrecognized`].
+    fn collected_unrecognized_options(&self) -> &[(u32, ExtensionHeaderOptionAction)] {
+        match &self.collected_unrecognized_options {
+            Some(v) => v,
+            None => &[],
         }
     }
+
+    /// The per-option-kind counts accumulated so far. See
+    /// `ExtensionHeaderOptionCounts`.
+    fn option_counts(&self) -> &ExtensionHeaderOptionCounts {
+        &self.option_counts
+    }
+
+    /// The extension-header-specific context data accumulated while
+    /// parsing.
+    fn specific_context(&self) -> &C {
+        &self.specific_context
+    }
 }
 
 impl<C: Sized + Clone> RecordsContext for ExtensionHeaderOptionContext<C> {}
@@ -679,36 +1382,43 @@ where
             Some(k) => k,
         };
 
-        // Will never get an error because we only use the 2 least significant bits which
-        // can only have a max value of 3 and all values in [0, 3] are valid values of
-        // `ExtensionHeaderOptionAction`.
-        let action =
-            ExtensionHeaderOptionAction::try_from((kind >> 6) & 0x3).expect("Unexpected error");
-        let mutable = ((kind >> 5) & 0x1) == 0x1;
-        let kind = kind & 0x1F;
+        let (action, mutable, kind) = ExtensionHeaderOptionAction::from_option_type(kind);
 
         // If our kind is a PAD1, consider it a NOP.
         if kind == Self::PAD1 {
             // Update context.
             context.options_parsed += 1;
             context.bytes_parsed += 1;
+            context.option_counts.pad1 += 1;
 
             return Ok(Some(None));
         }
 
+        // The pointer for any error at or after this point in parsing this
+        // option refers to the start of the option (the `kind` octet we
+        // already consumed above), matching the `UnrecognizedOption` pointer
+        // convention below.
+        let option_pointer = u32::try_from(context.bytes_parsed).unwrap();
+
         let len = data
             .take_byte_front()
             .ok_or_else(|| ExtensionHeaderOptionParsingError::BufferExhausted)?;
 
-        let data = data
-            .take_front(len as usize)
-            .ok_or_else(|| ExtensionHeaderOptionParsingError::BufferExhausted)?;
+        let data = data.take_front(len as usize).ok_or_else(|| {
+            // We ran out of bytes in the extension header itself (as opposed
+            // to running out of the whole packet buffer, which would already
+            // have been caught by the caller sizing our input to the header's
+            // `Hdr Ext Len`), so this option's claimed length overruns the
+            // header it's declared to be part of.
+            ExtensionHeaderOptionParsingError::OptionTooLong { pointer: option_pointer, len }
+        })?;
 
         // If our kind is a PADN, consider it a NOP as well.
         if kind == Self::PADN {
             // Update context.
             context.options_parsed += 1;
             context.bytes_parsed += 2 + (len as usize);
+            context.option_counts.padn += 1;
 
             return Ok(Some(None));
         }
@@ -724,20 +1434,34 @@ where
                 // Update context.
                 context.options_parsed += 1;
                 context.bytes_parsed += 2 + (len as usize);
+                *context.option_counts.recognized.entry(kind).or_insert(0) += 1;
 
                 Ok(Some(Some(ExtensionHeaderOption { action, mutable, data: o })))
             }
             None => {
-                // Unrecognized option type.
-                match action {
-                    // `O::parse_option` should never return `None` when the action is
+                // Unrecognized option type. `action` is the action encoded in the
+                // option itself, but `context.unrecognized_option_action_override`,
+                // when set, takes priority over it (see
+                // `new_with_unrecognized_option_action_override`).
+                let effective_action =
+                    context.unrecognized_option_action_override.unwrap_or(action);
+
+                match effective_action {
+                    // Under normal parsing (no override), `O::parse_option` should
+                    // never return `None` when the action is
                     // `ExtensionHeaderOptionAction::SkipAndContinue` because we expect
                     // `O::parse_option` to return something that holds the option data
                     // without actually parsing it since we pass `true` for its
-                    // `allow_unrecognized` parameter.
-                    ExtensionHeaderOptionAction::SkipAndContinue => unreachable!(
-                        "Should never end up here since action was set to skip and continue"
-                    ),
+                    // `allow_unrecognized` parameter. With an override in effect,
+                    // though, we can end up here even when the option's own action
+                    // bits say otherwise, in which case we skip it as requested.
+                    ExtensionHeaderOptionAction::SkipAndContinue => {
+                        context.options_parsed += 1;
+                        context.bytes_parsed += 2 + (len as usize);
+                        context.option_counts.unrecognized += 1;
+
+                        Ok(Some(None))
+                    }
                     // We know the below `try_from` call will not result in a `None` value because
                     // the maximum size of an IPv6 packet's payload (extension headers + body) is
                     // `std::u32::MAX`. This maximum size is only possible when using IPv6
@@ -748,10 +1472,31 @@ where
                     // a normal IPv6 packet (not a jumbogram), the maximum size of the payload is
                     // `std::u16::MAX` (as the normal payload length field is only 16 bits), which
                     // is significantly less than the maximum possible size of a jumbogram.
-                    _ => Err(ExtensionHeaderOptionParsingError::UnrecognizedOption {
-                        pointer: u32::try_from(context.bytes_parsed).unwrap(),
-                        action,
-                    }),
+                    _ => {
+                        let pointer = u32::try_from(context.bytes_parsed).unwrap();
+
+                        // If this context is collecting unrecognized options instead of
+                        // aborting at the first one, record it (up to the cap) and keep
+                        // going as though the option had been skipped.
+                        if let Some(collected) = &mut context.collected_unrecognized_options {
+                            if collected.len() < MAX_COLLECTED_UNRECOGNIZED_OPTIONS {
+                                collected.push((pointer, effective_action));
+                            }
+
+                            context.options_parsed += 1;
+                            context.bytes_parsed += 2 + (len as usize);
+                            context.option_counts.unrecognized += 1;
+
+                            return Ok(Some(None));
+                        }
+
+                        context.option_counts.unrecognized += 1;
+
+                        Err(ExtensionHeaderOptionParsingError::UnrecognizedOption {
+                            pointer,
+                            action: effective_action,
+                        })
+                    }
                 }
             }
         }
@@ -762,6 +1507,10 @@ where
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum ExtensionHeaderOptionParsingError {
     UnrecognizedOption { pointer: u32, action: ExtensionHeaderOptionAction },
+    // An option's declared `len` reaches past the end of the extension
+    // header it's part of, as opposed to `BufferExhausted`, which means we
+    // ran out of bytes reading the option's own `kind`/`len` fields.
+    OptionTooLong { pointer: u32, len: u8 },
     BufferExhausted,
 }
 
@@ -770,7 +1519,7 @@ pub(crate) enum ExtensionHeaderOptionParsingError {
 /// `ExtensionHeaderOptionAction` is an action that MUST be taken (according
 /// to RFC 8200 section 4.2) when an IPv6 processing node does not
 /// recognize an option's type.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ExtensionHeaderOptionAction {
     /// Skip over the option and continue processing the header.
     /// value = 0.
@@ -795,6 +1544,29 @@ pub(crate) enum ExtensionHeaderOptionAction {
     DiscardPacketSendICMPNoMulticast,
 }
 
+impl ExtensionHeaderOptionAction {
+    /// Decodes an extension header option's type byte (the first octet of an
+    /// option, sometimes called `kind` in this module) into its `action`,
+    /// `mutable` bit, and the actual option kind value, per RFC 8200 section
+    /// 4.2:
+    ///  - Bits 7-6 (`(kind >> 6) & 0x3`): the action to take if the option's
+    ///    type is unrecognized.
+    ///  - Bit 5 (`(kind >> 5) & 0x1`): whether the option's data may change
+    ///    en route to the packet's final destination.
+    ///  - Bits 4-0 (`kind & 0x1F`): the option's type.
+    pub(crate) fn from_option_type(kind: u8) -> (ExtensionHeaderOptionAction, bool, u8) {
+        // Will never get an error because we only use the 2 least significant bits which
+        // can only have a max value of 3 and all values in [0, 3] are valid values of
+        // `ExtensionHeaderOptionAction`.
+        let action =
+            ExtensionHeaderOptionAction::try_from((kind >> 6) & 0x3).expect("Unexpected error");
+        let mutable = ((kind >> 5) & 0x1) == 0x1;
+        let kind = kind & 0x1F;
+
+        (action, mutable, kind)
+    }
+}
+
 impl TryFrom<u8> for ExtensionHeaderOptionAction {
     type Error = ();
 
@@ -895,10 +1667,14 @@ pub(super) fn is_valid_next_header_upper_layer(next_header: u8) -> bool {
 /// length of the IPv6 header (including extension headers) that we know about up
 /// to the point of the error, `err`. Note, any data in a packet after the first
 /// `header_len` bytes is not parsed, so its context is unknown.
+///
+/// `header_type` is the extension header type whose options were being
+/// parsed, and is attached to the returned error for diagnostics.
 fn ext_hdr_opt_err_to_ext_hdr_err(
     offset: u32,
     header_len: usize,
     err: ExtensionHeaderOptionParsingError,
+    header_type: Ipv6ExtHdrType,
 ) -> Ipv6ExtensionHeaderParsingError {
     match err {
         ExtensionHeaderOptionParsingError::UnrecognizedOption { pointer, action } => {
@@ -907,14 +1683,52 @@ fn ext_hdr_opt_err_to_ext_hdr_err(
                 must_send_icmp: true,
                 header_len,
                 action,
+                additional: Vec::new(),
+                header_type: Some(header_type),
+            }
+        }
+        ExtensionHeaderOptionParsingError::OptionTooLong { pointer, len: _ } => {
+            Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+                pointer: offset + pointer,
+                must_send_icmp: true,
+                header_len,
+                header_type: Some(header_type),
             }
         }
         ExtensionHeaderOptionParsingError::BufferExhausted => {
-            Ipv6ExtensionHeaderParsingError::BufferExhausted
+            Ipv6ExtensionHeaderParsingError::BufferExhausted { header_type: Some(header_type) }
         }
     }
 }
 
+/// Converts the unrecognized options collected by a context created with
+/// [`ExtensionHeaderOptionContext::new_collect_unrecognized`] into the
+/// `Ipv6ExtensionHeaderParsingError` that reports them, or `None` if none
+/// were collected.
+///
+/// `offset`, `header_len`, and `header_type` have the same meaning as in
+/// [`ext_hdr_opt_err_to_ext_hdr_err`].
+fn collected_unrecognized_options_to_ext_hdr_err(
+    offset: u32,
+    header_len: usize,
+    collected: &[(u32, ExtensionHeaderOptionAction)],
+    header_type: Ipv6ExtHdrType,
+) -> Option<Ipv6ExtensionHeaderParsingError> {
+    let (first_pointer, first_action) = *collected.first()?;
+
+    Some(Ipv6ExtensionHeaderParsingError::UnrecognizedOption {
+        pointer: offset + first_pointer,
+        must_send_icmp: true,
+        header_len,
+        action: first_action,
+        additional: collected[1..]
+            .iter()
+            .map(|(pointer, action)| (offset + pointer, *action))
+            .collect(),
+        header_type: Some(header_type),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -962,6 +1776,101 @@ mod tests {
         assert!(!is_valid_next_header(255, false));
     }
 
+    #[test]
+    fn test_has_ext_headers() {
+        // A fixed header whose Next Header is already an upper-layer
+        // protocol has no extension headers to parse.
+        assert!(!has_ext_headers(IpProto::Tcp.into()));
+
+        // A fixed header whose Next Header is an extension header type does.
+        assert!(has_ext_headers(Ipv6ExtHdrType::HopByHopOptions.into()));
+    }
+
+    #[test]
+    fn test_extension_header_option_action_from_option_type() {
+        // kind = 0b000_00000: SkipAndContinue, not mutable, kind 0.
+        assert_eq!(
+            ExtensionHeaderOptionAction::from_option_type(0b000_00000),
+            (ExtensionHeaderOptionAction::SkipAndContinue, false, 0),
+        );
+
+        // kind = 0b010_00001: DiscardPacket, not mutable, kind 1.
+        assert_eq!(
+            ExtensionHeaderOptionAction::from_option_type(0b010_00001),
+            (ExtensionHeaderOptionAction::DiscardPacket, false, 1),
+        );
+
+        // kind = 0b101_00010: DiscardPacketSendICMP, mutable, kind 2.
+        assert_eq!(
+            ExtensionHeaderOptionAction::from_option_type(0b101_00010),
+            (ExtensionHeaderOptionAction::DiscardPacketSendICMP, true, 2),
+        );
+
+        // kind = 0b111_11111: DiscardPacketSendICMPNoMulticast, mutable, kind 31.
+        assert_eq!(
+            ExtensionHeaderOptionAction::from_option_type(0b111_11111),
+            (ExtensionHeaderOptionAction::DiscardPacketSendICMPNoMulticast, true, 31),
+        );
+    }
+
+    #[test]
+    fn test_extract_fragment_data() {
+        // A HopByHop Options header (with no options, so it is the minimum
+        // length of 8 bytes) followed by a Fragment header, followed by a
+        // TCP payload. `extract_fragment_data` should skip over the HopByHop
+        // header without parsing its options and return the Fragment
+        // header's data.
+        let frag_offset_res_m_flag: u16 = (5063 << 3) | 1;
+        let identification: u32 = 3266246449;
+        #[rustfmt::skip]
+        let buffer = [
+            // HopByHop Options Extension Header.
+            Ipv6ExtHdrType::Fragment.into(),       // Next Header
+            0,                                      // Hdr Ext Len (0 means 8 bytes total)
+            0, 0, 0, 0, 0, 0,                       // Pad
+            // Fragment Extension Header.
+            IpProto::Tcp.into(),                    // Next Header
+            0,                                       // Reserved
+            (frag_offset_res_m_flag >> 8) as u8,    // Fragment Offset MSB
+            (frag_offset_res_m_flag & 0xFF) as u8,  // Fragment Offset LS5bits w/ Res w/ M Flag
+            // Identification
+            (identification >> 24) as u8,
+            ((identification >> 16) & 0xFF) as u8,
+            ((identification >> 8) & 0xFF) as u8,
+            (identification & 0xFF) as u8,
+        ];
+        let mut bv = &buffer[..];
+        let fragment_data =
+            extract_fragment_data(&mut bv, Ipv6ExtHdrType::HopByHopOptions.into())
+                .expect("should have found a Fragment header");
+        assert_eq!(fragment_data.fragment_offset(), 5063);
+        assert_eq!(fragment_data.m_flag(), true);
+        assert_eq!(fragment_data.identification(), 3266246449);
+
+        // A Next Header that is already an upper-layer protocol has no
+        // Fragment header to find.
+        assert!(extract_fragment_data(&mut &[][..], IpProto::Tcp.into()).is_none());
+    }
+
+    #[test]
+    fn test_new_expecting_ext_header() {
+        // Seeding an upper-layer protocol into the strict constructor should
+        // be caught immediately, instead of silently succeeding with a
+        // context that would go on to parse zero extension headers.
+        let tcp: u8 = IpProto::Tcp.into();
+        let error = Ipv6ExtensionHeaderParsingContext::new_expecting_ext_header(tcp)
+            .expect_err("should not allow an upper-layer protocol as the expected Next Header");
+        if let Ipv6ExtensionHeaderParsingError::NotAnExtensionHeader { next_header } = error {
+            assert_eq!(next_header, tcp);
+        } else {
+            panic!("Should have matched with NotAnExtensionHeader: {:?}", error);
+        }
+
+        // A Next Header that is actually an extension header type is fine.
+        let hop_by_hop: u8 = Ipv6ExtHdrType::HopByHopOptions.into();
+        assert!(Ipv6ExtensionHeaderParsingContext::new_expecting_ext_header(hop_by_hop).is_ok());
+    }
+
     #[test]
     fn test_hop_by_hop_options() {
         // Test parsing of Pad1 (marked as NOP)
@@ -1085,6 +1994,76 @@ mod tests {
         assert_eq!(context.options_parsed, 1);
     }
 
+    #[test]
+    fn test_hop_by_hop_options_unrecognized_option_action_override() {
+        // Same unknown option type w/ action set to discard as in
+        // `test_hop_by_hop_options_err`, but this time parsed with a context
+        // that overrides the action for unrecognized options to skip and
+        // continue. Parsing should succeed instead of erroring.
+        #[rustfmt::skip]
+        let buffer = [
+            1,   1, 0,                    // Pad3
+            127, 0,                       // Unrecognized Option Type w/ action to discard
+            1,   6, 0, 0, 0, 0, 0, 0,     // Pad8
+        ];
+        let mut context =
+            ExtensionHeaderOptionContext::new_with_unrecognized_option_action_override(
+                ExtensionHeaderOptionAction::SkipAndContinue,
+            );
+        Records::<_, HopByHopOptionsImpl>::parse_with_mut_context(&buffer[..], &mut context)
+            .expect("Should have parsed successfully with the unrecognized option action override");
+        assert_eq!(context.bytes_parsed, buffer.len());
+        assert_eq!(context.options_parsed, 3);
+        assert_eq!(context.option_counts.unrecognized, 1);
+    }
+
+    #[test]
+    fn test_hop_by_hop_options_option_too_long() {
+        // An option's declared length reaching past the end of the options
+        // buffer should be reported distinctly from simply running out of
+        // bytes reading an option's `kind`/`len` fields.
+        #[rustfmt::skip]
+        let buffer = [
+            1,  1, 0,  // Pad3
+            2, 10,     // An option claiming 10 bytes of data, but none follow.
+        ];
+        let mut context = ExtensionHeaderOptionContext::new();
+        assert_eq!(
+            Records::<_, HopByHopOptionsImpl>::parse_with_mut_context(&buffer[..], &mut context)
+                .expect_err("Parsed successfully with an option length overrunning the header"),
+            ExtensionHeaderOptionParsingError::OptionTooLong { pointer: 3, len: 10 }
+        );
+        assert_eq!(context.bytes_parsed, 3);
+        assert_eq!(context.options_parsed, 1);
+    }
+
+    #[test]
+    fn test_hop_by_hop_options_counts() {
+        // A mix of Pad1, PadN, an unrecognized option whose action is
+        // skip/continue (which `HopByHopOptionDataImpl` accepts without
+        // erroring, so it lands in the per-kind `recognized` counts), and an
+        // unrecognized option whose action would otherwise abort parsing
+        // (counted as `unrecognized`).
+        #[rustfmt::skip]
+        let buffer = [
+            0,                            // Pad1
+            1, 0,                         // Pad2
+            63,  1, 0,                    // Unrecognized Option Type but can skip/continue
+            127, 0,                       // Unrecognized Option Type w/ action to discard
+        ];
+        let mut context = ExtensionHeaderOptionContext::new_collect_unrecognized();
+        let options =
+            Records::<_, HopByHopOptionsImpl>::parse_with_mut_context(&buffer[..], &mut context)
+                .unwrap();
+        assert_eq!(options.iter().count(), 1);
+        assert_eq!(context.option_counts().pad1(), 1);
+        assert_eq!(context.option_counts().padn(), 1);
+        // Counts are keyed by the option's kind (bits 4-0 of its type), so
+        // option type 63 is counted under kind 31.
+        assert_eq!(context.option_counts().recognized(63 & 0x1F), 1);
+        assert_eq!(context.option_counts().unrecognized(), 1);
+    }
+
     #[test]
     fn test_destination_options() {
         // Test parsing of Pad1 (marked as NOP)
@@ -1257,6 +2236,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             assert_eq!(pointer, 0);
@@ -1284,12 +2264,15 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            ..
         } = error
         {
             assert_eq!(pointer, 8);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacket);
+            assert!(additional.is_empty());
         } else {
             panic!("Should have matched with UnrecognizedOption: {:?}", error);
         }
@@ -1312,12 +2295,15 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            ..
         } = error
         {
             assert_eq!(pointer, 8);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacketSendICMP);
+            assert!(additional.is_empty());
         } else {
             panic!("Should have matched with UnrecognizedOption: {:?}", error);
         }
@@ -1341,17 +2327,72 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            ..
         } = error
         {
             assert_eq!(pointer, 8);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacketSendICMPNoMulticast);
+            assert!(additional.is_empty());
         } else {
             panic!("Should have matched with UnrecognizedOption: {:?}", error);
         }
     }
 
+    #[test]
+    fn test_hop_by_hop_options_jumbo_payload_inconsistent() {
+        // RFC 2675 requires a jumbogram's fixed header Payload Length to be
+        // zero, so a Jumbo Payload option alongside a non-zero Payload
+        // Length should be reported as an erroneous header field.
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::HopByHopOptions.into())
+            .with_payload_len(100);
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(),  // Next Header
+            0,                    // Hdr Ext Len (In 8-octet units, not including first 8 octets)
+            0xC2, 4, 0, 1, 0, 0,  // Jumbo Payload option, length 0x00010000
+        ];
+        let error =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect_err("Parsed successfully with an inconsistent Jumbo Payload option");
+        if let Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+            pointer,
+            must_send_icmp,
+            header_len,
+            ..
+        } = error
+        {
+            assert_eq!(pointer, 2);
+            assert!(must_send_icmp);
+            assert_eq!(header_len, 0);
+        } else {
+            panic!("Should have matched with ErroneousHeaderField: {:?}", error);
+        }
+
+        // The same Jumbo Payload option is fine when the fixed header's
+        // Payload Length is zero, as RFC 2675 requires for jumbograms.
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::HopByHopOptions.into())
+            .with_payload_len(0);
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .unwrap();
+        let ext_hdrs: Vec<Ipv6ExtensionHeader> = ext_hdrs.iter().collect();
+        assert_eq!(ext_hdrs.len(), 1);
+        if let Ipv6ExtensionHeaderData::HopByHopOptions { options } = ext_hdrs[0].data() {
+            let options: Vec<HopByHopOption> = options.iter().collect();
+            assert_eq!(options.len(), 1);
+            if let HopByHopOptionData::JumboPayload(len) = options[0].data {
+                assert_eq!(len, 0x00010000);
+            } else {
+                panic!("Should have matched with JumboPayload: {:?}", options[0].data);
+            }
+        } else {
+            panic!("Should have matched HopByHopOptions {:?}", ext_hdrs[0].data());
+        }
+    }
+
     #[test]
     fn test_routing_ext_hdr() {
         // Test parsing of just a single Routing Extension Header.
@@ -1374,6 +2415,38 @@ mod tests {
         assert_eq!(ext_hdrs.iter().count(), 0);
     }
 
+    #[test]
+    fn test_routing_data_collect_addresses() {
+        // Since this stack doesn't parse Routing Type 0 (see
+        // `test_routing_ext_hdr_err`), the only `RoutingTypeSpecificData` a
+        // `RoutingData` can ever carry is `Other`, from which there is
+        // nothing to collect.
+        let routing_data = RoutingData {
+            bytes: &[0, 0],
+            type_specific_data: RoutingTypeSpecificData::Other(&0),
+        };
+        assert_eq!(routing_data.collect_addresses(), Vec::<Ipv6Addr>::new());
+    }
+
+    #[test]
+    fn test_routing_data_process_at_node() {
+        // Walk a (hypothetical) Type 0 header through successive node processing, as
+        // `segments_left` counts down from 2 to 0. Since this stack doesn't parse Routing
+        // Type 0 (see `test_routing_ext_hdr_err`), `type_specific_data` can only ever be
+        // `Other`, so there is never an address to swap in - `process_at_node` returns `None`
+        // at every step, for two different reasons: first because no routing type is
+        // supported, and finally because the header is exhausted. If this stack ever grows
+        // support for an addressed routing type, the first two steps below should start
+        // returning the address the header carries at that point.
+        for segments_left in [2, 1, 0] {
+            let routing_data = RoutingData {
+                bytes: &[0, segments_left],
+                type_specific_data: RoutingTypeSpecificData::Other(&0),
+            };
+            assert_eq!(routing_data.process_at_node(), None);
+        }
+    }
+
     #[test]
     fn test_routing_ext_hdr_err() {
         // Test parsing of just a single Routing Extension Header with errors.
@@ -1398,11 +2471,13 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            header_type,
         } = error
         {
             assert_eq!(pointer, 2);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
+            assert_eq!(header_type, Some(Ipv6ExtHdrType::Routing));
         } else {
             panic!("Should have matched with ErroneousHeaderField: {:?}", error);
         }
@@ -1428,6 +2503,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             assert_eq!(pointer, 0);
@@ -1458,6 +2534,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             // Should point to the location of the routing type.
@@ -1469,6 +2546,77 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_routing_ext_hdr_truncated_addresses() {
+        // `hdr_ext_len` of 4 promises 32 bytes of addresses, but the buffer is cut one
+        // address (16 bytes) short. Make sure we get a `TruncatedRoutingAddresses` error
+        // reporting the shortfall, rather than a generic `BufferExhausted`.
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::Routing.into());
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(), // Next Header
+            4,                   // Hdr Ext Len (In 8-octet units, not including first 8 octets)
+            0,                   // Routing Type
+            0,                   // Segments Left (0 so no error from segments left)
+            0, 0, 0, 0,          // Reserved
+            // Only one address provided; a second is missing.
+            0,  1,  2,  3,  4,  5,  6,  7,  8,  9,  10, 11, 12, 13, 14, 15,
+        ];
+        let error =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect_err("Parsed successfully with a truncated address list");
+        if let Ipv6ExtensionHeaderParsingError::TruncatedRoutingAddresses { expected, got, .. } =
+            error
+        {
+            assert_eq!(expected, 32);
+            assert_eq!(got, 16);
+        } else {
+            panic!("Should have matched with TruncatedRoutingAddresses: {:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_build_routing_type0() {
+        let addresses = [Ipv6Addr::new([0; 16]), Ipv6Addr::new([1; 16]), Ipv6Addr::new([2; 16])];
+        let mut bytes = Vec::new();
+        build_routing_type0(IpProto::Tcp.into(), 0, &addresses, &mut bytes);
+
+        assert_eq!(bytes[0], IpProto::Tcp.into());
+        assert_eq!(bytes[1], 6); // Hdr Ext Len: 2 units per address * 3 addresses.
+        assert_eq!(bytes[2], 0); // Routing Type 0.
+        assert_eq!(bytes[3], 0); // Segments Left.
+        assert_eq!(&bytes[4..8], &[0; 4]); // Reserved.
+        assert_eq!(bytes.len(), 8 + 16 * addresses.len());
+
+        // `parse_routing` does not extract Routing Type 0's addresses (see
+        // `RoutingData::collect_addresses`), but with `segments_left` at 0 it
+        // should still recognize and skip a well-formed Type 0 header of
+        // ours without error, consuming exactly the bytes we wrote.
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::Routing.into());
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&bytes[..], context)
+                .unwrap();
+        assert_eq!(ext_hdrs.iter().count(), 0);
+
+        // Round-trip the addresses themselves at the byte level: the region
+        // `build_routing_type0` wrote them into deserializes back to the
+        // addresses we asked for.
+        for (i, expected) in addresses.iter().enumerate() {
+            let offset = 8 + i * 16;
+            let mut raw = [0; 16];
+            raw.copy_from_slice(&bytes[offset..offset + 16]);
+            assert_eq!(Ipv6Addr::new(raw), *expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_build_routing_type0_too_many_segments_left() {
+        let addresses = [Ipv6Addr::new([0; 16])];
+        let mut bytes = Vec::new();
+        build_routing_type0(IpProto::Tcp.into(), 2, &addresses, &mut bytes);
+    }
+
     #[test]
     fn test_fragment_ext_hdr() {
         // Test parsing of just a single Fragment Extension Header.
@@ -1496,6 +2644,7 @@ mod tests {
 
         if let Ipv6ExtensionHeaderData::Fragment { fragment_data } = ext_hdrs[0].data() {
             assert_eq!(fragment_data.fragment_offset(), 5063);
+            assert_eq!(fragment_data.fragment_offset_bytes(), 5063 * 8);
             assert_eq!(fragment_data.m_flag(), true);
             assert_eq!(fragment_data.identification(), 3266246449);
         } else {
@@ -1503,6 +2652,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fragment_ext_hdr_summarize() {
+        // The summary of a Fragment Extension Header should carry the same
+        // values as the borrowed `FragmentData` it was built from.
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::Fragment.into());
+        let frag_offset_res_m_flag: u16 = (5063 << 3) | 1;
+        let identification: u32 = 3266246449;
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(),                   // Next Header
+            0,                                     // Reserved
+            (frag_offset_res_m_flag >> 8) as u8,   // Fragment Offset MSB
+            (frag_offset_res_m_flag & 0xFF) as u8, // Fragment Offset LS5bits w/ Res w/ M Flag
+            // Identification
+            (identification >> 24) as u8,
+            ((identification >> 16) & 0xFF) as u8,
+            ((identification >> 8) & 0xFF) as u8,
+            (identification & 0xFF) as u8,
+        ];
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .unwrap();
+        let ext_hdrs: Vec<Ipv6ExtensionHeader> = ext_hdrs.iter().collect();
+        assert_eq!(ext_hdrs.len(), 1);
+
+        let summary = ext_hdrs[0].summarize();
+        assert_eq!(summary.next_header, IpProto::Tcp.into());
+        assert_eq!(
+            summary.data,
+            Ipv6ExtensionHeaderDataSummary::Fragment {
+                fragment_offset: 5063,
+                m_flag: true,
+                identification: 3266246449,
+            }
+        );
+    }
+
     #[test]
     fn test_fragment_ext_hdr_err() {
         // Test parsing of just a single Fragment Extension Header with errors.
@@ -1530,6 +2716,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             assert_eq!(pointer, 0);
@@ -1540,6 +2727,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fragment_ext_hdr_reserved_byte() {
+        // A non-zero reserved byte should be ignored by default, but
+        // rejected under a strict context.
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(), // Next Header
+            0xFF,                // Reserved (non-zero)
+            0, 0,                // Fragment Offset/Res/M Flag
+            0, 0, 0, 0,          // Identification
+        ];
+
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::Fragment.into());
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect("Lenient context should ignore a non-zero reserved byte");
+        assert_eq!(ext_hdrs.iter().count(), 1);
+
+        let context =
+            Ipv6ExtensionHeaderParsingContext::new_strict(Ipv6ExtHdrType::Fragment.into());
+        let error =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect_err("Strict context should reject a non-zero reserved byte");
+        if let Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+            pointer,
+            must_send_icmp,
+            header_len,
+            ..
+        } = error
+        {
+            assert_eq!(pointer, 1);
+            assert!(!must_send_icmp);
+            assert_eq!(header_len, 0);
+        } else {
+            panic!("Should have matched with ErroneousHeaderField: {:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_fragment_ext_hdr_reserved_bits() {
+        // The 2 reserved bits preceding the M flag should be ignored by
+        // default, but rejected under a strict context.
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(), // Next Header
+            0,                   // Reserved
+            0,                   // Fragment Offset MSB
+            0b0000_0110,         // Fragment Offset LSBits, reserved bits set, M Flag unset
+            0, 0, 0, 0,          // Identification
+        ];
+
+        let context = Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::Fragment.into());
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect("Lenient context should ignore non-zero reserved bits");
+        assert_eq!(ext_hdrs.iter().count(), 1);
+
+        let context =
+            Ipv6ExtensionHeaderParsingContext::new_strict(Ipv6ExtHdrType::Fragment.into());
+        let error =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect_err("Strict context should reject non-zero reserved bits");
+        if let Ipv6ExtensionHeaderParsingError::ErroneousHeaderField {
+            pointer,
+            must_send_icmp,
+            header_len,
+            ..
+        } = error
+        {
+            assert_eq!(pointer, 3);
+            assert!(!must_send_icmp);
+            assert_eq!(header_len, 0);
+        } else {
+            panic!("Should have matched with ErroneousHeaderField: {:?}", error);
+        }
+    }
+
     #[test]
     fn test_no_next_header_ext_hdr() {
         // Test parsing of just a single NoNextHeader Extension Header.
@@ -1601,6 +2865,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             assert_eq!(pointer, 0);
@@ -1628,12 +2893,16 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            header_type,
         } = error
         {
             assert_eq!(pointer, 8);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacket);
+            assert!(additional.is_empty());
+            assert_eq!(header_type, Some(Ipv6ExtHdrType::DestinationOptions));
         } else {
             panic!("Should have matched with UnrecognizedOption: {:?}", error);
         }
@@ -1656,12 +2925,15 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            ..
         } = error
         {
             assert_eq!(pointer, 8);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacketSendICMP);
+            assert!(additional.is_empty());
         } else {
             panic!("Should have matched with UnrecognizedOption: {:?}", error);
         }
@@ -1685,12 +2957,15 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            ..
         } = error
         {
             assert_eq!(pointer, 8);
             assert!(must_send_icmp);
             assert_eq!(header_len, 0);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacketSendICMPNoMulticast);
+            assert!(additional.is_empty());
         } else {
             panic!("Should have matched with UnrecognizedOption: {:?}", error);
         }
@@ -1800,6 +3075,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             assert_eq!(pointer, 8);
@@ -1846,6 +3122,7 @@ mod tests {
             pointer,
             must_send_icmp,
             header_len,
+            ..
         } = error
         {
             assert_eq!(pointer, 0);
@@ -1884,14 +3161,214 @@ mod tests {
             must_send_icmp,
             header_len,
             action,
+            additional,
+            ..
         } = error
         {
             assert_eq!(pointer, 16);
             assert!(must_send_icmp);
             assert_eq!(header_len, 8);
             assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacketSendICMP);
+            assert!(additional.is_empty());
         } else {
             panic!("Should have matched with UnrecognizedNextHeader: {:?}", error);
         }
     }
+
+    #[test]
+    fn test_destination_options_ext_hdr_multiple_unrecognized_options() {
+        // A Destination Options header with two unrecognized options, each with an
+        // action that would otherwise require discarding the packet at the first one.
+        // Destination Options collects all of them (up to a cap) so they can be
+        // reported together, rather than stopping at the first.
+        let context =
+            Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::DestinationOptions.into());
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(),      // Next Header
+            1,                        // Hdr Ext Len (8-octet units, not incl. first 8 octets)
+            127, 0,                   // Unrecognized option type w/ action = discard
+            191, 0,                   // Unrecognized option type w/ action = discard & send icmp
+            1,   8, 0, 0, 0, 0, 0, 0, 0, 0, // Pad10
+        ];
+        let error =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .expect_err("Parsed successfully with unrecognized destination option types");
+        if let Ipv6ExtensionHeaderParsingError::UnrecognizedOption {
+            pointer,
+            must_send_icmp,
+            header_len,
+            action,
+            additional,
+            ..
+        } = error
+        {
+            // The first unrecognized option is still what governs how the packet is
+            // handled...
+            assert_eq!(pointer, 2);
+            assert!(must_send_icmp);
+            assert_eq!(header_len, 0);
+            assert_eq!(action, ExtensionHeaderOptionAction::DiscardPacket);
+            // ...but the second is reported too, for diagnostics.
+            assert_eq!(additional, vec![(4, ExtensionHeaderOptionAction::DiscardPacketSendICMP)]);
+        } else {
+            panic!("Should have matched with UnrecognizedOption: {:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_is_mutable_en_route() {
+        // A Routing header is always mutable, since `segments_left` is changed by every
+        // node that processes it, regardless of what (unsupported) routing type it
+        // carries. This stack never actually constructs a `Routing` header while parsing
+        // a chain (see `test_routing_ext_hdr`), so build one directly, the same way
+        // `test_routing_data_collect_addresses` does.
+        let routing = Ipv6ExtensionHeaderData::Routing {
+            routing_data: RoutingData {
+                bytes: &[0, 0],
+                type_specific_data: RoutingTypeSpecificData::Other(&0),
+            },
+        };
+        assert!(routing.is_mutable_en_route());
+
+        // A Destination Options header with a mutable option is mutable.
+        let context =
+            Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::DestinationOptions.into());
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(), // Next Header
+            0,                   // Hdr Ext Len (In 8-octet units, not including first 8 octets)
+            37, 2, 0, 0,         // Option w/ action = skip/continue, mutable bit set
+            1,  0,               // Pad2
+        ];
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .unwrap();
+        let ext_hdrs: Vec<Ipv6ExtensionHeader> = ext_hdrs.iter().collect();
+        assert_eq!(ext_hdrs.len(), 1);
+        assert!(ext_hdrs[0].data().is_mutable_en_route());
+
+        // A Hop-by-Hop Options header made up of only pad options is immutable.
+        let context =
+            Ipv6ExtensionHeaderParsingContext::new(Ipv6ExtHdrType::HopByHopOptions.into());
+        #[rustfmt::skip]
+        let buffer = [
+            IpProto::Tcp.into(), // Next Header
+            0,                   // Hdr Ext Len (In 8-octet units, not including first 8 octets)
+            1,  4, 0, 0, 0, 0,   // Pad6
+        ];
+        let ext_hdrs =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buffer[..], context)
+                .unwrap();
+        let ext_hdrs: Vec<Ipv6ExtensionHeader> = ext_hdrs.iter().collect();
+        assert_eq!(ext_hdrs.len(), 1);
+        assert!(!ext_hdrs[0].data().is_mutable_en_route());
+    }
+
+    #[test]
+    fn test_fuzz_parse_ext_headers_never_panics() {
+        // A handful of adversarial buffers that have tripped up extension
+        // header parsers in the past: truncated headers, and a Hdr Ext Len
+        // claiming far more data than is actually present. None of these
+        // should panic; all should be reported as errors.
+        let buffers: &[(&[u8], u8)] = &[
+            // Empty buffer.
+            (&[], Ipv6ExtHdrType::HopByHopOptions.into()),
+            // Truncated Routing Extension Header: claims a Hdr Ext Len of
+            // 4 (32 bytes of addresses) but only provides a few bytes.
+            (&[IpProto::Tcp.into(), 4, 0, 0, 0, 0, 0, 0], Ipv6ExtHdrType::Routing.into()),
+            // Hdr Ext Len claims the maximum number of 8-octet units, far
+            // beyond what's in the buffer.
+            (&[IpProto::Tcp.into(), 255, 0, 0, 0, 0, 0, 0], Ipv6ExtHdrType::HopByHopOptions.into()),
+            // Truncated Fragment Extension Header (needs 8 bytes).
+            (&[IpProto::Tcp.into(), 0, 0], Ipv6ExtHdrType::Fragment.into()),
+            // First Next Header value is itself not a recognized extension
+            // header or upper layer protocol.
+            (&[0, 0, 0, 0], 255),
+        ];
+
+        for (data, first_next_header) in buffers {
+            assert!(
+                fuzz_parse_ext_headers(data, *first_next_header).is_err(),
+                "expected an error for buffer {:?} with first next header {}",
+                data,
+                first_next_header
+            );
+        }
+    }
+
+    #[test]
+    fn test_rewrite_next_header() {
+        #[rustfmt::skip]
+        let mut buf = [
+            // Fixed header.  Only byte 6, the Next Header field, is meaningful here.
+            0, 0, 0, 0, 0, 0, Ipv6ExtHdrType::DestinationOptions.into(), 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+
+            // First Destination Options Extension Header
+            Ipv6ExtHdrType::DestinationOptions.into(), // Next Header
+            0,                                         // Hdr Ext Len
+            0,                                         // Pad1
+            1, 0,                                      // Pad2
+            1, 1, 0,                                   // Pad3
+
+            // Second Destination Options Extension Header
+            Ipv6ExtHdrType::DestinationOptions.into(), // Next Header
+            0,                                         // Hdr Ext Len
+            0,                                         // Pad1
+            1, 0,                                      // Pad2
+            1, 1, 0,                                   // Pad3
+
+            // Third Destination Options Extension Header
+            IpProto::Tcp.into(), // Next Header
+            0,                   // Hdr Ext Len
+            0,                   // Pad1
+            1, 0,                // Pad2
+            1, 1, 0,             // Pad3
+
+            // Body
+            1, 2, 3, 4,
+        ];
+
+        let old_next_header = rewrite_next_header(&mut buf, 2, IpProto::Udp.into());
+        assert_eq!(old_next_header, Ipv6ExtHdrType::DestinationOptions.into());
+
+        // Re-parse the chain and confirm that the second header's Next Header was updated in
+        // place, and that the chain now ends there - the third header's bytes are still in
+        // `buf`, but are no longer linked into the extension header chain.
+        let context = Ipv6ExtensionHeaderParsingContext::new(buf[6]);
+        let records =
+            Records::<&[u8], Ipv6ExtensionHeaderImpl>::parse_with_context(&buf[40..], context)
+                .unwrap();
+        let parsed: Vec<_> = records.iter().collect();
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].next_header, Ipv6ExtHdrType::DestinationOptions.into());
+        assert_eq!(parsed[1].next_header, IpProto::Udp.into());
+    }
+
+    #[test]
+    fn test_parse_ipv6_payload() {
+        #[rustfmt::skip]
+        let buffer = [
+            // Hop By Hop Options Extension Header
+            IpProto::Tcp.into(), // Next Header
+            0,                   // Hdr Ext Len
+            0,                   // Pad1
+            1, 0,                // Pad2
+            1, 1, 0,             // Pad3
+
+            // "TCP" payload
+            1, 2, 3, 4,
+        ];
+
+        let (ext_hdrs, upper_layer_proto, upper_layer_payload) =
+            parse_ipv6_payload(Ipv6ExtHdrType::HopByHopOptions.into(), &buffer[..]).unwrap();
+
+        assert_eq!(ext_hdrs.len(), 1);
+        assert_eq!(ext_hdrs[0].next_header, IpProto::Tcp.into());
+        assert_eq!(upper_layer_proto, IpProto::Tcp.into());
+        assert_eq!(upper_layer_payload, &[1, 2, 3, 4]);
+    }
 }
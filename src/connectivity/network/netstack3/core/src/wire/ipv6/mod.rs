@@ -27,7 +27,8 @@ use crate::wire::{FromRaw, MaybeParsed, U16};
 use ext_hdrs::{
     is_valid_next_header, is_valid_next_header_upper_layer, ExtensionHeaderOptionAction,
     Ipv6ExtensionHeader, Ipv6ExtensionHeaderData, Ipv6ExtensionHeaderImpl,
-    Ipv6ExtensionHeaderParsingContext, Ipv6ExtensionHeaderParsingError, IPV6_FRAGMENT_EXT_HDR_LEN,
+    Ipv6ExtensionHeaderParsingContext, Ipv6ExtensionHeaderParsingError, Ipv6ExtensionHeaderSummary,
+    IPV6_FRAGMENT_EXT_HDR_LEN,
 };
 
 /// Length of the IPv6 fixed header.
@@ -66,6 +67,9 @@ fn ext_hdr_err_fn(hdr: &FixedHeader, err: Ipv6ExtensionHeaderParsingError) -> Ip
             pointer,
             must_send_icmp,
             header_len,
+            // Only used for diagnostics; which header type caused the error
+            // doesn't change how we respond to it.
+            header_type: _,
         } => {
             let (pointer, action) = match pointer.checked_add(IPV6_FIXED_HDR_LEN as u32) {
                 // Pointer calculation overflowed so set action to discard the packet and
@@ -92,6 +96,7 @@ fn ext_hdr_err_fn(hdr: &FixedHeader, err: Ipv6ExtensionHeaderParsingError) -> Ip
             pointer,
             must_send_icmp,
             header_len,
+            header_type: _,
         } => {
             let (pointer, action) = match pointer.checked_add(IPV6_FIXED_HDR_LEN as u32) {
                 None => (0, IpParseErrorAction::DiscardPacket),
@@ -113,6 +118,11 @@ fn ext_hdr_err_fn(hdr: &FixedHeader, err: Ipv6ExtensionHeaderParsingError) -> Ip
             must_send_icmp,
             header_len,
             action,
+            // Only used for diagnostics when a header collects more than one
+            // unrecognized option; the ICMP response we send is always about
+            // the first one, per `action` above.
+            additional: _,
+            header_type: _,
         } => {
             let (pointer, action) = match pointer.checked_add(IPV6_FIXED_HDR_LEN as u32) {
                 None => (0, IpParseErrorAction::DiscardPacket),
@@ -144,10 +154,14 @@ fn ext_hdr_err_fn(hdr: &FixedHeader, err: Ipv6ExtensionHeaderParsingError) -> Ip
                 action,
             }
         }
-        Ipv6ExtensionHeaderParsingError::BufferExhausted
-        | Ipv6ExtensionHeaderParsingError::MalformedData => {
-            // Unexpectedly running out of a buffer or encountering malformed
-            // data when parsing is a formatting error.
+        Ipv6ExtensionHeaderParsingError::BufferExhausted { .. }
+        | Ipv6ExtensionHeaderParsingError::MalformedData { .. }
+        | Ipv6ExtensionHeaderParsingError::TruncatedRoutingAddresses { .. }
+        | Ipv6ExtensionHeaderParsingError::NotAnExtensionHeader { .. } => {
+            // Unexpectedly running out of a buffer, encountering malformed
+            // data, or a context constructed with `new_expecting_ext_header`
+            // getting an upper-layer protocol after all, are all formatting
+            // errors.
             IpParseError::Parse { error: ParseError::Format }
         }
     }
@@ -297,6 +311,10 @@ impl<B: ByteSlice> FragmentablePacket for Ipv6Packet<B> {
             "Should never call this function if the packet does not have a fragment header"
         );
     }
+
+    fn fragment_body_len(&self) -> usize {
+        self.body().len()
+    }
 }
 
 impl<B: ByteSlice> Ipv6Packet<B> {
@@ -306,6 +324,27 @@ impl<B: ByteSlice> Ipv6Packet<B> {
         self.extension_hdrs.iter()
     }
 
+    /// The offset, from the start of the extension header chain, at which
+    /// extension header parsing stopped.
+    ///
+    /// This is the offset of the upper-layer payload (i.e. `self.body()`)
+    /// relative to the start of the extension headers, letting callers
+    /// slice it directly out of the extension header bytes without
+    /// re-walking the chain.
+    pub(crate) fn ext_hdrs_bytes_parsed(&self) -> usize {
+        self.extension_hdrs.bytes().len()
+    }
+
+    /// Collects an owned, serializable summary of this packet's extension
+    /// header chain.
+    ///
+    /// The returned summaries do not borrow from `self`, so callers can hold
+    /// onto them (e.g. to serialize the chain to JSON) after the packet
+    /// itself has gone out of scope.
+    pub(crate) fn collect_ext_hdr_summaries(&self) -> Vec<Ipv6ExtensionHeaderSummary> {
+        self.iter_extension_hdrs().map(|ext_hdr| ext_hdr.summarize()).collect()
+    }
+
     /// The packet body.
     pub(crate) fn body(&self) -> &[u8] {
         &self.body
@@ -541,7 +580,8 @@ impl<B: ByteSlice> ParsablePacket<B, ()> for Ipv6PacketRaw<B> {
             buffer.take_back(buffer.len() - pl_len).unwrap();
         }
 
-        let mut extension_hdr_context = Ipv6ExtensionHeaderParsingContext::new(fixed_hdr.next_hdr);
+        let mut extension_hdr_context = Ipv6ExtensionHeaderParsingContext::new(fixed_hdr.next_hdr)
+            .with_payload_len(fixed_hdr.payload_len.get());
 
         let extension_hdrs =
             RecordsRaw::parse_raw_with_mut_context(&mut buffer, &mut extension_hdr_context)
@@ -903,6 +943,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ext_hdrs_bytes_parsed_points_at_upper_layer_payload() {
+        #[rustfmt::skip]
+        let mut buf = [
+            // FixedHeader (will be replaced later)
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+
+            // HopByHop Options Extension Header
+            IpProto::Udp.into(), // Next Header
+            0,                   // Hdr Ext Len (In 8-octet units, not including first 8 octets)
+            0,                   // Pad1
+            1, 0,                // Pad2
+            1, 1, 0,             // Pad3
+
+            // "UDP header" body (not really parsed as UDP here, just opaque bytes).
+            11, 22, 33, 44, 55,
+        ];
+        let mut fixed_hdr = new_fixed_hdr();
+        fixed_hdr.next_hdr = Ipv6ExtHdrType::HopByHopOptions.into();
+        fixed_hdr.payload_len = U16::new((buf.len() - IPV6_FIXED_HDR_LEN) as u16);
+        let fixed_hdr_buf = fixed_hdr_to_bytes(fixed_hdr);
+        buf[..IPV6_FIXED_HDR_LEN].copy_from_slice(&fixed_hdr_buf);
+        let mut buf = &buf[..];
+        let packet = buf.parse::<Ipv6Packet<_>>().unwrap();
+        assert_eq!(packet.proto(), IpProto::Udp);
+
+        // The HopByHop Options header above is exactly 8 bytes, so that's
+        // where parsing should have stopped, pointing right at the start of
+        // the UDP header bytes.
+        assert_eq!(packet.ext_hdrs_bytes_parsed(), 8);
+        assert_eq!(packet.body(), [11, 22, 33, 44, 55]);
+    }
+
     #[test]
     fn test_parse_error() {
         // Set the version to 5. The version must be 6.
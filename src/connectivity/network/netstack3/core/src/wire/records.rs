@@ -503,6 +503,22 @@ where
         Ok(Records { bytes, context: c })
     }
 
+    /// Counts the number of records that can be parsed from `bytes`, without
+    /// materializing them.
+    ///
+    /// This is useful when a caller only needs to know how many records are
+    /// present (e.g. to validate a count carried elsewhere in a packet)
+    /// without paying the cost of keeping each parsed record around, as
+    /// `parse_with_context(bytes, context)?.iter().count()` would.
+    pub(crate) fn count_records(bytes: B, mut context: R::Context) -> Result<usize, R::Error> {
+        let mut b = LongLivedBuff::new(bytes.deref());
+        let mut count = 0;
+        while next::<_, R>(&mut b, &mut context)?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
     /// Parse a set of records with a context, using a `BufferView`.
     ///
     /// See `parse_bv_with_mut_context` for details on `bytes`, `context`, and
@@ -581,6 +597,19 @@ where
     }
 }
 
+impl<B, R: RecordsImplLayout> Records<B, R> {
+    /// Get the context these records were parsed with.
+    ///
+    /// This is the context as it was before parsing, which `iter` starts
+    /// each iteration from. Changes that parsing made to the context (e.g.
+    /// how many bytes were consumed) are not reflected here; they are
+    /// visible in the context passed to `parse_with_mut_context` and in
+    /// `RecordsIter::context` after iterating.
+    pub(crate) fn context(&self) -> &R::Context {
+        &self.context
+    }
+}
+
 impl<'a, B, R> Records<B, R>
 where
     B: 'a + ByteSlice,
@@ -1028,6 +1057,21 @@ mod test {
         }
     }
 
+    #[test]
+    fn count_records_matches_iter_count() {
+        for limit in 0..=(DUMMY_BYTES.len() / std::mem::size_of::<DummyRecord>() + 1) {
+            let count =
+                LimitedRecords::<_, LimitContextRecordImpl>::count_records(&DUMMY_BYTES[..], limit)
+                    .unwrap();
+            let parsed = LimitedRecords::<_, LimitContextRecordImpl>::parse_with_context(
+                &DUMMY_BYTES[..],
+                limit,
+            )
+            .unwrap();
+            assert_eq!(count, parsed.iter().count());
+        }
+    }
+
     #[test]
     fn limit_records_parsing_with_bv() {
         // Test without mutable limit/context
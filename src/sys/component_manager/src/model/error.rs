@@ -60,6 +60,11 @@ pub enum ModelError {
         #[fail(cause)]
         err: Error,
     },
+    #[fail(
+        display = "path for component {} has {} segments, which exceeds the maximum of {}",
+        moniker, actual, max
+    )]
+    PathTooLong { moniker: AbsoluteMoniker, actual: usize, max: usize },
 }
 
 impl ModelError {
@@ -105,6 +110,10 @@ impl ModelError {
     pub fn unsupported_hook_error(err: impl Into<Error>) -> ModelError {
         ModelError::UnsupportedHookError { err: err.into() }
     }
+
+    pub fn path_too_long(moniker: AbsoluteMoniker, actual: usize, max: usize) -> ModelError {
+        ModelError::PathTooLong { moniker, actual, max }
+    }
 }
 
 impl From<ResolverError> for ModelError {
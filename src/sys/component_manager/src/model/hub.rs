@@ -12,7 +12,7 @@ use {
             error::ModelError,
         },
     },
-    cm_rust::{CapabilityPath, FrameworkCapabilityDecl},
+    cm_rust::{CapabilityPath, ExposeDecl, FrameworkCapabilityDecl, UseDecl, UseStorageDecl},
     failure::format_err,
     fidl::endpoints::ServerEnd,
     fidl_fuchsia_io::{DirectoryProxy, NodeMarker, CLONE_FLAG_SAME_RIGHTS},
@@ -20,12 +20,17 @@ use {
     fuchsia_vfs_pseudo_fs::{directory, file::simple::read_only},
     fuchsia_zircon as zx,
     futures::{
+        channel::oneshot,
         future::{AbortHandle, Abortable, BoxFuture},
         lock::Mutex,
     },
     std::{collections::HashMap, sync::Arc},
 };
 
+/// Maximum number of path segments `HubCapability::open_async` will accept, as a guard against a
+/// pathological request (for example, one with thousands of segments) appended to `dir_path`.
+const MAX_PATH_SEGMENTS: usize = 1000;
+
 struct HubCapability {
     abs_moniker: model::AbsoluteMoniker,
     capability_path: CapabilityPath,
@@ -56,13 +61,20 @@ impl HubCapability {
             )));
         }
 
-        dir_path.append(
-            &mut relative_path
-                .split("/")
-                .map(|s| s.to_string())
-                .filter(|s| !s.is_empty())
-                .collect::<Vec<String>>(),
-        );
+        let mut relative_path_segments = relative_path
+            .split("/")
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<String>>();
+        let total_segments = dir_path.len() + relative_path_segments.len();
+        if total_segments > MAX_PATH_SEGMENTS {
+            return Err(ModelError::path_too_long(
+                self.abs_moniker.clone(),
+                total_segments,
+                MAX_PATH_SEGMENTS,
+            ));
+        }
+        dir_path.append(&mut relative_path_segments);
 
         let instances_map = await!(self.instances.lock());
         if !instances_map.contains_key(&self.abs_moniker) {
@@ -110,8 +122,29 @@ struct Execution {
     pub directory: directory::controlled::Controller<'static>,
 }
 
+/// A predicate applied to the target path of each capability a component uses or exposes, to
+/// redact sensitive capabilities from the hub's `exec/in` and `exec/expose` directories.
+///
+/// Returns `true` if `path` should be redacted (omitted from the hub).
+pub type CapabilityRedactionFn = Box<dyn Fn(&CapabilityPath) -> bool + Send + Sync>;
+
 pub struct Hub {
     instances: Arc<Mutex<HashMap<model::AbsoluteMoniker, Instance>>>,
+    /// If set, only capabilities whose target path starts with one of these prefixes are
+    /// visible in a component's `exec/in` and `exec/expose` directories. `None` means no
+    /// filtering is applied.
+    capability_allowlist: Option<Vec<String>>,
+    /// If set, capabilities whose target path matches this predicate are omitted from a
+    /// component's `exec/in` and `exec/expose` directories. `None` means no redaction is
+    /// applied. Combined with `capability_allowlist`, this allows fine-grained control over
+    /// what's visible in the hub.
+    capability_redaction: Option<CapabilityRedactionFn>,
+    /// Controller for the hub's root directory, retained so additional client connections to
+    /// the hub root can be minted on demand after construction, via `open_root`.
+    root_dir: directory::controlled::Controller<'static>,
+    /// Pending `wait_for_instance` calls, keyed by the moniker being waited on. Each sender is
+    /// notified (and removed) once that moniker is added to `instances`.
+    waiters: Arc<Mutex<HashMap<model::AbsoluteMoniker, Vec<oneshot::Sender<()>>>>>,
     /// Called when Hub is dropped to drop pseudodirectory hosting the Hub.
     abort_handle: AbortHandle,
 }
@@ -124,9 +157,16 @@ impl Drop for Hub {
 
 impl Hub {
     /// Create a new Hub given a |component_url| and a controller to the root directory.
+    /// If `capability_allowlist` is set, only capabilities whose target path starts with
+    /// one of its prefixes are exposed in `exec/in` and `exec/expose`. If `capability_redaction`
+    /// is set, capabilities whose target path matches it are additionally omitted from those
+    /// same directories.
     pub fn new(
         component_url: String,
         mut root_directory: directory::simple::Simple<'static>,
+        config: &model::ModelConfig,
+        capability_allowlist: Option<Vec<String>>,
+        capability_redaction: Option<CapabilityRedactionFn>,
     ) -> Result<Hub, ModelError> {
         let mut instances_map = HashMap::new();
         let abs_moniker = model::AbsoluteMoniker::root();
@@ -136,6 +176,15 @@ impl Hub {
                 .expect("Did not create directory.");
         root_directory.add_node("self", self_directory, &abs_moniker)?;
 
+        let instances = Arc::new(Mutex::new(instances_map));
+        Self::add_all_instances_file(&mut root_directory, instances.clone(), &abs_moniker)?;
+        Self::add_config_file(&mut root_directory, config, &abs_moniker)?;
+
+        // Wrap the root directory in a `Controller` so additional client connections to it can
+        // be minted later, via `open_root`, without needing to hold on to `root_directory`
+        // itself (which is about to be consumed below).
+        let (root_dir, root_directory) = directory::controlled::controlled(root_directory);
+
         // Run the hub root directory forever until the component manager is terminated.
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         let future = Abortable::new(root_directory, abort_registration);
@@ -143,7 +192,144 @@ impl Hub {
             let _ = await!(future);
         });
 
-        Ok(Hub { instances: Arc::new(Mutex::new(instances_map)), abort_handle })
+        let waiters = Arc::new(Mutex::new(HashMap::new()));
+
+        Ok(Hub {
+            instances,
+            capability_allowlist,
+            capability_redaction,
+            root_dir,
+            waiters,
+            abort_handle,
+        })
+    }
+
+    /// Open an additional client connection to the hub's root directory.
+    ///
+    /// This can be used to serve the hub to additional clients discovered after the `Hub` was
+    /// constructed, without needing to plumb the original `root_directory` through.
+    pub async fn open_root(
+        &self,
+        flags: u32,
+        open_mode: u32,
+        server_end: ServerEnd<NodeMarker>,
+    ) -> Result<(), ModelError> {
+        await!(self.root_dir.open_node(
+            flags,
+            open_mode,
+            vec![],
+            server_end,
+            &model::AbsoluteMoniker::root(),
+        ))
+    }
+
+    /// Resolves once `abs_moniker` appears in the hub, or immediately if it's already there.
+    /// Built on a one-shot notification fired by `notify_waiters` when the instance is added,
+    /// rather than polling.
+    pub async fn wait_for_instance(
+        &self,
+        abs_moniker: &model::AbsoluteMoniker,
+    ) -> Result<(), ModelError> {
+        let receiver = {
+            let instances = await!(self.instances.lock());
+            if instances.contains_key(abs_moniker) {
+                return Ok(());
+            }
+            let (sender, receiver) = oneshot::channel();
+            let mut waiters = await!(self.waiters.lock());
+            waiters.entry(abs_moniker.clone()).or_insert_with(Vec::new).push(sender);
+            receiver
+        };
+        await!(receiver).map_err(|_| {
+            ModelError::unsupported_hook_error(format_err!(
+                "Hub was dropped while waiting for instance {}",
+                abs_moniker
+            ))
+        })
+    }
+
+    /// Wakes up any `wait_for_instance` calls pending on `abs_moniker`. Called with `instances`
+    /// locked so that a `wait_for_instance` call cannot race between checking `instances` and
+    /// registering to be woken up.
+    async fn notify_waiters(&self, abs_moniker: &model::AbsoluteMoniker) {
+        let mut waiters = await!(self.waiters.lock());
+        if let Some(senders) = waiters.remove(abs_moniker) {
+            for sender in senders {
+                let _ = sender.send(());
+            }
+        }
+    }
+
+    /// Returns true if `path` should be visible in the hub, given `allowlist`. A `None`
+    /// allowlist means everything is visible.
+    ///
+    /// A prefix matches `path` only at a `/`-separated segment boundary, so an allowlist entry
+    /// of `/data` admits `/data` and `/data/foo` but not an unrelated path like `/database`
+    /// that merely happens to share the same leading characters.
+    fn capability_path_allowed(path: &CapabilityPath, allowlist: &Option<Vec<String>>) -> bool {
+        match allowlist {
+            None => true,
+            Some(prefixes) => {
+                let path = path.to_string();
+                prefixes.iter().any(|prefix| {
+                    path.starts_with(prefix.as_str())
+                        && path[prefix.len()..].chars().next().map_or(true, |c| c == '/')
+                })
+            }
+        }
+    }
+
+    /// Returns true if `path` should be redacted (omitted) from the hub, given `redaction`. A
+    /// `None` redaction means nothing is redacted.
+    fn capability_path_redacted(
+        path: &CapabilityPath,
+        redaction: &Option<CapabilityRedactionFn>,
+    ) -> bool {
+        match redaction {
+            None => false,
+            Some(redact) => redact(path),
+        }
+    }
+
+    /// Add an `all_instances` file to `root_directory` that serves a newline-separated,
+    /// sorted list of every moniker currently present in `instances`, read fresh on every
+    /// open so that it always reflects the instances known at that time.
+    fn add_all_instances_file(
+        root_directory: &mut directory::simple::Simple<'static>,
+        instances: Arc<Mutex<HashMap<model::AbsoluteMoniker, Instance>>>,
+        abs_moniker: &model::AbsoluteMoniker,
+    ) -> Result<(), ModelError> {
+        root_directory.add_node(
+            "all_instances",
+            read_only(move || {
+                let instances = instances.try_lock().ok_or(zx::Status::SHOULD_WAIT)?;
+                let mut monikers: Vec<String> =
+                    instances.keys().map(|moniker| moniker.to_string()).collect();
+                monikers.sort();
+                Ok(monikers.join("\n").into_bytes())
+            }),
+            &abs_moniker,
+        )?;
+        Ok(())
+    }
+
+    /// Add a `config` file to `root_directory` that serves a newline-separated summary of
+    /// `config`'s values as `key=value` lines, so that the active `ModelConfig` for this
+    /// component_manager instance can be inspected at runtime.
+    fn add_config_file(
+        root_directory: &mut directory::simple::Simple<'static>,
+        config: &model::ModelConfig,
+        abs_moniker: &model::AbsoluteMoniker,
+    ) -> Result<(), ModelError> {
+        let list_children_batch_size = config.list_children_batch_size;
+        root_directory.add_node(
+            "config",
+            read_only(move || {
+                Ok(format!("list_children_batch_size={}\n", list_children_batch_size).into_bytes())
+            }),
+            &abs_moniker,
+        )?;
+        Ok(())
     }
 
     fn add_instance_if_necessary(
@@ -219,28 +405,66 @@ impl Hub {
         Ok(())
     }
 
+    fn add_started_at_file(
+        execution_directory: &mut directory::controlled::Controlled<'static>,
+        started_at: zx::Time,
+        abs_moniker: &model::AbsoluteMoniker,
+    ) -> Result<(), ModelError> {
+        execution_directory.add_node(
+            "started_at",
+            { read_only(move || Ok(started_at.into_nanos().to_string().into_bytes())) },
+            &abs_moniker,
+        )?;
+        Ok(())
+    }
+
     fn add_in_directory(
         execution_directory: &mut directory::controlled::Controlled<'static>,
         realm_state: &model::RealmState,
         routing_facade: &model::RoutingFacade,
+        capability_allowlist: &Option<Vec<String>>,
+        capability_redaction: &Option<CapabilityRedactionFn>,
         abs_moniker: &model::AbsoluteMoniker,
     ) -> Result<(), ModelError> {
         let execution = realm_state.execution.as_ref().unwrap();
-        let decl = realm_state.get_decl();
+        let mut decl = realm_state.get_decl().clone();
+        decl.uses.retain(|use_| {
+            let path = match use_ {
+                UseDecl::Service(d) => &d.target_path,
+                UseDecl::Directory(d) => &d.target_path,
+                UseDecl::Storage(UseStorageDecl::Data(p)) => p,
+                UseDecl::Storage(UseStorageDecl::Cache(p)) => p,
+                UseDecl::Storage(UseStorageDecl::Meta) => return true,
+            };
+            Self::capability_path_allowed(path, capability_allowlist)
+                && !Self::capability_path_redacted(path, capability_redaction)
+        });
         let tree = model::DirTree::build_from_uses(
             routing_facade.route_use_fn_factory(),
             &abs_moniker,
-            decl.clone(),
+            decl,
         )?;
         let mut in_dir = directory::simple::empty();
         tree.install(&abs_moniker, &mut in_dir)?;
-        let pkg_dir = execution.namespace.as_ref().and_then(|n| n.package_dir.as_ref());
+        let namespace = execution.namespace.as_ref();
+        let pkg_dir = namespace.and_then(|n| n.package_dir.as_ref());
         if let Some(pkg_dir) = Self::clone_dir(pkg_dir) {
             in_dir.add_node(
                 "pkg",
                 directory_broker::DirectoryBroker::from_directory_proxy(pkg_dir),
                 &abs_moniker,
             )?;
+            // Report the package URL the `pkg` directory just above was resolved from, so
+            // tools can correlate the served directory with its source. Fall back to the
+            // component's resolved URL if the resolver didn't provide a package URL.
+            let package_url = namespace
+                .and_then(|n| n.package_url.clone())
+                .unwrap_or_else(|| execution.resolved_url.clone());
+            execution_directory.add_node(
+                "pkg_path",
+                { read_only(move || Ok(package_url.clone().into_bytes())) },
+                &abs_moniker,
+            )?;
         }
         execution_directory.add_node("in", in_dir, &abs_moniker)?;
         Ok(())
@@ -250,13 +474,23 @@ impl Hub {
         execution_directory: &mut directory::controlled::Controlled<'static>,
         realm_state: &model::RealmState,
         routing_facade: &model::RoutingFacade,
+        capability_allowlist: &Option<Vec<String>>,
+        capability_redaction: &Option<CapabilityRedactionFn>,
         abs_moniker: &model::AbsoluteMoniker,
     ) -> Result<(), ModelError> {
-        let decl = realm_state.get_decl();
+        let mut decl = realm_state.get_decl().clone();
+        decl.exposes.retain(|expose| {
+            let path = match expose {
+                ExposeDecl::Service(d) => &d.target_path,
+                ExposeDecl::Directory(d) => &d.target_path,
+            };
+            Self::capability_path_allowed(path, capability_allowlist)
+                && !Self::capability_path_redacted(path, capability_redaction)
+        });
         let tree = model::DirTree::build_from_exposes(
             routing_facade.route_expose_fn_factory(),
             &abs_moniker,
-            decl.clone(),
+            decl,
         );
         let mut expose_dir = directory::simple::empty();
         tree.install(&abs_moniker, &mut expose_dir)?;
@@ -309,6 +543,7 @@ impl Hub {
             component_url,
             &mut instances_map,
         ))?;
+        await!(self.notify_waiters(&abs_moniker));
 
         let instance = instances_map
             .get_mut(&abs_moniker)
@@ -317,6 +552,8 @@ impl Hub {
         // If we haven't already created an execution directory, create one now.
         if instance.execution.is_none() {
             if let Some(execution) = realm_state.execution.as_ref() {
+                let started_at = zx::Time::get(zx::ClockId::Monotonic);
+
                 let (execution_controller, mut execution_controlled) =
                     directory::controlled::controlled(directory::simple::empty());
 
@@ -332,10 +569,14 @@ impl Hub {
                     &abs_moniker,
                 )?;
 
+                Self::add_started_at_file(&mut execution_controlled, started_at, &abs_moniker)?;
+
                 Self::add_in_directory(
                     &mut execution_controlled,
                     realm_state,
                     &routing_facade,
+                    &self.capability_allowlist,
+                    &self.capability_redaction,
                     &abs_moniker,
                 )?;
 
@@ -343,6 +584,8 @@ impl Hub {
                     &mut execution_controlled,
                     realm_state,
                     &routing_facade,
+                    &self.capability_allowlist,
+                    &self.capability_redaction,
                     &abs_moniker,
                 )?;
 
@@ -360,6 +603,7 @@ impl Hub {
                 child_realm.component_url.clone(),
                 &mut instances_map,
             ))?;
+            await!(self.notify_waiters(&child_realm.abs_moniker));
         }
 
         Ok(())
@@ -452,8 +696,9 @@ mod tests {
             },
         },
         cm_rust::{
-            self, CapabilityPath, ChildDecl, ComponentDecl, ExposeDecl, ExposeDirectoryDecl,
-            ExposeServiceDecl, ExposeSource, UseDecl, UseDirectoryDecl, UseServiceDecl, UseSource,
+            self, CapabilityPath, ChildDecl, CollectionDecl, ComponentDecl, ExposeDecl,
+            ExposeDirectoryDecl, ExposeServiceDecl, ExposeSource, UseDecl, UseDirectoryDecl,
+            UseServiceDecl, UseSource,
         },
         fidl::endpoints::{ClientEnd, ServerEnd},
         fidl_fuchsia_io::{
@@ -521,6 +766,27 @@ mod tests {
         })
     }
 
+    /// Hosts a package directory with a 'meta' file.
+    fn pkg_dir_fn() -> Box<dyn Fn(ServerEnd<DirectoryMarker>) + Send + Sync> {
+        Box::new(move |server_end: ServerEnd<DirectoryMarker>| {
+            let mut pkg_dir = directory::simple::empty();
+            pkg_dir
+                .add_entry("meta", { read_only(move || Ok(b"root.cm".to_vec())) })
+                .map_err(|(s, _)| s)
+                .expect("Failed to add 'meta' entry");
+
+            pkg_dir.open(
+                OPEN_RIGHT_READABLE | OPEN_RIGHT_WRITABLE,
+                MODE_TYPE_DIRECTORY,
+                &mut iter::empty(),
+                ServerEnd::new(server_end.into_channel()),
+            );
+            fasync::spawn(async move {
+                let _ = await!(pkg_dir);
+            });
+        })
+    }
+
     type DirectoryCallback = Box<dyn Fn(ServerEnd<DirectoryMarker>) + Send + Sync>;
 
     struct ComponentDescriptor {
@@ -528,20 +794,29 @@ mod tests {
         pub decl: ComponentDecl,
         pub host_fn: Option<DirectoryCallback>,
         pub runtime_host_fn: Option<DirectoryCallback>,
+        pub package: Option<(String, DirectoryCallback)>,
     }
 
     async fn start_component_manager_with_hub(
         root_component_url: String,
         components: Vec<ComponentDescriptor>,
-    ) -> (Arc<model::Model>, DirectoryProxy) {
-        await!(start_component_manager_with_hub_and_hooks(root_component_url, components, vec![]))
+    ) -> (Arc<model::Model>, DirectoryProxy, Arc<Hub>) {
+        await!(start_component_manager_with_hub_and_hooks(
+            root_component_url,
+            components,
+            vec![],
+            None,
+            None,
+        ))
     }
 
     async fn start_component_manager_with_hub_and_hooks(
         root_component_url: String,
         components: Vec<ComponentDescriptor>,
         mut additional_hooks: model::Hooks,
-    ) -> (Arc<model::Model>, DirectoryProxy) {
+        capability_allowlist: Option<Vec<String>>,
+        capability_redaction: Option<CapabilityRedactionFn>,
+    ) -> (Arc<model::Model>, DirectoryProxy, Arc<Hub>) {
         let resolved_root_component_url = format!("{}_resolved", root_component_url);
         let mut resolver = model::ResolverRegistry::new();
         let mut runner = mocks::MockRunner::new();
@@ -557,6 +832,13 @@ mod tests {
                     .runtime_host_fns
                     .insert(resolved_root_component_url.clone(), runtime_host_fn);
             }
+
+            if let Some((package_url, package_dir_fn)) = component.package {
+                let (package_dir, server_end) =
+                    fidl::endpoints::create_proxy::<DirectoryMarker>().unwrap();
+                package_dir_fn(server_end);
+                mock_resolver.add_component_package(&component.name, package_url, package_dir);
+            }
         }
         resolver.register("test".to_string(), Box::new(mock_resolver));
 
@@ -570,9 +852,18 @@ mod tests {
             ServerEnd::<NodeMarker>::new(server_chan.into()),
         );
 
-        let hub = Arc::new(Hub::new(root_component_url.clone(), root_directory).unwrap());
+        let hub = Arc::new(
+            Hub::new(
+                root_component_url.clone(),
+                root_directory,
+                &model::ModelConfig::default(),
+                capability_allowlist,
+                capability_redaction,
+            )
+            .unwrap(),
+        );
         let mut hooks: model::Hooks = Vec::new();
-        hooks.push(hub);
+        hooks.push(hub.clone());
         hooks.append(&mut additional_hooks);
         let model = Arc::new(model::Model::new(model::ModelParams {
             framework_services: Arc::new(mocks::MockFrameworkServiceHost::new()),
@@ -591,13 +882,13 @@ mod tests {
             .into_proxy()
             .expect("failed to create directory proxy");
 
-        (model, hub_proxy)
+        (model, hub_proxy, hub)
     }
 
     #[fuchsia_async::run_singlethreaded(test)]
     async fn hub_basic() {
         let root_component_url = "test:///root".to_string();
-        let (_model, hub_proxy) = await!(start_component_manager_with_hub(
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
             root_component_url.clone(),
             vec![
                 ComponentDescriptor {
@@ -612,12 +903,14 @@ mod tests {
                     },
                     host_fn: None,
                     runtime_host_fn: None,
+                    package: None,
                 },
                 ComponentDescriptor {
                     name: "a".to_string(),
                     decl: ComponentDecl { children: vec![], ..default_component_decl() },
                     host_fn: None,
                     runtime_host_fn: None,
+                    package: None,
                 },
             ],
         ));
@@ -630,10 +923,200 @@ mod tests {
         assert_eq!("test:///a", await!(read_file(&hub_proxy, "self/children/a/url")));
     }
 
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_open_async_rejects_excessive_path_segments() {
+        let abs_moniker = model::AbsoluteMoniker::root();
+        let mut instances_map = HashMap::new();
+        let instance_controlled = Hub::add_instance_if_necessary(
+            &abs_moniker,
+            "test:///root".to_string(),
+            &mut instances_map,
+        )
+        .expect("Failed to add instance")
+        .expect("Did not create directory.");
+        fasync::spawn(async move {
+            let _ = await!(instance_controlled);
+        });
+
+        let hub_capability = HubCapability::new(
+            abs_moniker,
+            CapabilityPath::try_from("/hub").unwrap(),
+            Arc::new(Mutex::new(instances_map)),
+        );
+
+        let excessive_path =
+            iter::repeat("a").take(MAX_PATH_SEGMENTS + 1).collect::<Vec<_>>().join("/");
+        let (_client_chan, server_chan) = zx::Channel::create().unwrap();
+        let res =
+            await!(hub_capability.open_async(OPEN_RIGHT_READABLE, 0, excessive_path, server_chan));
+
+        match res {
+            Err(ModelError::PathTooLong { .. }) => {}
+            other => panic!("Expected a PathTooLong error, got {:?}", other),
+        }
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_open_root_after_construction() {
+        let hub = Hub::new(
+            "test:///root".to_string(),
+            directory::simple::empty(),
+            &model::ModelConfig::default(),
+            None,
+            None,
+        )
+        .expect("Failed to create Hub");
+
+        // `Hub::new` consumes `root_directory`, and no client channel was opened against it
+        // beforehand, so a connection to the hub root can only be obtained through `open_root`.
+        // The root holds `self` plus the `all_instances` and `config` files added by `Hub::new`.
+        let (proxy, server_end) = fidl::endpoints::create_proxy::<DirectoryMarker>().unwrap();
+        await!(hub.open_root(
+            OPEN_RIGHT_READABLE | OPEN_RIGHT_WRITABLE,
+            MODE_TYPE_DIRECTORY,
+            ServerEnd::new(server_end.into_channel()),
+        ))
+        .expect("Failed to open a second connection to the hub root");
+
+        assert_eq!(vec!["all_instances", "config", "self"], await!(list_directory(&proxy)));
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_wait_for_instance() {
+        let root_component_url = "test:///root".to_string();
+        let (model, _hub_proxy, hub) = await!(start_component_manager_with_hub(
+            root_component_url.clone(),
+            vec![
+                ComponentDescriptor {
+                    name: "root".to_string(),
+                    decl: ComponentDecl {
+                        collections: vec![CollectionDecl {
+                            name: "coll".to_string(),
+                            durability: fsys::Durability::Transient,
+                        }],
+                        ..default_component_decl()
+                    },
+                    host_fn: None,
+                    runtime_host_fn: None,
+                    package: None,
+                },
+                ComponentDescriptor {
+                    name: "c".to_string(),
+                    decl: default_component_decl(),
+                    host_fn: None,
+                    runtime_host_fn: None,
+                    package: None,
+                },
+            ],
+        ));
+
+        let child_moniker = model::AbsoluteMoniker::root()
+            .child(model::ChildMoniker::new("c".to_string(), Some("coll".to_string())));
+
+        // Start waiting before the dynamic child even exists, on a separate task so it can
+        // resolve concurrently with the child being added and bound below.
+        let (wait_done_sender, wait_done_receiver) = oneshot::channel();
+        let waiting_hub = hub.clone();
+        let waiting_moniker = child_moniker.clone();
+        fasync::spawn(async move {
+            let res = await!(waiting_hub.wait_for_instance(&waiting_moniker));
+            let _ = wait_done_sender.send(res);
+        });
+
+        await!(model.root_realm.add_dynamic_child(
+            "coll".to_string(),
+            &ChildDecl {
+                name: "c".to_string(),
+                url: "test:///c".to_string(),
+                startup: fsys::StartupMode::Lazy,
+            },
+            &model.hooks,
+        ))
+        .expect("Failed to add dynamic child");
+
+        // The wait only resolves once the child is actually bound, not merely added.
+        await!(model.look_up_and_bind_instance(child_moniker.clone()))
+            .expect("Failed to bind dynamic child");
+
+        await!(wait_done_receiver)
+            .expect("wait_for_instance task was dropped")
+            .expect("wait_for_instance failed");
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_started_at_time() {
+        let root_component_url = "test:///root".to_string();
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
+            root_component_url.clone(),
+            vec![ComponentDescriptor {
+                name: "root".to_string(),
+                decl: ComponentDecl { children: vec![], ..default_component_decl() },
+                host_fn: None,
+                runtime_host_fn: None,
+                package: None,
+            }],
+        ));
+
+        let started_at = await!(read_file(&hub_proxy, "self/exec/started_at"));
+        assert!(!started_at.is_empty());
+        started_at.parse::<i64>().expect("started_at should be a parseable timestamp");
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_all_instances() {
+        let root_component_url = "test:///root".to_string();
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
+            root_component_url.clone(),
+            vec![
+                ComponentDescriptor {
+                    name: "root".to_string(),
+                    decl: ComponentDecl {
+                        children: vec![ChildDecl {
+                            name: "a".to_string(),
+                            url: "test:///a".to_string(),
+                            startup: fsys::StartupMode::Lazy,
+                        }],
+                        ..default_component_decl()
+                    },
+                    host_fn: None,
+                    runtime_host_fn: None,
+                    package: None,
+                },
+                ComponentDescriptor {
+                    name: "a".to_string(),
+                    decl: ComponentDecl { children: vec![], ..default_component_decl() },
+                    host_fn: None,
+                    runtime_host_fn: None,
+                    package: None,
+                },
+            ],
+        ));
+
+        let all_instances = await!(read_file(&hub_proxy, "all_instances"));
+        let monikers: Vec<&str> = all_instances.split('\n').collect();
+        assert!(monikers.contains(&"/"));
+        assert!(monikers.contains(&"/a"));
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_config() {
+        let root_component_url = "test:///root".to_string();
+        let (_model, hub_proxy, _hub) =
+            await!(start_component_manager_with_hub(root_component_url, vec![]));
+
+        let config = await!(read_file(&hub_proxy, "config"));
+        let lines: Vec<&str> = config.lines().collect();
+        assert!(lines.contains(&format!(
+            "list_children_batch_size={}",
+            model::ModelConfig::default().list_children_batch_size
+        )
+        .as_str()));
+    }
+
     #[fuchsia_async::run_singlethreaded(test)]
     async fn hub_out_directory() {
         let root_component_url = "test:///root".to_string();
-        let (_model, hub_proxy) = await!(start_component_manager_with_hub(
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
             root_component_url.clone(),
             vec![ComponentDescriptor {
                 name: "root".to_string(),
@@ -647,6 +1130,7 @@ mod tests {
                 },
                 host_fn: Some(foo_out_dir_fn()),
                 runtime_host_fn: None,
+                package: None,
             }],
         ));
 
@@ -660,7 +1144,7 @@ mod tests {
     #[fuchsia_async::run_singlethreaded(test)]
     async fn hub_runtime_directory() {
         let root_component_url = "test:///root".to_string();
-        let (_model, hub_proxy) = await!(start_component_manager_with_hub(
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
             root_component_url.clone(),
             vec![ComponentDescriptor {
                 name: "root".to_string(),
@@ -683,7 +1167,7 @@ mod tests {
     #[fuchsia_async::run_singlethreaded(test)]
     async fn hub_test_hook_interception() {
         let root_component_url = "test:///root".to_string();
-        let (_model, hub_proxy) = await!(start_component_manager_with_hub_and_hooks(
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub_and_hooks(
             root_component_url.clone(),
             vec![ComponentDescriptor {
                 name: "root".to_string(),
@@ -702,8 +1186,11 @@ mod tests {
                 },
                 host_fn: None,
                 runtime_host_fn: None,
+                package: None,
             }],
             vec![Arc::new(HubInjectionTestHook::new())],
+            None,
+            None,
         ));
 
         let in_dir = io_util::open_directory(
@@ -739,7 +1226,7 @@ mod tests {
     #[fuchsia_async::run_singlethreaded(test)]
     async fn hub_in_directory() {
         let root_component_url = "test:///root".to_string();
-        let (_model, hub_proxy) = await!(start_component_manager_with_hub(
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
             root_component_url.clone(),
             vec![ComponentDescriptor {
                 name: "root".to_string(),
@@ -770,6 +1257,7 @@ mod tests {
                 },
                 host_fn: None,
                 runtime_host_fn: None,
+                package: None,
             }],
         ));
 
@@ -794,10 +1282,135 @@ mod tests {
         );
     }
 
+    #[test]
+    fn capability_path_allowed_does_not_match_across_segment_boundary() {
+        let allowlist = Some(vec!["/data".to_string()]);
+        assert!(Hub::capability_path_allowed(
+            &CapabilityPath::try_from("/data").unwrap(),
+            &allowlist
+        ));
+        assert!(Hub::capability_path_allowed(
+            &CapabilityPath::try_from("/data/foo").unwrap(),
+            &allowlist
+        ));
+        assert!(!Hub::capability_path_allowed(
+            &CapabilityPath::try_from("/database").unwrap(),
+            &allowlist
+        ));
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_in_directory_with_allowlist() {
+        let root_component_url = "test:///root".to_string();
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub_and_hooks(
+            root_component_url.clone(),
+            vec![ComponentDescriptor {
+                name: "root".to_string(),
+                decl: ComponentDecl {
+                    children: vec![ChildDecl {
+                        name: "a".to_string(),
+                        url: "test:///a".to_string(),
+                        startup: fsys::StartupMode::Lazy,
+                    }],
+                    uses: vec![
+                        UseDecl::Service(UseServiceDecl {
+                            source: UseSource::Realm,
+                            source_path: CapabilityPath::try_from("/svc/baz").unwrap(),
+                            target_path: CapabilityPath::try_from("/svc/hippo").unwrap(),
+                        }),
+                        UseDecl::Directory(UseDirectoryDecl {
+                            source: UseSource::Realm,
+                            source_path: CapabilityPath::try_from("/data/foo").unwrap(),
+                            target_path: CapabilityPath::try_from("/data/bar").unwrap(),
+                        }),
+                    ],
+                    ..default_component_decl()
+                },
+                host_fn: None,
+                runtime_host_fn: None,
+                package: None,
+            }],
+            vec![],
+            Some(vec!["/svc".to_string()]),
+            None,
+        ));
+
+        let in_dir = io_util::open_directory(
+            &hub_proxy,
+            &Path::new("self/exec/in"),
+            OPEN_RIGHT_READABLE | OPEN_RIGHT_WRITABLE,
+        )
+        .expect("Failed to open directory");
+        assert_eq!(vec!["svc"], await!(list_directory(&in_dir)));
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_in_directory_with_redaction() {
+        let root_component_url = "test:///root".to_string();
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub_and_hooks(
+            root_component_url.clone(),
+            vec![ComponentDescriptor {
+                name: "root".to_string(),
+                decl: ComponentDecl {
+                    children: vec![ChildDecl {
+                        name: "a".to_string(),
+                        url: "test:///a".to_string(),
+                        startup: fsys::StartupMode::Lazy,
+                    }],
+                    uses: vec![
+                        UseDecl::Service(UseServiceDecl {
+                            source: UseSource::Realm,
+                            source_path: CapabilityPath::try_from("/svc/baz").unwrap(),
+                            target_path: CapabilityPath::try_from("/svc/secret").unwrap(),
+                        }),
+                        UseDecl::Directory(UseDirectoryDecl {
+                            source: UseSource::Realm,
+                            source_path: CapabilityPath::try_from("/data/foo").unwrap(),
+                            target_path: CapabilityPath::try_from("/data/bar").unwrap(),
+                        }),
+                    ],
+                    ..default_component_decl()
+                },
+                host_fn: None,
+                runtime_host_fn: None,
+                package: None,
+            }],
+            vec![],
+            None,
+            Some(Box::new(|path: &CapabilityPath| path.to_string() == "/svc/secret")),
+        ));
+
+        let in_dir = io_util::open_directory(
+            &hub_proxy,
+            &Path::new("self/exec/in"),
+            OPEN_RIGHT_READABLE | OPEN_RIGHT_WRITABLE,
+        )
+        .expect("Failed to open directory");
+        assert_eq!(vec!["data"], await!(list_directory(&in_dir)));
+    }
+
+    #[fuchsia_async::run_singlethreaded(test)]
+    async fn hub_in_directory_with_package() {
+        let root_component_url = "test:///root".to_string();
+        let package_url = "fuchsia-pkg://fuchsia.com/root#meta/root.cm".to_string();
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
+            root_component_url.clone(),
+            vec![ComponentDescriptor {
+                name: "root".to_string(),
+                decl: default_component_decl(),
+                host_fn: None,
+                runtime_host_fn: None,
+                package: Some((package_url.clone(), pkg_dir_fn())),
+            }],
+        ));
+
+        assert_eq!(package_url, await!(read_file(&hub_proxy, "self/exec/pkg_path")));
+    }
+
     #[fuchsia_async::run_singlethreaded(test)]
     async fn hub_expose_directory() {
         let root_component_url = "test:///root".to_string();
-        let (_model, hub_proxy) = await!(start_component_manager_with_hub(
+        let (_model, hub_proxy, _hub) = await!(start_component_manager_with_hub(
             root_component_url.clone(),
             vec![ComponentDescriptor {
                 name: "root".to_string(),
@@ -823,6 +1436,7 @@ mod tests {
                 },
                 host_fn: None,
                 runtime_host_fn: None,
+                package: None,
             }],
         ));
 
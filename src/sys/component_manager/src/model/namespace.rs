@@ -22,6 +22,12 @@ use {
 
 pub struct IncomingNamespace {
     pub package_dir: Option<DirectoryProxy>,
+
+    /// The URL of the package `package_dir` was resolved from, if any. Kept alongside
+    /// `package_dir` so callers can report where a served package directory came from without
+    /// having to separately track a component's resolved URL.
+    pub package_url: Option<String>,
+
     dir_abort_handles: Vec<AbortHandle>,
 }
 
@@ -35,7 +41,7 @@ impl Drop for IncomingNamespace {
 
 impl IncomingNamespace {
     pub fn new(package: Option<fsys::Package>) -> Result<Self, ModelError> {
-        let package_dir = match package {
+        let (package_dir, package_url) = match package {
             Some(package) => {
                 if package.package_dir.is_none() {
                     return Err(ModelError::ComponentInvalid);
@@ -45,11 +51,11 @@ impl IncomingNamespace {
                     .unwrap()
                     .into_proxy()
                     .expect("could not convert package dir to proxy");
-                Some(package_dir)
+                (Some(package_dir), package.package_url)
             }
-            None => None,
+            None => (None, None),
         };
-        Ok(Self { package_dir, dir_abort_handles: vec![] })
+        Ok(Self { package_dir, package_url, dir_abort_handles: vec![] })
     }
 
     /// In addition to populating an fsys::ComponentNamespace, `populate` will start serving and install
@@ -7,9 +7,9 @@ use {
     crate::model::*,
     cm_rust::{ComponentDecl, ExposeDecl, UseDecl},
     failure::{format_err, Error},
-    fidl::endpoints::ServerEnd,
+    fidl::endpoints::{ClientEnd, ServerEnd},
     fidl_fidl_examples_echo::{EchoMarker, EchoRequest, EchoRequestStream},
-    fidl_fuchsia_io::{DirectoryMarker, NodeMarker},
+    fidl_fuchsia_io::{DirectoryMarker, DirectoryProxy, NodeMarker, CLONE_FLAG_SAME_RIGHTS},
     fidl_fuchsia_sys2 as fsys, fuchsia_async as fasync,
     fuchsia_vfs_pseudo_fs::{
         directory::{self, entry::DirectoryEntry},
@@ -100,11 +100,12 @@ fn new_proxy_routing_fn(ty: CapabilityType) -> RoutingFn {
 
 pub struct MockResolver {
     components: HashMap<String, ComponentDecl>,
+    packages: HashMap<String, (String, DirectoryProxy)>,
 }
 
 impl MockResolver {
     pub fn new() -> Self {
-        MockResolver { components: HashMap::new() }
+        MockResolver { components: HashMap::new(), packages: HashMap::new() }
     }
 
     async fn resolve_async(&self, component_url: String) -> Result<fsys::Component, ResolverError> {
@@ -117,16 +118,41 @@ impl MockResolver {
         ))?;
         let fsys_decl =
             fsys::ComponentDecl::try_from(decl.clone()).expect("decl failed conversion");
+        let package = self.packages.get(name).map(|(package_url, package_dir)| {
+            let package_dir = io_util::clone_directory(package_dir, CLONE_FLAG_SAME_RIGHTS)
+                .expect("could not clone package directory");
+            let package_dir = ClientEnd::new(
+                package_dir
+                    .into_channel()
+                    .expect("could not convert directory to channel")
+                    .into_zx_channel(),
+            );
+            fsys::Package {
+                package_url: Some(package_url.clone()),
+                package_dir: Some(package_dir),
+            }
+        });
         Ok(fsys::Component {
             resolved_url: Some(format!("test:///{}_resolved", name)),
             decl: Some(fsys_decl),
-            package: None,
+            package,
         })
     }
 
     pub fn add_component(&mut self, name: &str, component: ComponentDecl) {
         self.components.insert(name.to_string(), component);
     }
+
+    /// Associates a package with `name`, so that resolving it also returns an
+    /// `fsys::Package` cloned from `package_dir`, with the given `package_url`.
+    pub fn add_component_package(
+        &mut self,
+        name: &str,
+        package_url: String,
+        package_dir: DirectoryProxy,
+    ) {
+        self.packages.insert(name.to_string(), (package_url, package_dir));
+    }
 }
 
 impl Resolver for MockResolver {
@@ -140,9 +140,13 @@ pub fn install_hub_if_possible(model_params: &mut ModelParams) -> Result<(), Mod
             &mut iter::empty(),
             ServerEnd::<NodeMarker>::new(out_dir_handle.into()),
         );
-        model_params
-            .hooks
-            .push(Arc::new(Hub::new(model_params.root_component_url.clone(), root_directory)?));
+        model_params.hooks.push(Arc::new(Hub::new(
+            model_params.root_component_url.clone(),
+            root_directory,
+            &model_params.config,
+            None,
+            None,
+        )?));
     };
     Ok(())
 }
@@ -15,11 +15,22 @@ use {
 
 pub struct CBackend<'a, W: io::Write> {
     w: &'a mut W,
+    doxygen: bool,
 }
 
 impl<'a, W: io::Write> CBackend<'a, W> {
     pub fn new(w: &'a mut W) -> Self {
-        CBackend { w }
+        CBackend { w, doxygen: false }
+    }
+
+    /// Like `new`, but renders `[Doc]` attributes as `/** ... */` Doxygen comments instead of
+    /// `//` line comments.
+    pub fn new_doxygen(w: &'a mut W) -> Self {
+        CBackend { w, doxygen: true }
+    }
+
+    fn doc_comment(&self, attrs: &Attrs, tabs: usize) -> String {
+        render_doc_comment(attrs, tabs, self.doxygen)
     }
 }
 
@@ -39,6 +50,33 @@ pub fn get_doc_comment(attrs: &ast::Attrs, tabs: usize) -> String {
     "".to_string()
 }
 
+/// Renders a `[Doc]` attribute as a Doxygen `/** ... */` block comment rather than the plain
+/// `//` line comments that `get_doc_comment` produces.
+pub fn get_doxygen_comment(attrs: &ast::Attrs, tabs: usize) -> String {
+    for attr in attrs.0.iter() {
+        if attr.key == "Doc" {
+            if let Some(ref val) = attr.val {
+                let indent: String = iter::repeat(' ').take(tabs * 4).collect();
+                let body: String = val
+                    .trim_end()
+                    .split("\n")
+                    .map(|line| format!("{} *{}\n", indent, line))
+                    .collect();
+                return format!("{indent}/**\n{body}{indent} */\n", indent = indent, body = body);
+            }
+        }
+    }
+    "".to_string()
+}
+
+fn render_doc_comment(attrs: &ast::Attrs, tabs: usize, doxygen: bool) -> String {
+    if doxygen {
+        get_doxygen_comment(attrs, tabs)
+    } else {
+        get_doc_comment(attrs, tabs)
+    }
+}
+
 fn ty_to_c_str(ast: &ast::BanjoAst, ty: &ast::Ty) -> Result<String, Error> {
     match ty {
         ast::Ty::Bool => Ok(String::from("bool")),
@@ -173,9 +211,10 @@ fn field_to_c_str(
     ident: &Ident,
     indent: &str,
     ast: &ast::BanjoAst,
+    doxygen: bool,
 ) -> Result<String, Error> {
     let mut accum = String::new();
-    accum.push_str(get_doc_comment(attrs, 1).as_str());
+    accum.push_str(render_doc_comment(attrs, 1, doxygen).as_str());
     let prefix = if ty.is_reference() { "" } else { "const " };
     match ty {
         ast::Ty::Vector { ty: ref inner_ty, .. } => {
@@ -525,7 +564,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         ast: &BanjoAst,
     ) -> Result<String, Error> {
         let mut accum = String::new();
-        accum.push_str(get_doc_comment(attributes, 0).as_str());
+        accum.push_str(self.doc_comment(attributes, 0).as_str());
         accum.push_str(
             format!(
                 "#define {name} {value}",
@@ -559,12 +598,12 @@ impl<'a, W: io::Write> CBackend<'a, W> {
             .iter()
             .map(|f| match f.ty {
                 ast::Ty::Vector { .. } => Err(format_err!("unsupported for UnionField: {:?}", f)),
-                _ => field_to_c_str(&f.attributes, &f.ty, &f.ident, "    ", &ast),
+                _ => field_to_c_str(&f.attributes, &f.ty, &f.ident, "    ", &ast, self.doxygen),
             })
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
         let mut accum = String::new();
-        accum.push_str(get_doc_comment(attributes, 0).as_str());
+        accum.push_str(self.doc_comment(attributes, 0).as_str());
         accum.push_str(
             format!(
                 include_str!("templates/c/struct.h"),
@@ -606,11 +645,11 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         let attrs = struct_attrs_to_c_str(attributes);
         let members = fields
             .iter()
-            .map(|f| field_to_c_str(&f.attributes, &f.ty, &f.ident, "    ", &ast))
+            .map(|f| field_to_c_str(&f.attributes, &f.ty, &f.ident, "    ", &ast, self.doxygen))
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
         let mut accum = String::new();
-        accum.push_str(get_doc_comment(attributes, 0).as_str());
+        accum.push_str(self.doc_comment(attributes, 0).as_str());
         accum.push_str(
             format!(
                 include_str!("templates/c/struct.h"),
@@ -663,7 +702,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
             .iter()
             .map(|m| {
                 let mut accum = String::new();
-                accum.push_str(get_doc_comment(&m.attributes, 0).as_str());
+                accum.push_str(self.doc_comment(&m.attributes, 0).as_str());
 
                 let (out_params, return_param) = get_out_params(&m, name, ast)?;
                 let in_params = get_in_params(&m, true, ast)?;
@@ -4,7 +4,9 @@
 
 use {
     crate::ast::{self, BanjoAst, Ident},
-    crate::backends::c::{array_bounds, get_doc_comment, name_buffer, name_size, not_callback},
+    crate::backends::c::{
+        array_bounds, get_doc_comment, get_doxygen_comment, name_buffer, name_size, not_callback,
+    },
     crate::backends::util::to_c_name,
     crate::backends::Backend,
     failure::{format_err, Error},
@@ -18,16 +20,32 @@ pub enum CppSubtype {
     Base,
     Internal,
     Mock,
+    Async,
 }
 
 pub struct CppBackend<'a, W: io::Write> {
     w: &'a mut W,
     subtype: CppSubtype,
+    doxygen: bool,
 }
 
 impl<'a, W: io::Write> CppBackend<'a, W> {
     pub fn new(w: &'a mut W, subtype: CppSubtype) -> Self {
-        CppBackend { w, subtype }
+        CppBackend { w, subtype, doxygen: false }
+    }
+
+    /// Like `new`, but renders `[Doc]` attributes as `/** ... */` Doxygen comments instead of
+    /// `//` line comments.
+    pub fn new_doxygen(w: &'a mut W, subtype: CppSubtype) -> Self {
+        CppBackend { w, subtype, doxygen: true }
+    }
+
+    fn doc_comment(&self, attrs: &ast::Attrs, tabs: usize) -> String {
+        if self.doxygen {
+            get_doxygen_comment(attrs, tabs)
+        } else {
+            get_doc_comment(attrs, tabs)
+        }
     }
 }
 
@@ -360,6 +378,56 @@ fn get_out_args(
     ))
 }
 
+// Builds the argument list used to forward a method's input parameters from an async wrapper
+// into the underlying ProtocolClient call. This mirrors the parameter shape get_in_params
+// produces for a transformed Protocol parameter, splitting it back into its ctx/ops halves
+// rather than collapsing it into the single C-ABI argument get_in_args would produce.
+fn get_async_call_args(m: &ast::Method, ast: &BanjoAst) -> Result<Vec<String>, Error> {
+    Ok(m.in_params
+        .iter()
+        .flat_map(|(name, ty)| {
+            if let ast::Ty::Identifier { id, .. } = ty {
+                if ast.id_to_type(id) == ast::Ty::Protocol && not_callback(ast, id) {
+                    return vec![
+                        format!("{}_ctx", to_c_name(name)),
+                        format!("{}_ops", to_c_name(name)),
+                    ];
+                }
+            }
+            vec![to_c_name(name)]
+        })
+        .collect())
+}
+
+// Returns the C++ types and names of the value(s) a promise resolves with for a method with the
+// given out parameters, using the raw (non-wrapper) types that match the `[Async]` callback ABI.
+fn get_async_out_parts(m: &ast::Method, ast: &BanjoAst) -> Result<Vec<(String, String)>, Error> {
+    m.out_params
+        .iter()
+        .map(|(name, ty)| Ok((ty_to_cpp_str(ast, false, ty)?, to_c_name(name))))
+        .collect()
+}
+
+// Computes the promise value type and the expression used to construct it from the parts
+// returned by get_async_out_parts (or the equivalent for the synchronous call path): no parts
+// resolve to void, a single part is returned bare, and multiple parts are tupled together.
+fn async_value_type_and_expr(parts: &Vec<(String, String)>) -> (String, String) {
+    match parts.len() {
+        0 => ("void".to_string(), "".to_string()),
+        1 => (parts[0].0.clone(), parts[0].1.clone()),
+        _ => (
+            format!(
+                "std::tuple<{}>",
+                parts.iter().map(|(ty, _)| ty.clone()).collect::<Vec<_>>().join(", ")
+            ),
+            format!(
+                "std::make_tuple({})",
+                parts.iter().map(|(_, name)| name.clone()).collect::<Vec<_>>().join(", ")
+            ),
+        ),
+    }
+}
+
 fn get_mock_out_param_types(m: &ast::Method, ast: &BanjoAst) -> Result<String, Error> {
     if m.out_params.is_empty() {
         Ok("void".to_string())
@@ -654,7 +722,7 @@ impl<'a, W: io::Write> CppBackend<'a, W> {
     ) -> Result<String, Error> {
         methods.iter().map(|m| {
             let mut accum = String::new();
-            accum.push_str(get_doc_comment(&m.attributes, 1).as_str());
+            accum.push_str(self.doc_comment(&m.attributes, 1).as_str());
 
             let (out_params, return_param) = get_out_params(&m, name, false, ast)?;
             let in_params = get_in_params(&m, false, false, ast)?;
@@ -720,7 +788,7 @@ impl<'a, W: io::Write> CppBackend<'a, W> {
             .iter()
             .map(|m| {
                 let mut accum = String::new();
-                accum.push_str(get_doc_comment(&m.attributes, 1).as_str());
+                accum.push_str(self.doc_comment(&m.attributes, 1).as_str());
 
                 let (out_params, return_param) = get_out_params(&m, name, true, ast)?;
                 let in_params = get_in_params(&m, true, true, ast)?;
@@ -802,7 +870,7 @@ impl<'a, W: io::Write> CppBackend<'a, W> {
                     include_str!("templates/cpp/interface.h"),
                     protocol_name = to_cpp_name(name.name()),
                     protocol_name_snake = to_c_name(name.name()).as_str(),
-                    protocol_docs = get_doc_comment(attributes, 0),
+                    protocol_docs = self.doc_comment(attributes, 0),
                     constructor_definition = self.codegen_interface_constructor_def(
                         name.name(),
                         attributes,
@@ -831,7 +899,7 @@ impl<'a, W: io::Write> CppBackend<'a, W> {
                     protocol_name = to_cpp_name(name.name()),
                     protocol_name_uppercase = to_c_name(name.name()).to_uppercase(),
                     protocol_name_snake = to_c_name(name.name()).as_str(),
-                    protocol_docs = get_doc_comment(attributes, 0),
+                    protocol_docs = self.doc_comment(attributes, 0),
                     constructor_definition = self.codegen_protocol_constructor_def(
                         name.name(),
                         attributes,
@@ -846,6 +914,269 @@ impl<'a, W: io::Write> CppBackend<'a, W> {
             .map(|x| x.join(""))
     }
 
+    fn codegen_async_defs(
+        &self,
+        name: &str,
+        methods: &Vec<ast::Method>,
+        ast: &BanjoAst,
+    ) -> Result<String, Error> {
+        methods
+            .iter()
+            .map(|m| {
+                let mut accum = String::new();
+                accum.push_str(self.doc_comment(&m.attributes, 1).as_str());
+
+                let in_params = get_in_params(&m, true, true, ast)?;
+                let call_args = get_async_call_args(&m, ast)?;
+                let function_name = to_cpp_name(m.name.as_str());
+
+                if m.attributes.has_attribute("Async") {
+                    let parts = get_async_out_parts(&m, ast)?;
+                    let (value_type, _) = async_value_type_and_expr(&parts);
+
+                    accum.push_str(
+                        format!(
+                            "    fit::promise<{value_type}> {function_name}({params}) {{\n",
+                            value_type = value_type,
+                            function_name = function_name,
+                            params = in_params.join(", ")
+                        )
+                        .as_str(),
+                    );
+                    accum.push_str(
+                        format!(
+                            "        fit::bridge<{value_type}> bridge;\n",
+                            value_type = value_type
+                        )
+                        .as_str(),
+                    );
+                    let args = call_args
+                        .into_iter()
+                        .chain(iter::once(format!(
+                            "&{protocol_name}AsyncClient::On{function_name}Complete",
+                            protocol_name = to_cpp_name(name),
+                            function_name = function_name
+                        )))
+                        .chain(iter::once(format!(
+                            "new fit::completer<{value_type}>(std::move(bridge.completer))",
+                            value_type = value_type
+                        )))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    accum.push_str(
+                        format!(
+                            "        client_.{function_name}({args});\n",
+                            function_name = function_name,
+                            args = args
+                        )
+                        .as_str(),
+                    );
+                    accum.push_str("        return bridge.consumer.promise();\n");
+                } else {
+                    let (skip, return_param) = get_first_param(ast, &m)?;
+                    let skip_amt = if skip { 1 } else { 0 };
+                    let remaining = m.out_params[skip_amt..]
+                        .iter()
+                        .map(|(name, ty)| Ok((ty_to_cpp_str(ast, true, ty)?, to_c_name(name))))
+                        .collect::<Result<Vec<(String, String)>, Error>>()?;
+
+                    let mut parts = Vec::new();
+                    if skip {
+                        parts.push((return_param, "ret".to_string()));
+                    }
+                    for (ty, name) in remaining.iter() {
+                        parts.push((ty.clone(), format!("out_{}", name)));
+                    }
+                    let (value_type, _) = async_value_type_and_expr(&parts);
+
+                    accum.push_str(
+                        format!(
+                            "    fit::promise<{value_type}> {function_name}({params}) {{\n",
+                            value_type = value_type,
+                            function_name = function_name,
+                            params = in_params.join(", ")
+                        )
+                        .as_str(),
+                    );
+                    for (ty, name) in remaining.iter() {
+                        accum.push_str(
+                            format!("        {ty} out_{name};\n", ty = ty, name = name).as_str(),
+                        );
+                    }
+                    let args = call_args
+                        .into_iter()
+                        .chain(remaining.iter().map(|(_, name)| format!("&out_{}", name)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let initial = if skip { "auto ret = " } else { "" };
+                    accum.push_str(
+                        format!(
+                            "        {initial}client_.{function_name}({args});\n",
+                            initial = initial,
+                            function_name = function_name,
+                            args = args
+                        )
+                        .as_str(),
+                    );
+                    let value_expr = match parts.len() {
+                        0 => "".to_string(),
+                        1 => format!("{}", parts[0].1),
+                        _ => {
+                            format!(
+                                "std::make_tuple({})",
+                                parts.iter().map(|(_, name)| name.clone()).collect::<Vec<_>>().join(", ")
+                            )
+                        }
+                    };
+                    accum.push_str(
+                        format!("        return fit::make_ok_promise({});\n", value_expr).as_str(),
+                    );
+                }
+                accum.push_str("    }\n");
+                Ok(accum)
+            })
+            .collect::<Result<Vec<String>, Error>>()
+            .map(|fns| fns.join("\n"))
+    }
+
+    fn codegen_async_trampolines(
+        &self,
+        methods: &Vec<ast::Method>,
+        ast: &BanjoAst,
+    ) -> Result<String, Error> {
+        let text = methods
+            .iter()
+            .filter(|m| m.attributes.has_attribute("Async"))
+            .map(|m| {
+                let function_name = to_cpp_name(m.name.as_str());
+                let parts = get_async_out_parts(&m, ast)?;
+                let (value_type, value_expr) = async_value_type_and_expr(&parts);
+
+                let params = iter::once("void* cookie".to_string())
+                    .chain(parts.iter().map(|(ty, name)| format!("{} {}", ty, name)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                let mut accum = String::new();
+                accum.push_str(
+                    format!(
+                        "    static void On{function_name}Complete({params}) {{\n",
+                        function_name = function_name,
+                        params = params
+                    )
+                    .as_str(),
+                );
+                accum.push_str(
+                    format!(
+                        "        std::unique_ptr<fit::completer<{value_type}>> completer(\n",
+                        value_type = value_type
+                    )
+                    .as_str(),
+                );
+                accum.push_str(
+                    format!(
+                        "            reinterpret_cast<fit::completer<{value_type}>*>(cookie));\n",
+                        value_type = value_type
+                    )
+                    .as_str(),
+                );
+                accum.push_str(
+                    format!(
+                        "        completer->complete_ok({value_expr});\n",
+                        value_expr = value_expr
+                    )
+                    .as_str(),
+                );
+                accum.push_str("    }");
+                Ok(accum)
+            })
+            .collect::<Result<Vec<String>, Error>>()
+            .map(|fns| fns.join("\n\n"))?;
+        Ok(if text.len() > 0 { text + "\n\n" } else { "".to_string() })
+    }
+
+    fn codegen_async_client(
+        &self,
+        name: &str,
+        methods: &Vec<ast::Method>,
+        attributes: &ast::Attrs,
+        ast: &BanjoAst,
+    ) -> Result<String, Error> {
+        Ok(format!(
+            include_str!("templates/cpp/async_client.h"),
+            protocol_name = to_cpp_name(name),
+            protocol_name_snake = to_c_name(name).as_str(),
+            protocol_docs = self.doc_comment(attributes, 0),
+            async_definitions = self.codegen_async_defs(name, methods, ast)?,
+            async_trampolines = self.codegen_async_trampolines(methods, ast)?,
+        ))
+    }
+
+    fn codegen_async_clients(
+        &self,
+        namespace: &Vec<ast::Decl>,
+        ast: &BanjoAst,
+    ) -> Result<String, Error> {
+        namespace
+            .iter()
+            .filter_map(filter_interface)
+            .chain(namespace.iter().filter_map(filter_protocol))
+            .map(|(name, methods, attributes)| {
+                self.codegen_async_client(name.name(), methods, attributes, ast)
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .map(|x| x.join(""))
+    }
+
+    fn codegen_async_includes(
+        &self,
+        namespace: &Vec<ast::Decl>,
+        ast: &BanjoAst,
+    ) -> Result<String, Error> {
+        let mut need_memory_header = false;
+        let mut need_cpp_tuple_header = false;
+
+        namespace
+            .iter()
+            .filter_map(filter_interface)
+            .chain(namespace.iter().filter_map(filter_protocol))
+            .for_each(|(_name, methods, _attributes)| {
+                methods.iter().for_each(|m| {
+                    if m.attributes.has_attribute("Async") {
+                        need_memory_header = true;
+                    }
+                    if m.out_params.len() > 1 {
+                        need_cpp_tuple_header = true;
+                    }
+                });
+            });
+
+        let mut accum = String::new();
+        if need_memory_header {
+            accum.push_str("#include <memory>\n");
+        }
+        if need_cpp_tuple_header {
+            accum.push_str("#include <tuple>\n");
+        }
+        if need_memory_header || need_cpp_tuple_header {
+            accum.push_str("\n");
+        }
+
+        let mut includes = vec!["lib/fit/bridge".to_string(), "lib/fit/promise".to_string()]
+            .into_iter()
+            .chain(
+                ast.namespaces
+                    .iter()
+                    .filter(|n| n.0 != "zx")
+                    .map(|n| n.0.replace('.', "/").replace("ddk", "ddktl")),
+            )
+            .map(|n| format!("#include <{}.h>", n))
+            .collect::<Vec<_>>();
+        includes.sort();
+        accum.push_str(&includes.join("\n"));
+        Ok(accum)
+    }
+
     fn codegen_includes(&self, ast: &BanjoAst) -> Result<String, Error> {
         let mut includes = vec![
             "ddk/driver".to_string(),
@@ -1333,6 +1664,15 @@ impl<'a, W: io::Write> Backend<'a, W> for CppBackend<'a, W> {
                 self.w.write_fmt(format_args!("{}", self.codegen_mock(namespace, &ast)?))?;
                 self.w.write_fmt(format_args!(include_str!("templates/cpp/footer.h")))?;
             }
+            CppSubtype::Async => {
+                self.w.write_fmt(format_args!(
+                    include_str!("templates/cpp/async_header.h"),
+                    includes = self.codegen_async_includes(namespace, &ast)?,
+                    namespace = &ast.primary_namespace,
+                ))?;
+                self.w.write_fmt(format_args!("{}", self.codegen_async_clients(namespace, &ast)?))?;
+                self.w.write_fmt(format_args!(include_str!("templates/cpp/footer.h")))?;
+            }
         }
 
         Ok(())
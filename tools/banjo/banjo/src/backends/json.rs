@@ -0,0 +1,23 @@
+// Copyright 2019 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use {crate::ast::BanjoAst, crate::backends::Backend, failure::Error, std::io};
+
+pub struct JsonIrBackend<'a, W: io::Write> {
+    w: &'a mut W,
+}
+
+impl<'a, W: io::Write> JsonIrBackend<'a, W> {
+    pub fn new(w: &'a mut W) -> Self {
+        JsonIrBackend { w }
+    }
+}
+
+impl<'a, W: io::Write> Backend<'a, W> for JsonIrBackend<'a, W> {
+    fn codegen(&mut self, ast: BanjoAst) -> Result<(), Error> {
+        serde_json::to_writer_pretty(&mut *self.w, &ast)?;
+        self.w.write_fmt(format_args!("\n"))?;
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ use {
 pub enum KernelSubtype {
     Numbers,
     Trace,
+    TraceHints,
 }
 
 pub struct KernelBackend<'a, W: io::Write> {
@@ -35,7 +36,53 @@ fn count_of_natively_returned_out_params(ast: &BanjoAst, method: &ast::Method) -
     }
 }
 
+// Arguments traced for a method are its in_params followed by whichever out_params are not
+// returned natively (see count_of_natively_returned_out_params).
+fn traced_args<'b>(m: &'b ast::Method, ast: &BanjoAst) -> Vec<&'b (String, ast::Ty)> {
+    let skip = count_of_natively_returned_out_params(ast, m);
+    m.in_params.iter().chain(m.out_params.iter().skip(skip)).collect()
+}
+
+// Hex is used for handles and flags, since those are more naturally read as bitfields or
+// opaque values; everything else (including counts) is traced in decimal.
+fn format_hint(name: &str, ty: &ast::Ty) -> &'static str {
+    match ty {
+        ast::Ty::Handle { .. } => "hex",
+        _ if name.to_lowercase().contains("flags") => "hex",
+        _ => "dec",
+    }
+}
+
 impl<'a, W: io::Write> KernelBackend<'a, W> {
+    fn codegen_trace_hints(
+        &self,
+        methods: &Vec<ast::Method>,
+        ast: &BanjoAst,
+    ) -> Result<String, Error> {
+        methods
+            .iter()
+            .filter(|m| !m.attributes.0.iter().any(|x| x.key == "vdsocall"))
+            .enumerate()
+            .map(|(id, m)| {
+                let args = traced_args(m, ast);
+                let nargs = args.len();
+                let hints = args
+                    .iter()
+                    .map(|(name, ty)| format!("\"{}\"", format_hint(name, ty)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Ok(format!(
+                    "{{{id}, {nargs}, \"{fn_name}\", {{{hints}}}}},",
+                    id = id,
+                    nargs = nargs,
+                    fn_name = util::to_c_name(m.name.as_str()),
+                    hints = hints
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .map(|x| x.join("\n"))
+    }
+
     fn codegen_trace(&self, methods: &Vec<ast::Method>, ast: &BanjoAst) -> Result<String, Error> {
         methods
             .iter()
@@ -100,6 +147,9 @@ impl<'a, W: io::Write> Backend<'a, W> for KernelBackend<'a, W> {
                     }
                     match &self.subtype {
                         KernelSubtype::Trace => Some(self.codegen_trace(methods, &ast)),
+                        KernelSubtype::TraceHints => {
+                            Some(self.codegen_trace_hints(methods, &ast))
+                        }
                         KernelSubtype::Numbers => Some(self.codegen_numbers(methods, &ast)),
                     }
                 }
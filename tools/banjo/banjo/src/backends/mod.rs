@@ -6,7 +6,7 @@ use {crate::ast::BanjoAst, failure::Error, std::io};
 
 pub use self::{
     abigen::AbigenBackend, ast::AstBackend, c::CBackend, cpp::CppBackend, cpp::CppSubtype,
-    fidlcat::FidlcatBackend, kernel::KernelBackend, kernel::KernelSubtype,
+    fidlcat::FidlcatBackend, json::JsonIrBackend, kernel::KernelBackend, kernel::KernelSubtype,
     syzkaller::SyzkallerBackend,
 };
 
@@ -15,6 +15,7 @@ mod ast;
 mod c;
 mod cpp;
 mod fidlcat;
+mod json;
 mod kernel;
 mod syzkaller;
 mod util;
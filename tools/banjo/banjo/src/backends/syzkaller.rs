@@ -134,6 +134,37 @@ fn get_dependent_arg_names(attrs: &ast::Attrs) -> (String, Vec<String>) {
     (key_arg_name, value_arg_names)
 }
 
+/// Scans every protocol method for `[Acquires]`/`[Releases]` attributes and returns, keyed by
+/// syzkaller resource name, the syscalls that create the resource and the syscalls that release
+/// it. A method is treated as a creator if it's tagged `[Acquires]` and has a resource-typed out
+/// param, and as a releaser if it's tagged `[Releases]` and has a resource-typed in param.
+fn collect_resource_lifecycle(
+    ast: &BanjoAst,
+    decl_order: &Vec<&Decl>,
+) -> Result<HashMap<String, (Vec<String>, Vec<String>)>, Error> {
+    let mut lifecycle: HashMap<String, (Vec<String>, Vec<String>)> = HashMap::new();
+    for decl in decl_order.iter() {
+        if let Decl::Protocol { attributes: _, name: _, methods } = decl {
+            for m in methods.iter() {
+                let syscall = format!("zx_{}", to_c_name(m.name.as_str()));
+                if m.attributes.has_attribute("Acquires") {
+                    if let Some((_, ty)) = m.out_params.iter().find(|(_, ty)| ast.is_resource(ty))
+                    {
+                        let resource = ty_to_syzkaller_str(ast, ty)?;
+                        lifecycle.entry(resource).or_insert_with(Default::default).0.push(syscall);
+                    }
+                } else if m.attributes.has_attribute("Releases") {
+                    if let Some((_, ty)) = m.in_params.iter().find(|(_, ty)| ast.is_resource(ty)) {
+                        let resource = ty_to_syzkaller_str(ast, ty)?;
+                        lifecycle.entry(resource).or_insert_with(Default::default).1.push(syscall);
+                    }
+                }
+            }
+        }
+    }
+    Ok(lifecycle)
+}
+
 impl<'a, W: io::Write> SyzkallerBackend<'a, W> {
     pub fn new(w: &'a mut W) -> Self {
         SyzkallerBackend {
@@ -326,6 +357,7 @@ impl<'a, W: io::Write> SyzkallerBackend<'a, W> {
         ty: &ast::Ty,
         values: &Vec<ast::Constant>,
         ast: &BanjoAst,
+        lifecycle: &HashMap<String, (Vec<String>, Vec<String>)>,
     ) -> Result<String, Error> {
         let mut special_values = String::new();
         if !values.is_empty() {
@@ -341,9 +373,25 @@ impl<'a, W: io::Write> SyzkallerBackend<'a, W> {
                     .as_str(),
             );
         }
+        let identifier = ty_to_syzkaller_str(ast, &ty).unwrap();
+        let mut lifecycle_comment = String::new();
+        if let Some((creators, releasers)) = lifecycle.get(&identifier) {
+            let mut clauses = Vec::new();
+            if !creators.is_empty() {
+                clauses.push(format!("created by {}", creators.join(", ")));
+            }
+            if !releasers.is_empty() {
+                clauses.push(format!("released by {}", releasers.join(", ")));
+            }
+            if !clauses.is_empty() {
+                lifecycle_comment =
+                    format!("# {identifier}: {clauses}\n", clauses = clauses.join(", "));
+            }
+        }
         Ok(format!(
-            "resource {identifier}[{underlying_type}]{values}",
-            identifier = ty_to_syzkaller_str(ast, &ty).unwrap(),
+            "{lifecycle_comment}resource {identifier}[{underlying_type}]{values}",
+            lifecycle_comment = lifecycle_comment,
+            identifier = identifier,
             underlying_type = ty_to_underlying_str(ast, &ty).unwrap(),
             values = special_values
         ))
@@ -598,12 +646,13 @@ impl<'a, W: io::Write> Backend<'a, W> for SyzkallerBackend<'a, W> {
         ))?;
 
         let decl_order = ast.validate_declaration_deps()?;
+        let lifecycle = collect_resource_lifecycle(&ast, &decl_order)?;
 
         let mut resource_definitions = decl_order
             .iter()
             .filter_map(|decl| match decl {
                 Decl::Resource { attributes: _, ty, values } => {
-                    Some(self.codegen_resource_def(ty, values, &ast))
+                    Some(self.codegen_resource_def(ty, values, &ast, &lifecycle))
                 }
                 _ => None,
             })
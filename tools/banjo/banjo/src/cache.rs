@@ -0,0 +1,58 @@
+// Copyright 2019 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use {
+    failure::Error,
+    std::{
+        collections::hash_map::DefaultHasher,
+        fs,
+        hash::{Hash, Hasher},
+        path::{Path, PathBuf},
+    },
+};
+
+/// Computes a cache key for a codegen invocation from the contents of its input files and the
+/// name of the backend/subtype being run, so that changing either invalidates the cache.
+fn cache_key(inputs: &[String], backend_key: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    for input in inputs {
+        input.hash(&mut hasher);
+    }
+    backend_key.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn cache_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("{}.banjo-cache", key))
+}
+
+/// Runs `generate` to produce codegen output, unless a previous run over the same input file
+/// contents and `backend_key` already produced output under `cache_dir`, in which case the
+/// cached output is returned directly and `generate` is not invoked. Passing `None` for
+/// `cache_dir` always calls `generate` and skips caching entirely.
+pub fn codegen_cached(
+    cache_dir: Option<&Path>,
+    inputs: &[String],
+    backend_key: &str,
+    generate: impl FnOnce() -> Result<Vec<u8>, Error>,
+) -> Result<Vec<u8>, Error> {
+    let cache_dir = match cache_dir {
+        Some(cache_dir) => cache_dir,
+        None => return generate(),
+    };
+    let key = cache_key(inputs, backend_key);
+    let path = cache_path(cache_dir, &key);
+    if let Ok(cached) = fs::read(&path) {
+        return Ok(cached);
+    }
+    let output = generate()?;
+    fs::create_dir_all(cache_dir)?;
+    // Write through a process-unique temp file and rename into place, so a concurrent reader of
+    // `path` (e.g. another banjo invocation racing to fill the same cache entry) only ever sees a
+    // complete file or none at all, never a partial write.
+    let tmp_path = cache_dir.join(format!("{}.tmp.{}", key, std::process::id()));
+    fs::write(&tmp_path, &output)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(output)
+}
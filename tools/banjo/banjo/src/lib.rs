@@ -6,5 +6,7 @@ use crate::parser::Rule;
 
 pub mod ast;
 pub mod backends;
+pub mod cache;
 pub mod fidl;
+pub mod manifest;
 pub mod parser;
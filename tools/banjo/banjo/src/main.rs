@@ -10,12 +10,13 @@ use {
     },
     failure::Error,
     pest::Parser,
-    std::{fs::File, io, io::Read, path::PathBuf, str::FromStr},
+    std::{fs::File, io, io::Read, io::Write, path::PathBuf, str::FromStr},
     structopt::StructOpt,
 };
 
 mod ast;
 mod backends;
+mod cache;
 mod fidl;
 mod parser;
 
@@ -41,6 +42,7 @@ impl FromStr for BackendName {
             "cpp" => Ok(BackendName::Cpp(backends::CppSubtype::Base)),
             "cpp_mock" => Ok(BackendName::Cpp(backends::CppSubtype::Mock)),
             "cpp_i" => Ok(BackendName::Cpp(backends::CppSubtype::Internal)),
+            "cpp_async" => Ok(BackendName::Cpp(backends::CppSubtype::Async)),
             "rust" => Ok(BackendName::Rust),
             "json" => Ok(BackendName::Json),
             "ast" => Ok(BackendName::Ast),
@@ -94,6 +96,12 @@ struct Opt {
     /// Don't include default zx types
     #[structopt(long = "omit-zx")]
     no_zx: bool,
+
+    /// Directory used to cache codegen output, keyed by a hash of the input files and the
+    /// backend/subtype being run. When set, a run whose inputs and backend match a previous
+    /// run reuses the cached output instead of regenerating it.
+    #[structopt(long = "cache-dir", parse(from_os_str))]
+    cache_dir: Option<PathBuf>,
 }
 
 fn main() -> Result<(), Error> {
@@ -159,14 +167,9 @@ fn main() -> Result<(), Error> {
     }
 
     let ast = BanjoAst::parse(pair_vec, fidl_vec)?;
-    let mut output: Box<dyn io::Write> = if let Some(output) = opt.output {
-        Box::new(File::create(output)?)
-    } else {
-        Box::new(io::stdout())
-    };
 
-    if let Some(name) = opt.name {
-        if name != ast.primary_namespace {
+    if let Some(ref name) = opt.name {
+        if name != &ast.primary_namespace {
             eprintln!(
                 "Generated library '{}' did not match --name arguement {}",
                 ast.primary_namespace, name
@@ -175,18 +178,45 @@ fn main() -> Result<(), Error> {
         }
     }
 
-    let mut backend: Box<dyn Backend<_>> = match opt.backend {
-        BackendName::C => Box::new(CBackend::new(&mut output)),
-        BackendName::Cpp(subtype) => Box::new(CppBackend::new(&mut output, subtype)),
-        BackendName::Ast => Box::new(AstBackend::new(&mut output)),
-        BackendName::Abigen => Box::new(AbigenBackend::new(&mut output)),
-        BackendName::Fidlcat => Box::new(FidlcatBackend::new(&mut output)),
-        BackendName::Kernel(subtype) => Box::new(KernelBackend::new(&mut output, subtype)),
-        BackendName::Syzkaller => Box::new(SyzkallerBackend::new(&mut output)),
-        e => {
-            eprintln!("{:?} backend is not yet implemented", e);
-            ::std::process::exit(1);
-        }
+    // Include `no_zx` since it changes the parsed AST (whether `zx.banjo` is included), and
+    // thus the generated output, without changing `cache_inputs`.
+    let backend_key = format!("{:?}/no_zx={}", opt.backend, opt.no_zx);
+    let backend_name = opt.backend;
+    let cache_inputs: Vec<String> = files.iter().cloned().chain(fidl_files).collect();
+    let generated = cache::codegen_cached(
+        opt.cache_dir.as_deref(),
+        &cache_inputs,
+        &backend_key,
+        move || {
+            let mut buf: Vec<u8> = Vec::new();
+            {
+                let mut backend: Box<dyn Backend<_>> = match backend_name {
+                    BackendName::C => Box::new(CBackend::new(&mut buf)),
+                    BackendName::Cpp(subtype) => Box::new(CppBackend::new(&mut buf, subtype)),
+                    BackendName::Ast => Box::new(AstBackend::new(&mut buf)),
+                    BackendName::Abigen => Box::new(AbigenBackend::new(&mut buf)),
+                    BackendName::Fidlcat => Box::new(FidlcatBackend::new(&mut buf)),
+                    BackendName::Kernel(subtype) => {
+                        Box::new(KernelBackend::new(&mut buf, subtype))
+                    }
+                    BackendName::Syzkaller => Box::new(SyzkallerBackend::new(&mut buf)),
+                    BackendName::Json => Box::new(JsonIrBackend::new(&mut buf)),
+                    e => {
+                        eprintln!("{:?} backend is not yet implemented", e);
+                        ::std::process::exit(1);
+                    }
+                };
+                backend.codegen(ast)?;
+            }
+            Ok(buf)
+        },
+    )?;
+
+    let mut output: Box<dyn io::Write> = if let Some(output) = opt.output {
+        Box::new(File::create(output)?)
+    } else {
+        Box::new(io::stdout())
     };
-    backend.codegen(ast)
+    output.write_all(&generated)?;
+    Ok(())
 }
@@ -0,0 +1,109 @@
+// Copyright 2019 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use {
+    crate::{
+        ast::BanjoAst,
+        backends::{self, Backend},
+        parser::{BanjoParser, Rule},
+    },
+    failure::Error,
+    pest::Parser,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// A backend/subtype to run as part of a [`generate_all`] invocation, together with the key and
+/// file extension its output should be written under.
+#[derive(Debug)]
+pub enum Target {
+    C,
+    Cpp(backends::CppSubtype),
+    Abigen,
+}
+
+impl Target {
+    fn key(&self) -> &'static str {
+        match self {
+            Target::C => "c",
+            Target::Cpp(backends::CppSubtype::Base) => "cpp",
+            Target::Cpp(backends::CppSubtype::Internal) => "cpp_i",
+            Target::Cpp(backends::CppSubtype::Mock) => "cpp_mock",
+            Target::Cpp(backends::CppSubtype::Async) => "cpp_async",
+            Target::Abigen => "abigen",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Target::C | Target::Cpp(_) => "h",
+            Target::Abigen => "abigen.out",
+        }
+    }
+
+    fn generate(&self, ast: BanjoAst) -> Result<Vec<u8>, Error> {
+        let mut buf: Vec<u8> = Vec::new();
+        {
+            let mut backend: Box<dyn Backend<_>> = match self {
+                Target::C => Box::new(backends::CBackend::new(&mut buf)),
+                Target::Cpp(backends::CppSubtype::Base) => {
+                    Box::new(backends::CppBackend::new(&mut buf, backends::CppSubtype::Base))
+                }
+                Target::Cpp(backends::CppSubtype::Internal) => {
+                    Box::new(backends::CppBackend::new(&mut buf, backends::CppSubtype::Internal))
+                }
+                Target::Cpp(backends::CppSubtype::Mock) => {
+                    Box::new(backends::CppBackend::new(&mut buf, backends::CppSubtype::Mock))
+                }
+                Target::Cpp(backends::CppSubtype::Async) => {
+                    Box::new(backends::CppBackend::new(&mut buf, backends::CppSubtype::Async))
+                }
+                Target::Abigen => Box::new(backends::AbigenBackend::new(&mut buf)),
+            };
+            backend.codegen(ast)?;
+        }
+        Ok(buf)
+    }
+}
+
+/// One file produced by [`generate_all`], naming the target that produced it and where it was
+/// written.
+#[derive(Debug, PartialEq)]
+pub struct ManifestEntry {
+    pub target: String,
+    pub path: PathBuf,
+}
+
+/// Parses `inputs` (in order, e.g. `zx.banjo` before the library that depends on it) once per
+/// target and writes each target's generated output under `out_dir`, named
+/// `<base_name>.<target key>.<extension>`. This wraps the existing per-backend generation used
+/// by the `banjo` binary so that callers needing several backends for a single input, such as
+/// build integration, don't have to invoke the parser and each backend by hand. Returns a
+/// manifest listing what was generated and where, in the same order as `targets`.
+pub fn generate_all(
+    out_dir: &Path,
+    base_name: &str,
+    inputs: &[&str],
+    targets: &[Target],
+) -> Result<Vec<ManifestEntry>, Error> {
+    fs::create_dir_all(out_dir)?;
+    targets
+        .iter()
+        .map(|target| {
+            let pair_vec = inputs
+                .iter()
+                .map(|input| BanjoParser::parse(Rule::file, input))
+                .collect::<Result<Vec<_>, _>>()?;
+            let ast = BanjoAst::parse(pair_vec, Vec::new())?;
+            let generated = target.generate(ast)?;
+            let file_name =
+                format!("{}.{}.{}", base_name, target.key(), target.extension());
+            let path = out_dir.join(file_name);
+            fs::write(&path, &generated)?;
+            Ok(ManifestEntry { target: target.key().to_string(), path })
+        })
+        .collect()
+}
@@ -0,0 +1,68 @@
+// Copyright 2019 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+#![cfg(test)]
+
+use {banjo_lib::cache::codegen_cached, std::cell::Cell, tempfile::TempDir};
+
+#[test]
+fn cache_hit_skips_regeneration() {
+    let dir = TempDir::new().unwrap();
+    let inputs = vec!["library foo;".to_string()];
+    let calls = Cell::new(0);
+
+    let first = codegen_cached(Some(dir.path()), &inputs, "cpp", || {
+        calls.set(calls.get() + 1);
+        Ok(b"generated once".to_vec())
+    })
+    .unwrap();
+    assert_eq!(first, b"generated once");
+    assert_eq!(calls.get(), 1);
+
+    let second = codegen_cached(Some(dir.path()), &inputs, "cpp", || {
+        calls.set(calls.get() + 1);
+        Ok(b"should not run".to_vec())
+    })
+    .unwrap();
+    assert_eq!(second, b"generated once");
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn cache_miss_after_input_changes() {
+    let dir = TempDir::new().unwrap();
+    let calls = Cell::new(0);
+
+    let original = vec!["library foo;".to_string()];
+    codegen_cached(Some(dir.path()), &original, "cpp", || {
+        calls.set(calls.get() + 1);
+        Ok(b"original".to_vec())
+    })
+    .unwrap();
+    assert_eq!(calls.get(), 1);
+
+    let modified = vec!["library foo; // changed".to_string()];
+    let regenerated = codegen_cached(Some(dir.path()), &modified, "cpp", || {
+        calls.set(calls.get() + 1);
+        Ok(b"regenerated".to_vec())
+    })
+    .unwrap();
+    assert_eq!(regenerated, b"regenerated");
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn no_cache_dir_always_regenerates() {
+    let inputs = vec!["library foo;".to_string()];
+    let calls = Cell::new(0);
+
+    for _ in 0..2 {
+        codegen_cached(None, &inputs, "cpp", || {
+            calls.set(calls.get() + 1);
+            Ok(b"generated".to_vec())
+        })
+        .unwrap();
+    }
+    assert_eq!(calls.get(), 2);
+}
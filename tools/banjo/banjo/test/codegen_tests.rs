@@ -11,6 +11,12 @@ mod c {
 
     codegen_test!(alignment, CBackend, ["banjo/alignment.test.banjo"], "c/alignment.h");
     codegen_test!(attributes, CBackend, ["banjo/attributes.test.banjo"], "c/attributes.h");
+    codegen_test!(
+        attributes_docs,
+        CBackend::new_doxygen,
+        ["banjo/attributes.test.banjo"],
+        "c/attributes-docs.h"
+    );
     codegen_test!(empty, CBackend, ["banjo/empty.test.banjo"], "c/empty.h");
     codegen_test!(enums, CBackend, ["banjo/enums.test.banjo"], "c/enums.h");
     codegen_test!(example_0, CBackend, ["banjo/example-0.test.banjo"], "c/example-0.h");
@@ -77,6 +83,13 @@ mod cpp {
     use banjo_lib::backends::CppSubtype;
 
     codegen_test!(empty, CppBackend, ["banjo/empty.test.banjo"], "cpp/empty.h", CppSubtype::Base);
+    codegen_test!(
+        attributes_docs,
+        CppBackend::new_doxygen,
+        ["banjo/attributes.test.banjo"],
+        "cpp/attributes-docs.h",
+        CppSubtype::Base
+    );
     codegen_test!(
         example_4,
         CppBackend,
@@ -324,6 +337,21 @@ mod cpp {
         "cpp/mock-protocol-vector.h",
         CppSubtype::Mock
     );
+
+    codegen_test!(
+        async_protocol_primitive,
+        CppBackend,
+        ["../zx.banjo", "banjo/protocol-primitive.test.banjo"],
+        "cpp/protocol-primitive-async.h",
+        CppSubtype::Async
+    );
+    codegen_test!(
+        async_interface,
+        CppBackend,
+        ["../zx.banjo", "banjo/interface.test.banjo"],
+        "cpp/interface-async.h",
+        CppSubtype::Async
+    );
 }
 
 mod abigen {
@@ -384,6 +412,14 @@ mod kernel {
         KernelSubtype::Trace
     );
 
+    codegen_test!(
+        trace_hints,
+        KernelBackend,
+        ["banjo/abigen-protocol-basic.test.banjo"],
+        "kernel/trace-hints.inc",
+        KernelSubtype::TraceHints
+    );
+
     codegen_test!(
         numbers_empty,
         KernelBackend,
@@ -448,6 +484,13 @@ mod syzkaller {
         "syzkaller/syzkaller-protocol-resource.txt"
     );
 
+    codegen_test!(
+        syzkaller_protocol_lifecycle,
+        SyzkallerBackend,
+        ["../zx.banjo", "banjo/syzkaller-protocol-lifecycle.test.banjo"],
+        "syzkaller/syzkaller-protocol-lifecycle.txt"
+    );
+
     codegen_test!(
         syzkaller_struct,
         SyzkallerBackend,
@@ -483,3 +526,16 @@ mod syzkaller {
         "syzkaller/syzkaller-protocol-specialized-syscalls.txt"
     );
 }
+
+mod json {
+    use super::*;
+
+    codegen_test!(json_types, JsonIrBackend, ["banjo/json-types.test.banjo"], "json/types.json");
+
+    codegen_test!(
+        json_interface,
+        JsonIrBackend,
+        ["banjo/json-interface.test.banjo"],
+        "json/interface.json"
+    );
+}
@@ -0,0 +1,56 @@
+// Copyright 2019 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+#![cfg(test)]
+
+use {
+    banjo_lib::{
+        ast::BanjoAst,
+        backends::{AbigenBackend, Backend, CBackend, CppBackend, CppSubtype},
+        manifest::{generate_all, Target},
+        parser::{BanjoParser, Rule},
+    },
+    pest::Parser,
+    std::fs,
+    tempfile::TempDir,
+};
+
+fn parse(input: &str) -> BanjoAst {
+    let pair_vec = vec![BanjoParser::parse(Rule::file, input).unwrap()];
+    BanjoAst::parse(pair_vec, Vec::new()).unwrap()
+}
+
+#[test]
+fn generate_all_writes_every_target_and_a_matching_manifest() {
+    let dir = TempDir::new().unwrap();
+    let input = include_str!("banjo/abigen-protocol-basic.test.banjo");
+
+    let entries = generate_all(
+        dir.path(),
+        "abigen_protocol_basic",
+        &[input],
+        &[Target::C, Target::Cpp(CppSubtype::Base), Target::Abigen],
+    )
+    .unwrap();
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].target, "c");
+    assert_eq!(entries[1].target, "cpp");
+    assert_eq!(entries[2].target, "abigen");
+    for entry in &entries {
+        assert!(entry.path.starts_with(dir.path()));
+    }
+
+    let mut c_output = Vec::new();
+    CBackend::new(&mut c_output).codegen(parse(input)).unwrap();
+    assert_eq!(fs::read(&entries[0].path).unwrap(), c_output);
+
+    let mut cpp_output = Vec::new();
+    CppBackend::new(&mut cpp_output, CppSubtype::Base).codegen(parse(input)).unwrap();
+    assert_eq!(fs::read(&entries[1].path).unwrap(), cpp_output);
+
+    let mut abigen_output = Vec::new();
+    AbigenBackend::new(&mut abigen_output).codegen(parse(input)).unwrap();
+    assert_eq!(fs::read(&entries[2].path).unwrap(), abigen_output);
+}
@@ -5,8 +5,10 @@
 #![cfg(test)]
 
 mod ast_tests;
+mod cache_tests;
 mod codegen_tests;
 mod fidl_tests;
+mod manifest_tests;
 
 /// Makes a banjo backend test.
 /// Arguments:
@@ -15,9 +17,18 @@ mod fidl_tests;
 ///     [input files]: vector of path relative input files
 ///     output file: file to compare against generated output
 ///     subtype: optional argument to backend generator
+/// `backend` may also be written as `Backend::ctor` to use a constructor other than `new`.
 #[macro_export]
 macro_rules! codegen_test {
     ( $id:ident, $backend: ident, [ $( $banjo_file:expr),* ], $ast_file:expr $(, $subtype:expr)? ) => {
+        codegen_test!(
+            $id, $backend::new, [ $( $banjo_file),* ], $ast_file $(, $subtype)?
+        );
+    };
+    (
+        $id:ident, $backend: ident :: $ctor:ident, [ $( $banjo_file:expr),* ], $ast_file:expr
+        $(, $subtype:expr)?
+    ) => {
             #[test]
             fn $id() {
                 use pest::Parser;
@@ -36,7 +47,7 @@ macro_rules! codegen_test {
                 let ast = banjo_lib::ast::BanjoAst::parse(pair_vec, Vec::new()).unwrap();
                 {
                     let mut backend: Box<dyn backends::Backend<_>> =
-                        Box::new(backends::$backend::new(&mut output $(, $subtype)?));
+                        Box::new(backends::$backend::$ctor(&mut output $(, $subtype)?));
                     backend.codegen(ast).unwrap();
                 }
                 let output = String::from_utf8(output).unwrap();